@@ -0,0 +1,85 @@
+//! Field parser benchmarks
+//!
+//! Compares the CSV field parsers against the previous `Vec<char>` based implementation.
+//! Run with `cargo bench`.
+
+use libparse::chars::*;
+use libparse::csv;
+use libparse::*;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const ITERATIONS: u32 = 20;
+
+fn char_vec_non_escaped(input: &str) -> PResult<&str, String> {
+    zero_or_more(any_char.iff(|ch| !matches!(*ch, ',' | '"' | '\r' | '\n')))
+        .map(|chars| chars.into_iter().collect())
+        .parse(input)
+}
+
+fn char_vec_escaped(input: &str) -> PResult<&str, String> {
+    right_from_pair(
+        char('"'),
+        left_from_pair(
+            zero_or_more(
+                any_char
+                    .iff(|ch| *ch != '"')
+                    .fallback_on(left_from_pair(char('"'), char('"'))),
+            ),
+            char('"'),
+        ),
+    )
+    .map(|chars| chars.into_iter().collect())
+    .parse(input)
+}
+
+fn char_vec_field(input: &str) -> PResult<&str, String> {
+    char_vec_escaped
+        .fallback_on(char_vec_non_escaped)
+        .parse(input)
+}
+
+fn document() -> String {
+    let mut doc = String::new();
+    for i in 0..20_000 {
+        doc.push_str(&format!(
+            "{},\"\"\"Quoted title number {}\"\", with a comma\",plain author name,en,{}\r\n",
+            i,
+            i,
+            i * 7
+        ));
+    }
+    doc
+}
+
+fn run<F>(name: &str, doc: &str, mut field: F) -> Duration
+where
+    F: FnMut(&str) -> PResult<&str, String>,
+{
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        let mut input = doc;
+        while !input.is_empty() {
+            let (next_input, value) = field(input).unwrap();
+            black_box(value);
+            input = &next_input[1..];
+        }
+    }
+    let elapsed = start.elapsed() / ITERATIONS;
+    println!(
+        "{:<12} {:>10.3} ms/iter",
+        name,
+        elapsed.as_secs_f64() * 1000.0
+    );
+    elapsed
+}
+
+fn main() {
+    let doc = document();
+    let baseline = run("char_vec", &doc, char_vec_field);
+    let current = run("field", &doc, csv::field);
+    println!(
+        "speedup      {:>10.2}x",
+        baseline.as_secs_f64() / current.as_secs_f64()
+    );
+}
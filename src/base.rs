@@ -1,26 +1,206 @@
 //! Core types needed to build a parser
 
+use std::cell::Cell;
+use std::fmt::{self, Debug, Write};
 use std::marker::{PhantomData, Sized};
+use std::ops::Range;
 
 /// Code that indicates where parsing failed
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum ErrorCode {
     Failure(Reason),
     NoInput,
     Char(char),
+    Tag(&'static str),
+    Byte(u8),
+    ByteTag(&'static [u8]),
     LineBreak,
     Predicate,
+    Token,
+    DepthExceeded,
+    /// Parser with the given name was entered again at the same position, which would never
+    /// terminate, reported by parsers guarded with `left_recursion_guard`
+    LeftRecursion(&'static str),
+    Eof,
+    /// Input ended before the parser could decide, reported only by parsers run through
+    /// `Parser::complete_or_partial`
+    Incomplete,
+    /// Alternatives failed at the same position with the given codes, none of which is
+    /// `Expected` itself
+    Expected(Vec<ErrorCode>),
+}
+
+impl ErrorCode {
+    /// Merges two codes of alternatives that failed at the same position
+    pub fn merge(self, other: ErrorCode) -> ErrorCode {
+        let mut codes = self.into_alternatives();
+        for code in other.into_alternatives() {
+            if !codes.contains(&code) {
+                codes.push(code);
+            }
+        }
+        if codes.len() == 1 {
+            codes.pop().expect("one code")
+        } else {
+            ErrorCode::Expected(codes)
+        }
+    }
+
+    /// Returns the name of the kind of this code, like `line_break`, which does not change
+    /// between versions
+    pub fn name(&self) -> &'static str {
+        self.kind().name()
+    }
+
+    /// Returns the kind of this code, which can be matched on without its data
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            ErrorCode::Failure(_) => ErrorKind::Failure,
+            ErrorCode::NoInput => ErrorKind::NoInput,
+            ErrorCode::Char(_) => ErrorKind::Char,
+            ErrorCode::Tag(_) => ErrorKind::Tag,
+            ErrorCode::Byte(_) => ErrorKind::Byte,
+            ErrorCode::ByteTag(_) => ErrorKind::ByteTag,
+            ErrorCode::LineBreak => ErrorKind::LineBreak,
+            ErrorCode::Predicate => ErrorKind::Predicate,
+            ErrorCode::Token => ErrorKind::Token,
+            ErrorCode::DepthExceeded => ErrorKind::DepthExceeded,
+            ErrorCode::LeftRecursion(_) => ErrorKind::LeftRecursion,
+            ErrorCode::Eof => ErrorKind::Eof,
+            ErrorCode::Incomplete => ErrorKind::Incomplete,
+            ErrorCode::Expected(_) => ErrorKind::Expected,
+        }
+    }
+
+    fn into_alternatives(self) -> Vec<ErrorCode> {
+        match self {
+            ErrorCode::Expected(codes) => codes,
+            code => vec![code],
+        }
+    }
+}
+
+/// Kind of an `ErrorCode`, with a numeric value that does not change between versions
+///
+/// New kinds get new values, so matches on kinds need a wildcard arm; values of removed kinds
+/// are never reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u16)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    Failure = 1,
+    NoInput = 2,
+    Char = 3,
+    Tag = 4,
+    Byte = 5,
+    ByteTag = 6,
+    LineBreak = 7,
+    Predicate = 8,
+    Token = 9,
+    DepthExceeded = 10,
+    LeftRecursion = 11,
+    Eof = 12,
+    Incomplete = 13,
+    Expected = 14,
+}
+
+impl ErrorKind {
+    const ALL: [ErrorKind; 14] = [
+        ErrorKind::Failure,
+        ErrorKind::NoInput,
+        ErrorKind::Char,
+        ErrorKind::Tag,
+        ErrorKind::Byte,
+        ErrorKind::ByteTag,
+        ErrorKind::LineBreak,
+        ErrorKind::Predicate,
+        ErrorKind::Token,
+        ErrorKind::DepthExceeded,
+        ErrorKind::LeftRecursion,
+        ErrorKind::Eof,
+        ErrorKind::Incomplete,
+        ErrorKind::Expected,
+    ];
+
+    /// Returns the numeric value of this kind
+    pub fn code(self) -> u16 {
+        self as u16
+    }
+
+    /// Returns the kind with the given numeric value, if there is one
+    pub fn from_code(code: u16) -> Option<ErrorKind> {
+        ErrorKind::ALL.into_iter().find(|kind| kind.code() == code)
+    }
+
+    /// Returns the name of this kind, like `line_break`
+    pub fn name(self) -> &'static str {
+        match self {
+            ErrorKind::Failure => "failure",
+            ErrorKind::NoInput => "no_input",
+            ErrorKind::Char => "char",
+            ErrorKind::Tag => "tag",
+            ErrorKind::Byte => "byte",
+            ErrorKind::ByteTag => "byte_tag",
+            ErrorKind::LineBreak => "line_break",
+            ErrorKind::Predicate => "predicate",
+            ErrorKind::Token => "token",
+            ErrorKind::DepthExceeded => "depth_exceeded",
+            ErrorKind::LeftRecursion => "left_recursion",
+            ErrorKind::Eof => "eof",
+            ErrorKind::Incomplete => "incomplete",
+            ErrorKind::Expected => "expected",
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorCode::Failure(Reason::InvalidInput { expected }) => {
+                write!(f, "expected {}", expected)
+            }
+            ErrorCode::Failure(Reason::_SystemFailure) => write!(f, "internal failure"),
+            ErrorCode::NoInput => write!(f, "unexpected end of input"),
+            ErrorCode::Char(ch) => write!(f, "expected {:?}", ch),
+            ErrorCode::Tag(tag) => write!(f, "expected {:?}", tag),
+            ErrorCode::Byte(byte) => write!(f, "expected byte 0x{:02x}", byte),
+            ErrorCode::ByteTag(tag) => write!(f, "expected bytes {:?}", tag),
+            ErrorCode::LineBreak => write!(f, "expected line break"),
+            ErrorCode::Predicate => write!(f, "unexpected input"),
+            ErrorCode::Token => write!(f, "unexpected token"),
+            ErrorCode::DepthExceeded => write!(f, "nesting too deep"),
+            ErrorCode::LeftRecursion(name) => write!(f, "left recursion in {}", name),
+            ErrorCode::Eof => write!(f, "expected end of input"),
+            ErrorCode::Incomplete => write!(f, "incomplete input"),
+            ErrorCode::Expected(codes) => {
+                let descriptions = codes
+                    .iter()
+                    .map(|code| {
+                        let description = code.to_string();
+                        match description.strip_prefix("expected ") {
+                            Some(expected) => expected.to_string(),
+                            None => description,
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                write!(f, "expected {}", descriptions.join(" or "))
+            }
+        }
+    }
 }
 
 /// Describes Failure reason
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum Reason {
     _SystemFailure,
     InvalidInput { expected: &'static str },
 }
 
 /// Generic parsing error
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Error<I> {
     pub input: I,
     pub code: ErrorCode,
@@ -40,22 +220,268 @@ pub fn failure(input: I, reason: Reason) -> Self {
         }
     }
 
+    /// Creates an error for input that ended before the parser could decide
+    ///
+    /// Reports `ErrorCode::Incomplete` while parsing streamed input and the given code otherwise
+    pub fn end_of_input(input: I, code: ErrorCode) -> Self {
+        if is_streaming() {
+            Error::new(input, ErrorCode::Incomplete)
+        } else {
+            Error::new(input, code)
+        }
+    }
+
+    /// Indicates whether parsing could succeed if more input was available
+    pub fn is_incomplete(&self) -> bool {
+        self.code == ErrorCode::Incomplete
+    }
+
     /// Indicates whether this error is a failure
+    ///
+    /// Exceeding the nesting depth and left recursion are always failures, so that
+    /// repetitions and defaults do not recover from them, and fallbacks are not tried either
     pub fn is_failure(&self) -> bool {
-        match self.code {
-            ErrorCode::Failure(_) => true,
-            _ => false,
+        matches!(
+            self.code,
+            ErrorCode::Failure(_) | ErrorCode::DepthExceeded | ErrorCode::LeftRecursion(_)
+        )
+    }
+}
+
+impl<I: Input> Error<I> {
+    /// Indicates whether this error occurred further into the input than the other one
+    pub fn is_further_than(&self, other: &Error<I>) -> bool {
+        self.input.input_len() < other.input.input_len()
+    }
+}
+
+thread_local! {
+    static STREAMING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Indicates whether the input being parsed may be followed by more input, i.e. whether a
+/// parser is run through `Parser::complete_or_partial`
+///
+/// Parsers that stop at the end of input must then report `ErrorCode::Incomplete`, both when
+/// they fail and when they succeed but more input could change the result.
+pub fn is_streaming() -> bool {
+    STREAMING.with(Cell::get)
+}
+
+/// Runs the given function when dropped, which restores per-thread parsing state even if a
+/// parser panics in between
+pub(crate) struct OnDrop<F: FnMut()>(pub(crate) F);
+
+impl<F: FnMut()> Drop for OnDrop<F> {
+    fn drop(&mut self) {
+        (self.0)()
+    }
+}
+
+/// Number of characters of the remaining input kept in owned errors
+const EXCERPT_LEN: usize = 32;
+
+/// Beginning of the text, truncated to the length of excerpts kept in errors
+pub(crate) fn excerpt(text: &str) -> &str {
+    match text.char_indices().nth(EXCERPT_LEN) {
+        Some((end, _)) => &text[..end],
+        None => text,
+    }
+}
+
+impl Error<&str> {
+    /// Creates a snapshot of this error that does not borrow the input, given the length of
+    /// the original input
+    pub fn to_owned_error(&self, original_len: usize) -> OwnedError {
+        OwnedError {
+            offset: original_len - self.input.len(),
+            excerpt: excerpt(self.input).to_string(),
+            code: self.code.clone(),
         }
     }
+
+    /// Returns the line and the column, both counted from one, at which the error occurred
+    /// in the original input
+    ///
+    /// Lines are separated by `\n`; columns count characters.
+    pub fn line_column(&self, original: &str) -> (usize, usize) {
+        let before = &original[..original.len() - self.input.len()];
+        let line_start = before.rfind('\n').map_or(0, |pos| pos + 1);
+        let line = before.matches('\n').count() + 1;
+        (line, before[line_start..].chars().count() + 1)
+    }
+
+    /// Renders the error as a JSON object, given the original input
+    ///
+    /// The object has the fields `code` (see `ErrorCode::name`), `message`, `offset` (in
+    /// bytes), `line`, `column` (see `Error::line_column`) and `excerpt` (the beginning of the
+    /// remaining input).
+    pub fn to_json(&self, original: &str) -> String {
+        let mut json = String::new();
+        self.write_json(original, &mut json)
+            .expect("writing to a String cannot fail");
+        json
+    }
+
+    fn write_json(&self, original: &str, out: &mut String) -> fmt::Result {
+        use crate::csv::json::write_string;
+
+        let owned = self.to_owned_error(original.len());
+        let (line, column) = self.line_column(original);
+        out.write_str("{\"code\":")?;
+        write_string(self.code.name(), out)?;
+        out.write_str(",\"message\":")?;
+        write_string(&self.code.to_string(), out)?;
+        write!(
+            out,
+            ",\"offset\":{},\"line\":{},\"column\":{},\"excerpt\":",
+            owned.offset, line, column
+        )?;
+        write_string(&owned.excerpt, out)?;
+        out.write_char('}')
+    }
+}
+
+/// Parsing error that owns its data, so it can be stored or sent across threads after the
+/// input is gone
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct OwnedError {
+    /// Byte offset in the original input at which parsing failed
+    pub offset: usize,
+    /// Beginning of the input remaining at the offset, truncated to a few characters
+    pub excerpt: String,
+    pub code: ErrorCode,
+}
+
+impl fmt::Display for OwnedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "parse error at byte {} near {:?}: {}",
+            self.offset, self.excerpt, self.code
+        )
+    }
 }
 
+impl std::error::Error for OwnedError {}
+
 /// Result type of parsing
 ///
 /// When parsing results in `Ok`, contains the remainder of the input
 /// and the output result of the parsing
 pub type PResult<I, O> = Result<(I, O), Error<I>>;
 
+/// Input types that can be consumed by parsers
+pub trait Input {
+    /// Returns the number of units (bytes for text) left in the input
+    fn input_len(&self) -> usize;
+
+    /// Returns the first `len` units of the input
+    fn prefix(&self, len: usize) -> Self;
+
+    /// Indicates whether both inputs are the same position in the same underlying data
+    ///
+    /// Used to validate memoized results; inputs that cannot tell are never considered the same
+    fn same_position(&self, _other: &Self) -> bool {
+        false
+    }
+}
+
+impl Input for &str {
+    fn input_len(&self) -> usize {
+        self.len()
+    }
+
+    fn prefix(&self, len: usize) -> Self {
+        &self[..len]
+    }
+
+    fn same_position(&self, other: &Self) -> bool {
+        std::ptr::eq(*self, *other)
+    }
+}
+
+impl<T> Input for &[T] {
+    fn input_len(&self) -> usize {
+        self.len()
+    }
+
+    fn prefix(&self, len: usize) -> Self {
+        &self[..len]
+    }
+
+    fn same_position(&self, other: &Self) -> bool {
+        std::ptr::eq(*self, *other)
+    }
+}
+
+/// Inputs that parsers can backtrack on, by taking a checkpoint before parsing and rewinding
+/// to it when parsing fails
+///
+/// Every `Copy` input is its own checkpoint. Inputs that cannot be copied, like token streams
+/// or stateful readers, implement this trait to take part in backtracking combinators.
+pub trait Rewind: Sized {
+    type Checkpoint;
+
+    /// Records the current position of the input
+    fn checkpoint(&self) -> Self::Checkpoint;
+
+    /// Moves the input back to a recorded position
+    fn rewind(self, checkpoint: Self::Checkpoint) -> Self;
+}
+
+impl<I: Copy> Rewind for I {
+    type Checkpoint = I;
+
+    fn checkpoint(&self) -> I {
+        *self
+    }
+
+    fn rewind(self, checkpoint: I) -> I {
+        checkpoint
+    }
+}
+
+/// Byte range consumed by a parser
+///
+/// Both ends are stored as the length of the input remaining at that point, which keeps spans
+/// produced by nested parsers comparable without knowing where parsing started
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Span {
+    from: usize,
+    to: usize,
+}
+
+impl Span {
+    /// Creates a span from the remaining input lengths before and after parsing
+    pub fn new(len_before: usize, len_after: usize) -> Self {
+        Span {
+            from: len_before,
+            to: len_after,
+        }
+    }
+
+    /// Number of bytes covered by this span
+    pub fn len(&self) -> usize {
+        self.from - self.to
+    }
+
+    /// Indicates whether the parser consumed nothing
+    pub fn is_empty(&self) -> bool {
+        self.from == self.to
+    }
+
+    /// Converts this span into a byte range of the original input of the given length
+    pub fn range(&self, original_len: usize) -> Range<usize> {
+        original_len - self.from..original_len - self.to
+    }
+}
+
 /// All parsers should implement this trait
+///
+/// Every provided method requires `Self: Sized`, so that `dyn Parser` stays usable
 pub trait Parser<I, O> {
     /// Parses an input type and returns an output type of a parsing error
     fn parse(&mut self, input: I) -> PResult<I, O>;
@@ -89,10 +515,148 @@ fn and_then_map<F, P2, O2>(self, map_fn: F) -> AndThenMap<Self, F, O, P2>
         }
     }
 
+    /// Like `and_then_map`, but the produced parser is applied only once, so it may move
+    /// captured values out instead of cloning them
+    fn and_then_map_once<F, P2, O2>(self, map_fn: F) -> AndThenMapOnce<Self, F, O, P2>
+    where
+        F: FnMut(O) -> P2,
+        P2: ParserOnce<I, O2>,
+        Self: Sized,
+    {
+        AndThenMapOnce {
+            first: self,
+            map_fn,
+            phantom1: PhantomData,
+            phantom2: PhantomData,
+        }
+    }
+
+    /// Moves this parser to a new one that passes the result of parsing, along with the
+    /// remaining input, to a function that decides the final result
+    fn flat_map<F, O2>(self, map_fn: F) -> FlatMap<Self, F, O>
+    where
+        F: FnMut(I, O) -> PResult<I, O2>,
+        Self: Sized,
+    {
+        FlatMap {
+            parser: self,
+            map_fn,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Borrows this parser so that it can be passed to combinators without being moved
+    ///
+    /// A blanket `Parser` impl for `&mut P` would overlap with the one for closures, so borrowing
+    /// goes through the `ByRef` adapter instead (closures can still be passed as `&mut f`)
+    fn by_ref(&mut self) -> ByRef<'_, Self>
+    where
+        Self: Sized,
+    {
+        ByRef { parser: self }
+    }
+
+    /// Moves this parser to the heap, erasing its type so that it can be named in signatures,
+    /// stored in structs or chosen at runtime
+    fn boxed<'a>(self) -> DynParser<'a, I, O>
+    where
+        Self: Sized + 'a,
+    {
+        let mut parser = self;
+        Box::new(move |input| parser.parse(input))
+    }
+
+    /// Moves this parser to a new one that also outputs the span of the consumed input
+    fn spanned(self) -> Spanned<Self>
+    where
+        I: Input,
+        Self: Sized,
+    {
+        Spanned { parser: self }
+    }
+
+    /// Moves this parser to a new one that parses zero or more occurrences and appends their
+    /// results to the given collection, outputting the number of appended results
+    ///
+    /// Like `fold_many`, an occurrence that consumes no input ends the repetition without
+    /// being appended. The collection is not cleared first, and results appended before a
+    /// failure are kept.
+    fn repeat_into<C>(self, collection: &mut C) -> RepeatInto<'_, Self, C, O>
+    where
+        C: Extend<O>,
+        I: Input + Rewind,
+        Self: Sized,
+    {
+        RepeatInto {
+            parser: self,
+            collection,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Scans the input for all non-overlapping matches of this parser, returning every result
+    /// with the byte range of its match
+    ///
+    /// Scanning resumes after each match; where parsing fails, it resumes one char later.
+    /// Matches that consume no input are ignored.
+    fn parse_all_matches<'a>(&mut self, input: &'a str) -> Vec<(O, Range<usize>)>
+    where
+        Self: Parser<&'a str, O> + Sized,
+    {
+        let mut matches = Vec::new();
+        let mut rem_input = input;
+        while let Some(ch) = rem_input.chars().next() {
+            let start = input.len() - rem_input.len();
+            match Parser::<&'a str, O>::parse(self, rem_input) {
+                Ok((next_input, output)) if next_input.len() < rem_input.len() => {
+                    matches.push((output, start..input.len() - next_input.len()));
+                    rem_input = next_input;
+                }
+                _ => rem_input = &rem_input[ch.len_utf8()..],
+            }
+        }
+        matches
+    }
+
+    /// Moves this parser to a new one that logs every invocation to stderr under the given label
+    ///
+    /// Logging happens only in debug builds; nested labeled parsers are indented by depth
+    fn dbg(self, label: &'static str) -> Dbg<Self>
+    where
+        I: Input + Debug,
+        Self: Sized,
+    {
+        Dbg {
+            parser: self,
+            label,
+        }
+    }
+
+    /// Moves this parser to a new one that is identified by the given name in `Debug` output,
+    /// and whose invocations are recorded under the name while a trace is captured with
+    /// `trace::capture`
+    ///
+    /// Combinators like `pair` return closures, which cannot be printed; naming them is how
+    /// they show up in the `Debug` output of the parsers composed from them.
+    fn named(self, name: &'static str) -> Named<Self>
+    where
+        I: Input,
+        Self: Sized,
+    {
+        Named { parser: self, name }
+    }
+
     /// Moves this parser to a new one that can fallback to a given parser in case this one fails
+    ///
+    /// Incomplete input, exceeding the nesting depth and left recursion are reported without
+    /// trying the fallback parser. When both parsers fail, a failure (see `Error::is_failure`)
+    /// is never replaced by a recoverable error, preferring the failure of the fallback parser;
+    /// otherwise the error that got further into the input is reported, and errors at the same
+    /// position are merged into `ErrorCode::Expected`
     fn fallback_on<P2>(self, fallback_parser: P2) -> Fallback<Self, P2>
     where
         P2: Parser<I, O>,
+        I: Input + Rewind + Clone,
         Self: Sized,
     {
         Fallback {
@@ -101,11 +665,36 @@ fn fallback_on<P2>(self, fallback_parser: P2) -> Fallback<Self, P2>
         }
     }
 
+    /// Moves this parser to a new one that outputs the given default value without consuming any input
+    /// when parsing fails with a recoverable error
+    fn with_default(self, default: O) -> WithDefault<Self, O>
+    where
+        O: Clone,
+        I: Rewind,
+        Self: Sized,
+    {
+        WithDefault {
+            parser: self,
+            default,
+        }
+    }
+
+    /// Moves this parser to a new one that treats the input as a chunk of a longer stream,
+    /// reporting `Completeness::Partial` when the result depends on input that is not
+    /// available yet
+    fn complete_or_partial(self) -> CompleteOrPartial<Self>
+    where
+        I: Rewind,
+        Self: Sized,
+    {
+        CompleteOrPartial { parser: self }
+    }
+
     /// Moves this parser to a new one that accepts the results of parsing only if it satisfies the given predicate
     fn iff<F>(self, predicate: F) -> Predicate<Self, F>
     where
         F: FnMut(&O) -> bool,
-        I: Copy,
+        I: Rewind,
         Self: Sized,
     {
         Predicate {
@@ -120,7 +709,7 @@ fn iff<F>(self, predicate: F) -> Predicate<Self, F>
     fn iff_or_invalid<F>(self, predicate: F) -> Predicate<Self, F>
     where
         F: FnMut(&O) -> bool,
-        I: Copy,
+        I: Rewind,
         Self: Sized,
     {
         Predicate {
@@ -140,11 +729,76 @@ fn parse(&mut self, input: I) -> PResult<I, O> {
     }
 }
 
+/// Parser that can be applied through a shared reference because parsing doesn't mutate it
+///
+/// Pure parsers can be shared between threads, e.g. by calling `parse_ref` from the workers of
+/// a parallel iterator. Closures implementing `Fn` are pure parsers, and so are the adapters
+/// of pure parsers whose functions implement `Fn`.
+///
+/// A closure written inline in a call like `map(|x| ...)` only implements `FnMut`, because its
+/// kind is taken from the bound of the method. Pass a function, or bind the closure to a
+/// variable first, to keep the adapter pure.
+pub trait PureParser<I, O>: Parser<I, O> {
+    /// Parses an input type and returns an output type of a parsing error
+    fn parse_ref(&self, input: I) -> PResult<I, O>;
+}
+
+impl<I, O, F> PureParser<I, O> for F
+where
+    F: Fn(I) -> PResult<I, O>,
+{
+    fn parse_ref(&self, input: I) -> PResult<I, O> {
+        self(input)
+    }
+}
+
+/// Parser that can be applied only once, like a closure that moves captured values into its
+/// result
+pub trait ParserOnce<I, O> {
+    /// Consumes the parser, parsing an input type and returning an output type of a parsing
+    /// error
+    fn parse_once(self, input: I) -> PResult<I, O>;
+}
+
+impl<I, O, F> ParserOnce<I, O> for F
+where
+    F: FnOnce(I) -> PResult<I, O>,
+{
+    fn parse_once(self, input: I) -> PResult<I, O> {
+        self(input)
+    }
+}
+
+/// Parser with an erased type, created by `Parser::boxed`
+///
+/// Unlike `registry::BoxedParser`, which accepts string input of any lifetime, it parses
+/// one input type and is not required to be `Send`.
+pub type DynParser<'a, I, O> = Box<dyn FnMut(I) -> PResult<I, O> + 'a>;
+
 /// Map is a parser that applies a map function on the result of parsing
+#[must_use = "parsers do nothing unless used"]
 pub struct Map<P, F, O1> {
     parser: P,
     map_fn: F,
-    phantom: PhantomData<O1>,
+    phantom: PhantomData<fn(O1)>,
+}
+
+impl<P: Clone, F: Clone, O1> Clone for Map<P, F, O1> {
+    fn clone(&self) -> Self {
+        Map {
+            parser: self.parser.clone(),
+            map_fn: self.map_fn.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<P: Debug, F, O1> Debug for Map<P, F, O1> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Map")
+            .field("parser", &self.parser)
+            .finish_non_exhaustive()
+    }
 }
 
 impl<I, O1, O2, P, F> Parser<I, O2> for Map<P, F, O1>
@@ -160,13 +814,44 @@ fn parse(&mut self, input: I) -> PResult<I, O2> {
     }
 }
 
+impl<I, O1, O2, P, F> PureParser<I, O2> for Map<P, F, O1>
+where
+    P: PureParser<I, O1>,
+    F: Fn(O1) -> O2,
+{
+    fn parse_ref(&self, input: I) -> PResult<I, O2> {
+        let (next_input, result) = self.parser.parse_ref(input)?;
+        Ok((next_input, (self.map_fn)(result)))
+    }
+}
+
 /// AndThenMap is a parser that applies map function on the result of parsing to produce a new parser
 /// for the following input
+#[must_use = "parsers do nothing unless used"]
 pub struct AndThenMap<P1, F, O1, P2> {
     first: P1,
     map_fn: F,
-    phantom1: PhantomData<O1>,
-    phantom2: PhantomData<P2>,
+    phantom1: PhantomData<fn(O1)>,
+    phantom2: PhantomData<fn() -> P2>,
+}
+
+impl<P1: Clone, F: Clone, O1, P2> Clone for AndThenMap<P1, F, O1, P2> {
+    fn clone(&self) -> Self {
+        AndThenMap {
+            first: self.first.clone(),
+            map_fn: self.map_fn.clone(),
+            phantom1: PhantomData,
+            phantom2: PhantomData,
+        }
+    }
+}
+
+impl<P1: Debug, F, O1, P2> Debug for AndThenMap<P1, F, O1, P2> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AndThenMap")
+            .field("first", &self.first)
+            .finish_non_exhaustive()
+    }
 }
 
 impl<I, O1, O2, P1, P2, F> Parser<I, O2> for AndThenMap<P1, F, O1, P2>
@@ -183,7 +868,297 @@ fn parse(&mut self, input: I) -> PResult<I, O2> {
     }
 }
 
+impl<I, O1, O2, P, F> ParserOnce<I, O2> for Map<P, F, O1>
+where
+    P: ParserOnce<I, O1>,
+    F: FnOnce(O1) -> O2,
+{
+    fn parse_once(self, input: I) -> PResult<I, O2> {
+        let (next_input, result) = self.parser.parse_once(input)?;
+        Ok((next_input, (self.map_fn)(result)))
+    }
+}
+
+/// AndThenMapOnce is a parser that applies map function on the result of parsing to produce a
+/// single-use parser for the following input
+#[must_use = "parsers do nothing unless used"]
+pub struct AndThenMapOnce<P1, F, O1, P2> {
+    first: P1,
+    map_fn: F,
+    phantom1: PhantomData<fn(O1)>,
+    phantom2: PhantomData<fn() -> P2>,
+}
+
+impl<P1: Clone, F: Clone, O1, P2> Clone for AndThenMapOnce<P1, F, O1, P2> {
+    fn clone(&self) -> Self {
+        AndThenMapOnce {
+            first: self.first.clone(),
+            map_fn: self.map_fn.clone(),
+            phantom1: PhantomData,
+            phantom2: PhantomData,
+        }
+    }
+}
+
+impl<P1: Debug, F, O1, P2> Debug for AndThenMapOnce<P1, F, O1, P2> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AndThenMapOnce")
+            .field("first", &self.first)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<I, O1, O2, P1, P2, F> Parser<I, O2> for AndThenMapOnce<P1, F, O1, P2>
+where
+    P1: Parser<I, O1>,
+    P2: ParserOnce<I, O2>,
+    F: FnMut(O1) -> P2,
+{
+    fn parse(&mut self, input: I) -> PResult<I, O2> {
+        let (next_input, result) = self.first.parse(input)?;
+        (self.map_fn)(result).parse_once(next_input)
+    }
+}
+
+/// FlatMap is a parser that applies a function returning a parsing result on the result of parsing
+#[must_use = "parsers do nothing unless used"]
+pub struct FlatMap<P, F, O1> {
+    parser: P,
+    map_fn: F,
+    phantom: PhantomData<fn(O1)>,
+}
+
+impl<P: Clone, F: Clone, O1> Clone for FlatMap<P, F, O1> {
+    fn clone(&self) -> Self {
+        FlatMap {
+            parser: self.parser.clone(),
+            map_fn: self.map_fn.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<P: Debug, F, O1> Debug for FlatMap<P, F, O1> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FlatMap")
+            .field("parser", &self.parser)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<I, O1, O2, P, F> Parser<I, O2> for FlatMap<P, F, O1>
+where
+    P: Parser<I, O1>,
+    F: FnMut(I, O1) -> PResult<I, O2>,
+{
+    fn parse(&mut self, input: I) -> PResult<I, O2> {
+        let (next_input, result) = self.parser.parse(input)?;
+        (self.map_fn)(next_input, result)
+    }
+}
+
+impl<I, O1, O2, P, F> PureParser<I, O2> for FlatMap<P, F, O1>
+where
+    P: PureParser<I, O1>,
+    F: Fn(I, O1) -> PResult<I, O2>,
+{
+    fn parse_ref(&self, input: I) -> PResult<I, O2> {
+        let (next_input, result) = self.parser.parse_ref(input)?;
+        (self.map_fn)(next_input, result)
+    }
+}
+
+/// ByRef is a parser that borrows another parser
+#[derive(Debug)]
+#[must_use = "parsers do nothing unless used"]
+pub struct ByRef<'a, P: ?Sized> {
+    parser: &'a mut P,
+}
+
+impl<'a, P: ?Sized> ByRef<'a, P> {
+    /// Borrows the given parser, which may be a trait object
+    pub fn new(parser: &'a mut P) -> Self {
+        ByRef { parser }
+    }
+}
+
+impl<I, O, P> Parser<I, O> for ByRef<'_, P>
+where
+    P: Parser<I, O> + ?Sized,
+{
+    fn parse(&mut self, input: I) -> PResult<I, O> {
+        self.parser.parse(input)
+    }
+}
+
+/// Spanned is a parser that pairs the result of parsing with the span of the consumed input
+#[derive(Debug, Clone)]
+#[must_use = "parsers do nothing unless used"]
+pub struct Spanned<P> {
+    parser: P,
+}
+
+impl<I, O, P> Parser<I, (O, Span)> for Spanned<P>
+where
+    P: Parser<I, O>,
+    I: Input,
+{
+    fn parse(&mut self, input: I) -> PResult<I, (O, Span)> {
+        let len_before = input.input_len();
+        let (next_input, result) = self.parser.parse(input)?;
+        let span = Span::new(len_before, next_input.input_len());
+        Ok((next_input, (result, span)))
+    }
+}
+
+impl<I, O, P> PureParser<I, (O, Span)> for Spanned<P>
+where
+    P: PureParser<I, O>,
+    I: Input,
+{
+    fn parse_ref(&self, input: I) -> PResult<I, (O, Span)> {
+        let len_before = input.input_len();
+        let (next_input, result) = self.parser.parse_ref(input)?;
+        let span = Span::new(len_before, next_input.input_len());
+        Ok((next_input, (result, span)))
+    }
+}
+
+/// RepeatInto is a parser that appends the results of repeated parsing to a collection
+#[must_use = "parsers do nothing unless used"]
+pub struct RepeatInto<'c, P, C, O> {
+    parser: P,
+    collection: &'c mut C,
+    phantom: PhantomData<fn(O)>,
+}
+
+impl<P: Debug, C, O> Debug for RepeatInto<'_, P, C, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RepeatInto")
+            .field("parser", &self.parser)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<I, O, P, C> Parser<I, usize> for RepeatInto<'_, P, C, O>
+where
+    P: Parser<I, O>,
+    C: Extend<O>,
+    I: Input + Rewind,
+{
+    fn parse(&mut self, mut input: I) -> PResult<I, usize> {
+        let mut count = 0;
+        loop {
+            let len = input.input_len();
+            let checkpoint = input.checkpoint();
+            match self.parser.parse(input) {
+                Ok((next_input, _)) if next_input.input_len() == len => {
+                    return Ok((next_input, count))
+                }
+                Ok((next_input, output)) => {
+                    input = next_input;
+                    self.collection.extend(Some(output));
+                    count += 1;
+                }
+                Err(err) if err.is_failure() || err.is_incomplete() => return Err(err),
+                Err(err) => return Ok((err.input.rewind(checkpoint), count)),
+            }
+        }
+    }
+}
+
+/// Number of input units shown in debug logs
+const DBG_PREFIX_LEN: usize = 24;
+
+thread_local! {
+    static DBG_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Dbg is a parser that logs its invocations for debugging purposes
+#[derive(Debug, Clone)]
+#[must_use = "parsers do nothing unless used"]
+pub struct Dbg<P> {
+    parser: P,
+    label: &'static str,
+}
+
+impl<I, O, P> Parser<I, O> for Dbg<P>
+where
+    P: Parser<I, O>,
+    I: Input + Debug,
+{
+    fn parse(&mut self, input: I) -> PResult<I, O> {
+        if !cfg!(debug_assertions) {
+            return self.parser.parse(input);
+        }
+
+        let depth = DBG_DEPTH.with(|cell| cell.replace(cell.get() + 1));
+        let restore = OnDrop(|| DBG_DEPTH.with(|cell| cell.set(depth)));
+        let indent = "  ".repeat(depth);
+        let len = input.input_len();
+        let shown = input.prefix(len.min(DBG_PREFIX_LEN));
+        let ellipsis = if len > DBG_PREFIX_LEN { "..." } else { "" };
+        eprintln!("{}{}: {:?}{}", indent, self.label, shown, ellipsis);
+
+        let result = self.parser.parse(input);
+        drop(restore);
+        match &result {
+            Ok((next_input, _)) => eprintln!(
+                "{}{}: ok, consumed {}",
+                indent,
+                self.label,
+                len - next_input.input_len()
+            ),
+            Err(err) => eprintln!(
+                "{}{}: error {:?} after {}",
+                indent,
+                self.label,
+                err.code,
+                len - err.input.input_len()
+            ),
+        }
+        result
+    }
+}
+
+/// Named is a parser identified by a static name
+///
+/// Invocations are recorded under the name while a trace is captured with `trace::capture`.
+/// Only the name is printed in `Debug` output, so any parser, including closures, can be
+/// printed once it is named.
+#[derive(Clone)]
+#[must_use = "parsers do nothing unless used"]
+pub struct Named<P> {
+    parser: P,
+    name: &'static str,
+}
+
+impl<P> Named<P> {
+    /// Returns the name of the parser
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl<P> Debug for Named<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Named").field(&self.name).finish()
+    }
+}
+
+impl<I, O, P> Parser<I, O> for Named<P>
+where
+    P: Parser<I, O>,
+    I: Input,
+{
+    fn parse(&mut self, input: I) -> PResult<I, O> {
+        crate::trace::record(self.name, input, |input| self.parser.parse(input))
+    }
+}
+
 /// Fallback is a parser that applies a fallback parsing logic in case the primary one fails
+#[derive(Debug, Clone)]
+#[must_use = "parsers do nothing unless used"]
 pub struct Fallback<P1, P2> {
     primary: P1,
     fallback: P2,
@@ -193,15 +1168,111 @@ impl<I, O, P1, P2> Parser<I, O> for Fallback<P1, P2>
 where
     P1: Parser<I, O>,
     P2: Parser<I, O>,
+    I: Input + Rewind + Clone,
+{
+    fn parse(&mut self, input: I) -> PResult<I, O> {
+        parse_with_fallback(
+            |input| self.primary.parse(input),
+            |input| self.fallback.parse(input),
+            input,
+        )
+    }
+}
+
+impl<I, O, P1, P2> PureParser<I, O> for Fallback<P1, P2>
+where
+    P1: PureParser<I, O>,
+    P2: PureParser<I, O>,
+    I: Input + Rewind + Clone,
+{
+    fn parse_ref(&self, input: I) -> PResult<I, O> {
+        parse_with_fallback(
+            |input| self.primary.parse_ref(input),
+            |input| self.fallback.parse_ref(input),
+            input,
+        )
+    }
+}
+
+fn parse_with_fallback<I, O>(
+    primary: impl FnOnce(I) -> PResult<I, O>,
+    fallback: impl FnOnce(I) -> PResult<I, O>,
+    input: I,
+) -> PResult<I, O>
+where
+    I: Input + Rewind + Clone,
+{
+    let checkpoint = input.checkpoint();
+    match primary(input) {
+        // Trying the fallback parser could only recurse in the same way again
+        Err(err) if err.is_incomplete() || is_runaway(&err.code) => Err(err),
+        Err(err) => {
+            match fallback(err.input.clone().rewind(checkpoint)) {
+                // Failures are never replaced by recoverable errors
+                Err(fallback_err) if fallback_err.is_failure() || fallback_err.is_incomplete() => {
+                    Err(fallback_err)
+                }
+                Err(_) if err.is_failure() => Err(err),
+                Err(fallback_err) if err.is_further_than(&fallback_err) => Err(err),
+                Err(fallback_err) if !fallback_err.is_further_than(&err) => Err(Error::new(
+                    fallback_err.input,
+                    err.code.merge(fallback_err.code),
+                )),
+                result => result,
+            }
+        }
+        result => result,
+    }
+}
+
+/// Indicates whether the error comes from a guard of a recursive grammar
+pub(crate) fn is_runaway(code: &ErrorCode) -> bool {
+    matches!(code, ErrorCode::DepthExceeded | ErrorCode::LeftRecursion(_))
+}
+
+/// WithDefault is a parser that recovers from errors by outputting a default value
+#[derive(Debug, Clone)]
+#[must_use = "parsers do nothing unless used"]
+pub struct WithDefault<P, O> {
+    parser: P,
+    default: O,
+}
+
+impl<I, O, P> Parser<I, O> for WithDefault<P, O>
+where
+    P: Parser<I, O>,
+    O: Clone,
+    I: Rewind,
 {
     fn parse(&mut self, input: I) -> PResult<I, O> {
-        self.primary
-            .parse(input)
-            .or_else(|err: Error<I>| self.fallback.parse(err.input))
+        let checkpoint = input.checkpoint();
+        match self.parser.parse(input) {
+            Err(err) if err.is_failure() || err.is_incomplete() => Err(err),
+            Err(err) => Ok((err.input.rewind(checkpoint), self.default.clone())),
+            ok => ok,
+        }
+    }
+}
+
+impl<I, O, P> PureParser<I, O> for WithDefault<P, O>
+where
+    P: PureParser<I, O>,
+    O: Clone,
+    I: Rewind,
+{
+    fn parse_ref(&self, input: I) -> PResult<I, O> {
+        let checkpoint = input.checkpoint();
+        match self.parser.parse_ref(input) {
+            Err(err) if err.is_failure() || err.is_incomplete() => Err(err),
+            Err(err) => Ok((err.input.rewind(checkpoint), self.default.clone())),
+            ok => ok,
+        }
     }
 }
 
 /// Accepts the results of parsing only if it satisfies the given predicate
+#[derive(Clone)]
+#[must_use = "parsers do nothing unless used"]
 pub struct Predicate<P, F> {
     parser: P,
     predicate: F,
@@ -212,23 +1283,96 @@ impl<I, O, P, F> Parser<I, O> for Predicate<P, F>
 where
     P: Parser<I, O>,
     F: FnMut(&O) -> bool,
-    I: Copy,
+    I: Rewind,
 {
     fn parse(&mut self, input: I) -> PResult<I, O> {
+        let checkpoint = input.checkpoint();
         let (next_input, result) = self.parser.parse(input)?;
-        if (self.predicate)(&result) {
-            Ok((next_input, result))
+        let accepted = (self.predicate)(&result);
+        self.check(accepted, next_input, result, checkpoint)
+    }
+}
+
+impl<I, O, P, F> PureParser<I, O> for Predicate<P, F>
+where
+    P: PureParser<I, O>,
+    F: Fn(&O) -> bool,
+    I: Rewind,
+{
+    fn parse_ref(&self, input: I) -> PResult<I, O> {
+        let checkpoint = input.checkpoint();
+        let (next_input, result) = self.parser.parse_ref(input)?;
+        self.check((self.predicate)(&result), next_input, result, checkpoint)
+    }
+}
+
+impl<P: Debug, F> Debug for Predicate<P, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Predicate")
+            .field("parser", &self.parser)
+            .field("assume_invalid", &self.assume_invalid)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<P, F> Predicate<P, F> {
+    fn check<I: Rewind, O>(
+        &self,
+        accepted: bool,
+        next_input: I,
+        result: O,
+        checkpoint: I::Checkpoint,
+    ) -> PResult<I, O> {
+        if accepted {
+            return Ok((next_input, result));
+        }
+        let input = next_input.rewind(checkpoint);
+        if self.assume_invalid {
+            Err(Error::failure(
+                input,
+                Reason::InvalidInput {
+                    expected: "unknown",
+                },
+            ))
         } else {
-            if self.assume_invalid {
-                Err(Error::failure(
-                    input,
-                    Reason::InvalidInput {
-                        expected: "unknown",
-                    },
-                ))
-            } else {
-                Err(Error::new(input, ErrorCode::Predicate))
+            Err(Error::new(input, ErrorCode::Predicate))
+        }
+    }
+}
+
+/// Result of parsing a chunk of streamed input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Completeness<O> {
+    /// Parsing succeeded and more input would not change the result
+    Complete(O),
+    /// Parsing could not be decided without more input
+    Partial,
+}
+
+/// CompleteOrPartial is a parser that distinguishes results limited by the end of input
+#[derive(Debug, Clone)]
+#[must_use = "parsers do nothing unless used"]
+pub struct CompleteOrPartial<P> {
+    parser: P,
+}
+
+impl<I, O, P> Parser<I, Completeness<O>> for CompleteOrPartial<P>
+where
+    P: Parser<I, O>,
+    I: Rewind,
+{
+    fn parse(&mut self, input: I) -> PResult<I, Completeness<O>> {
+        let checkpoint = input.checkpoint();
+        let streaming = STREAMING.with(|cell| cell.replace(true));
+        let restore = OnDrop(|| STREAMING.with(|cell| cell.set(streaming)));
+        let result = self.parser.parse(input);
+        drop(restore);
+        match result {
+            Ok((next_input, output)) => Ok((next_input, Completeness::Complete(output))),
+            Err(err) if err.is_incomplete() => {
+                Ok((err.input.rewind(checkpoint), Completeness::Partial))
             }
+            Err(err) => Err(err),
         }
     }
 }
@@ -0,0 +1,314 @@
+//! Command-line tool for common CSV operations
+
+use libparse::csv::{
+    self, Agg, Column, CsvRecord, Dialect, LineEnding, Projection, StrayQuotes, Writer,
+    WriterOptions,
+};
+use libparse::{Error, OwnedError};
+use std::io::{self, Read, Write};
+use std::process::ExitCode;
+use std::{env, fs};
+
+const USAGE: &str = "\
+Usage: libparse <COMMAND> [OPTIONS] [FILE]
+
+Reads FILE, or the standard input when FILE is missing or `-`.
+
+Commands:
+  validate            Checks that the input is a valid CSV document
+  convert             Rewrites the input as RFC 4180 CSV, or as configured by the output options
+  select <COLUMNS>    Keeps only the comma-separated columns, given by header name or index
+  head                Prints the first records
+  tail                Prints the last records
+  stats <COLUMN>      Prints statistics of a column, given by header name or index
+
+Options:
+  -d, --delimiter <CHAR>  Field delimiter of the input
+  -w, --whitespace        Fields of the input are separated by runs of spaces and tabs
+  -q, --quotes <MODE>     Quotes inside unquoted fields: terminate, literal or error
+  -n, --lines <N>         Number of records to print (head, tail; default 10)
+  -D, --output-delimiter <CHAR>
+                          Field delimiter of the output (convert, select, head, tail)
+  -l, --line-ending <LE>  Line ending of the output: crlf or lf (convert, select, head, tail)
+      --no-final-line-break
+                          Does not terminate the last output record with a line break
+                          (convert, select, head, tail)
+  -h, --help              Prints this message";
+
+/// Command line arguments shared by all commands
+struct Args {
+    command: String,
+    operand: Option<String>,
+    path: Option<String>,
+    dialect: Dialect,
+    output: WriterOptions,
+    lines: usize,
+}
+
+fn delimiter(value: &str) -> Result<char, String> {
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (Some(ch), None) if !matches!(ch, '"' | '\r' | '\n') => Ok(ch),
+        _ => Err(format!("invalid delimiter {:?}", value)),
+    }
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let command = args.next().ok_or("missing command")?;
+    let mut positional = Vec::new();
+    let mut dialect = Dialect::default();
+    let mut output = WriterOptions::default();
+    let mut lines = 10;
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("missing value for {}", name));
+        match arg.as_str() {
+            "-d" | "--delimiter" => dialect.delimiter = delimiter(&value(&arg)?)?,
+            "-w" | "--whitespace" => dialect.whitespace_delimited = true,
+            "-D" | "--output-delimiter" => output.delimiter = delimiter(&value(&arg)?)?,
+            "-l" | "--line-ending" => {
+                output.line_ending = match value(&arg)?.as_str() {
+                    "crlf" => LineEnding::CrLf,
+                    "lf" => LineEnding::Lf,
+                    ending => return Err(format!("invalid line ending {:?}", ending)),
+                };
+            }
+            "--no-final-line-break" => output.final_line_break = false,
+            "-q" | "--quotes" => {
+                dialect.stray_quotes = match value(&arg)?.as_str() {
+                    "terminate" => StrayQuotes::Terminate,
+                    "literal" => StrayQuotes::Literal,
+                    "error" => StrayQuotes::Error,
+                    mode => return Err(format!("invalid quotes mode {:?}", mode)),
+                };
+            }
+            "-n" | "--lines" => {
+                let count = value(&arg)?;
+                lines = count
+                    .parse()
+                    .map_err(|_| format!("invalid number of records {:?}", count))?;
+            }
+            "-" => positional.push(arg),
+            _ if arg.starts_with('-') => return Err(format!("unknown option {}", arg)),
+            _ => positional.push(arg),
+        }
+    }
+
+    let takes_operand = matches!(command.as_str(), "select" | "stats");
+    let mut positional = positional.into_iter();
+    let operand = if takes_operand {
+        Some(
+            positional
+                .next()
+                .ok_or(format!("missing operand of {}", command))?,
+        )
+    } else {
+        None
+    };
+    let path = positional.next().filter(|path| path != "-");
+    if positional.next().is_some() {
+        return Err("too many arguments".to_string());
+    }
+    Ok(Args {
+        command,
+        operand,
+        path,
+        dialect,
+        output,
+        lines,
+    })
+}
+
+fn read_input(path: Option<&str>) -> io::Result<String> {
+    match path {
+        Some(path) => fs::read_to_string(path),
+        None => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input)?;
+            Ok(input)
+        }
+    }
+}
+
+fn column(name: &str) -> Column {
+    match name.parse::<usize>() {
+        Ok(index) => Column::Index(index),
+        Err(_) => Column::from(name),
+    }
+}
+
+fn write_records(
+    out: &mut dyn Write,
+    records: &[CsvRecord],
+    options: &WriterOptions,
+) -> Result<(), String> {
+    let mut writer = Writer::with_options(out, options.clone());
+    writer
+        .write_records(records)
+        .map_err(|err| err.to_string())?;
+    writer.into_inner().flush().map_err(|err| err.to_string())
+}
+
+fn run(args: &Args, input: &str, out: &mut dyn Write) -> Result<(), String> {
+    let parse_error = |err: Error<&str>| -> String {
+        let err: OwnedError = err.to_owned_error(input.len());
+        err.to_string()
+    };
+    let dialect = &args.dialect;
+    match args.command.as_str() {
+        "validate" => {
+            let (_, records) = csv::parse_with(input, dialect).map_err(parse_error)?;
+            let fields = records.first().map_or(0, Vec::len);
+            writeln!(out, "valid: {} records, {} fields", records.len(), fields)
+                .map_err(|err| err.to_string())
+        }
+        "convert" => {
+            let (_, records) = csv::parse_with(input, dialect).map_err(parse_error)?;
+            write_records(out, &records, &args.output)
+        }
+        "select" => {
+            let columns = args.operand.as_deref().unwrap_or_default();
+            let projection = columns
+                .split(',')
+                .map(column)
+                .fold(Projection::new(), Projection::select);
+            let (_, records) =
+                csv::parse_projected_with(input, &projection, dialect).map_err(parse_error)?;
+            write_records(out, &records, &args.output)
+        }
+        "head" => {
+            let (_, records) = csv::head_with(input, args.lines, dialect).map_err(parse_error)?;
+            write_records(out, &records, &args.output)
+        }
+        "tail" => {
+            let (_, records) = csv::tail_with(input, args.lines, dialect).map_err(parse_error)?;
+            write_records(out, &records, &args.output)
+        }
+        "stats" => {
+            let name = args.operand.as_deref().unwrap_or_default();
+            let labels = ["sum", "min", "max", "mean", "distinct"];
+            let aggs = [Agg::Sum, Agg::Min, Agg::Max, Agg::Mean, Agg::CountDistinct];
+            let (_, aggregations) = csv::aggregate_all_with(input, column(name), &aggs, dialect)
+                .map_err(parse_error)?;
+            let mut lines = vec![
+                format!("count: {}", aggregations[0].count),
+                format!("skipped: {}", aggregations[0].skipped),
+            ];
+            for (label, aggregation) in labels.iter().zip(&aggregations) {
+                match aggregation.value {
+                    Some(value) => lines.push(format!("{}: {}", label, value)),
+                    None => lines.push(format!("{}: -", label)),
+                }
+            }
+            writeln!(out, "{}", lines.join("\n")).map_err(|err| err.to_string())
+        }
+        command => Err(format!("unknown command {}", command)),
+    }
+}
+
+fn main() -> ExitCode {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    if args.is_empty() || args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{}", USAGE);
+        return if args.is_empty() {
+            ExitCode::from(2)
+        } else {
+            ExitCode::SUCCESS
+        };
+    }
+    let args = match parse_args(args.into_iter()) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("libparse: {}\n\n{}", message, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    let input = match read_input(args.path.as_deref()) {
+        Ok(input) => input,
+        Err(err) => {
+            eprintln!("libparse: {}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+    match run(&args, &input, &mut io::stdout().lock()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("libparse: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Result<Args, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn output(arguments: &[&str], input: &str) -> Result<String, String> {
+        let mut out = Vec::new();
+        run(&args(arguments)?, input, &mut out)?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn parse_arguments() {
+        let parsed = args(&["select", "-d", ";", "-n", "3", "a,b", "-", "-l", "lf"]).unwrap();
+        assert_eq!(parsed.command, "select");
+        assert_eq!(parsed.operand.as_deref(), Some("a,b"));
+        assert_eq!(parsed.path, None);
+        assert_eq!(parsed.dialect.delimiter, ';');
+        assert_eq!(parsed.lines, 3);
+        assert_eq!(parsed.output.line_ending, LineEnding::Lf);
+
+        let parsed = args(&["convert", "-w", "-q", "literal", "in.csv"]).unwrap();
+        assert!(parsed.dialect.whitespace_delimited);
+        assert_eq!(parsed.dialect.stray_quotes, StrayQuotes::Literal);
+        assert_eq!(parsed.path.as_deref(), Some("in.csv"));
+
+        assert!(args(&[]).is_err());
+        assert!(args(&["stats"]).is_err());
+        assert!(args(&["head", "-d", "\""]).is_err());
+        assert!(args(&["head", "-n"]).is_err());
+        assert!(args(&["head", "-x"]).is_err());
+        assert!(args(&["head", "a", "b"]).is_err());
+    }
+
+    #[test]
+    fn commands() {
+        let input = "name;qty\nx;1\ny;3\nz;\n";
+        assert_eq!(
+            output(&["validate", "-d", ";"], input).unwrap(),
+            "valid: 4 records, 2 fields\n"
+        );
+        assert_eq!(
+            output(&["convert", "-d", ";", "-D", "|", "-l", "lf"], input).unwrap(),
+            "name|qty\nx|1\ny|3\nz|\n"
+        );
+        assert_eq!(
+            output(
+                &["select", "qty", "-d", ";", "--no-final-line-break"],
+                input
+            )
+            .unwrap(),
+            "qty\r\n1\r\n3\r\n"
+        );
+        assert_eq!(
+            output(&["head", "-n", "2", "-d", ";"], input).unwrap(),
+            "name,qty\r\nx,1\r\n"
+        );
+        assert_eq!(
+            output(&["tail", "-n", "1", "-w"], "a  b\n1 2\n").unwrap(),
+            "1,2\r\n"
+        );
+        assert_eq!(
+            output(&["stats", "qty", "-d", ";"], input).unwrap(),
+            "count: 2\nskipped: 1\nsum: 4\nmin: 1\nmax: 3\nmean: 2\ndistinct: 2\n"
+        );
+        assert!(output(&["validate"], "a,b\nc\n")
+            .unwrap_err()
+            .contains("more fields"));
+        assert!(output(&["sort"], input).is_err());
+    }
+}
@@ -0,0 +1,93 @@
+//! Parsers related to byte-level processing of binary input
+
+use crate::{is_streaming, Error, ErrorCode, PResult};
+
+/// Parser generator for parsers that recognize a single byte
+pub fn byte(b: u8) -> impl Fn(&[u8]) -> PResult<&[u8], u8> {
+    move |input: &[u8]| match input.first() {
+        Some(&next) if next == b => Ok((&input[1..], b)),
+        Some(_) => Err(Error::new(input, ErrorCode::Byte(b))),
+        None => Err(Error::end_of_input(input, ErrorCode::NoInput)),
+    }
+}
+
+/// Parser that matches any byte
+pub fn any_byte(input: &[u8]) -> PResult<&[u8], u8> {
+    match input.split_first() {
+        Some((&b, rest)) => Ok((rest, b)),
+        None => Err(Error::end_of_input(input, ErrorCode::NoInput)),
+    }
+}
+
+/// Parser generator for parsers that match exactly `n` bytes
+pub fn take_bytes(n: usize) -> impl Fn(&[u8]) -> PResult<&[u8], &[u8]> {
+    move |input: &[u8]| {
+        if input.len() >= n {
+            Ok((&input[n..], &input[..n]))
+        } else {
+            Err(Error::end_of_input(input, ErrorCode::NoInput))
+        }
+    }
+}
+
+/// Parser generator for parsers that recognize an exact byte sequence
+pub fn tag_bytes(tag: &'static [u8]) -> impl Fn(&[u8]) -> PResult<&[u8], &[u8]> {
+    move |input: &[u8]| match input.strip_prefix(tag) {
+        Some(rest) => Ok((rest, &input[..tag.len()])),
+        None if tag.starts_with(input) => Err(Error::end_of_input(input, ErrorCode::ByteTag(tag))),
+        None => Err(Error::new(input, ErrorCode::ByteTag(tag))),
+    }
+}
+
+/// Parser generator for parsers that match the longest (possibly empty) run of bytes that
+/// satisfy the given predicate
+pub fn take_bytes_while<F>(predicate: F) -> impl Fn(&[u8]) -> PResult<&[u8], &[u8]>
+where
+    F: Fn(u8) -> bool,
+{
+    move |input: &[u8]| match input.iter().position(|&b| !predicate(b)) {
+        Some(len) => Ok((&input[len..], &input[..len])),
+        None if is_streaming() => Err(Error::new(input, ErrorCode::Incomplete)),
+        None => Ok((&input[input.len()..], input)),
+    }
+}
+
+/// Parser generator for parsers that match a non-empty run of bytes that satisfy the given
+/// predicate
+pub fn take_bytes_while1<F>(predicate: F) -> impl Fn(&[u8]) -> PResult<&[u8], &[u8]>
+where
+    F: Fn(u8) -> bool,
+{
+    let take = take_bytes_while(predicate);
+    move |input: &[u8]| match take(input)? {
+        (_, []) => Err(Error::new(input, ErrorCode::Predicate)),
+        ok => Ok(ok),
+    }
+}
+
+macro_rules! int_parsers {
+    ($($ty:ty: $le:ident, $be:ident;)*) => {
+        $(
+            #[doc = concat!("Parser that reads a little-endian `", stringify!($ty), "`")]
+            pub fn $le(input: &[u8]) -> PResult<&[u8], $ty> {
+                let (rest, bytes) = take_bytes(std::mem::size_of::<$ty>())(input)?;
+                Ok((rest, <$ty>::from_le_bytes(bytes.try_into().unwrap())))
+            }
+
+            #[doc = concat!("Parser that reads a big-endian `", stringify!($ty), "`")]
+            pub fn $be(input: &[u8]) -> PResult<&[u8], $ty> {
+                let (rest, bytes) = take_bytes(std::mem::size_of::<$ty>())(input)?;
+                Ok((rest, <$ty>::from_be_bytes(bytes.try_into().unwrap())))
+            }
+        )*
+    };
+}
+
+int_parsers! {
+    u16: le_u16, be_u16;
+    u32: le_u32, be_u32;
+    u64: le_u64, be_u64;
+    i16: le_i16, be_i16;
+    i32: le_i32, be_i32;
+    i64: le_i64, be_i64;
+}
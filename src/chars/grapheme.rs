@@ -0,0 +1,65 @@
+//! Parsers that operate on extended grapheme clusters (user-perceived characters) instead of
+//! `char`s, so that emoji and combining sequences are never split
+
+use super::END_OF_STRING;
+use crate::{Error, ErrorCode, PResult, Parser};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Parser that matches any grapheme cluster
+pub fn any_grapheme(input: &str) -> PResult<&str, &str> {
+    match input.graphemes(true).next() {
+        Some(grapheme) => Ok((&input[grapheme.len()..], grapheme)),
+        None => Err(Error::end_of_input(input, END_OF_STRING)),
+    }
+}
+
+/// Parser generator for parsers that match exactly `n` grapheme clusters
+pub fn take_graphemes(n: usize) -> impl Fn(&str) -> PResult<&str, &str> {
+    move |input: &str| {
+        let mut indices = input.grapheme_indices(true).map(|(i, _)| i);
+        match indices.nth(n) {
+            Some(len) => Ok((&input[len..], &input[..len])),
+            None if count_graphemes(input) == n => Ok((&input[input.len()..], input)),
+            None => Err(Error::end_of_input(input, END_OF_STRING)),
+        }
+    }
+}
+
+/// Parser generator for parsers that match the longest (possibly empty) run of grapheme
+/// clusters that satisfy the given predicate
+pub fn take_graphemes_while<F>(predicate: F) -> impl Fn(&str) -> PResult<&str, &str>
+where
+    F: Fn(&str) -> bool,
+{
+    move |input: &str| {
+        let len = input
+            .grapheme_indices(true)
+            .find(|(_, grapheme)| !predicate(grapheme))
+            .map_or(input.len(), |(i, _)| i);
+        Ok((&input[len..], &input[..len]))
+    }
+}
+
+/// Parser generator for parsers that accept the output of the given parser only if it is at
+/// most `max` grapheme clusters long
+pub fn max_graphemes<'a, P>(
+    max: usize,
+    mut parser: P,
+) -> impl FnMut(&'a str) -> PResult<&'a str, &'a str>
+where
+    P: Parser<&'a str, &'a str>,
+{
+    move |input: &'a str| {
+        let (next_input, text) = parser.parse(input)?;
+        if count_graphemes(text) <= max {
+            Ok((next_input, text))
+        } else {
+            Err(Error::new(input, ErrorCode::Predicate))
+        }
+    }
+}
+
+/// Number of grapheme clusters in the given text
+pub fn count_graphemes(text: &str) -> usize {
+    text.graphemes(true).count()
+}
@@ -0,0 +1,252 @@
+//! Parsers related to character-level processing
+
+use crate::{
+    is_streaming, lexeme, pair, recognize, right_from_pair, Error, ErrorCode, PResult, Parser,
+    Reason,
+};
+
+#[cfg(feature = "unicode-segmentation")]
+pub mod grapheme;
+#[cfg(feature = "regex")]
+mod pattern;
+mod tokens;
+pub mod unicode;
+
+#[cfg(feature = "regex")]
+pub use self::pattern::*;
+pub use self::tokens::*;
+
+pub const END_OF_STRING: ErrorCode = ErrorCode::Char('\0');
+
+/// Parser generator for parsers that recognize a single character
+pub fn char(ch: char) -> impl Fn(&str) -> PResult<&str, char> {
+    move |input: &str| match input.chars().next().map(|next| next == ch) {
+        Some(true) => Ok((&input[ch.len_utf8()..], ch)),
+        Some(false) => Err(Error::new(input, ErrorCode::Char(ch))),
+        None => Err(Error::end_of_input(input, END_OF_STRING)),
+    }
+}
+
+/// Parser generator for parsers that recognize an exact string
+pub fn tag(tag: &'static str) -> impl Fn(&str) -> PResult<&str, &str> {
+    move |input: &str| match input.strip_prefix(tag) {
+        Some(rest) => Ok((rest, &input[..tag.len()])),
+        None if tag.starts_with(input) => Err(Error::end_of_input(input, ErrorCode::Tag(tag))),
+        None => Err(Error::new(input, ErrorCode::Tag(tag))),
+    }
+}
+
+/// Parser that matches any character in a string
+pub fn any_char(input: &str) -> PResult<&str, char> {
+    match input.chars().next() {
+        Some(ch) => Ok((&input[ch.len_utf8()..], ch)),
+        _ => Err(Error::end_of_input(input, END_OF_STRING)),
+    }
+}
+
+/// Parser generator for parsers that match a single character that satisfies the given
+/// predicate
+pub fn satisfy<F>(predicate: F) -> impl Fn(&str) -> PResult<&str, char>
+where
+    F: Fn(char) -> bool,
+{
+    move |input: &str| match input.chars().next() {
+        Some(ch) if predicate(ch) => Ok((&input[ch.len_utf8()..], ch)),
+        Some(_) => Err(Error::new(input, ErrorCode::Predicate)),
+        None => Err(Error::end_of_input(input, END_OF_STRING)),
+    }
+}
+
+/// Parser generator for parsers that match the longest (possibly empty) run of characters
+/// that satisfy the given predicate
+pub fn take_while<F>(predicate: F) -> impl Fn(&str) -> PResult<&str, &str>
+where
+    F: Fn(char) -> bool,
+{
+    move |input: &str| match input.find(|ch| !predicate(ch)) {
+        Some(len) => Ok((&input[len..], &input[..len])),
+        None if is_streaming() => Err(Error::new(input, ErrorCode::Incomplete)),
+        None => Ok((&input[input.len()..], input)),
+    }
+}
+
+/// Parser generator for parsers that match a non-empty run of characters that satisfy
+/// the given predicate
+pub fn take_while1<F>(predicate: F) -> impl Fn(&str) -> PResult<&str, &str>
+where
+    F: Fn(char) -> bool,
+{
+    let take = take_while(predicate);
+    move |input: &str| match take(input)? {
+        (_, "") => Err(Error::new(input, ErrorCode::Predicate)),
+        ok => Ok(ok),
+    }
+}
+
+/// Parser that matches a non-empty run of ASCII digits, returning them as text
+///
+/// Leading zeros are kept, e.g. for identifiers like `00042`
+pub fn digit1(input: &str) -> PResult<&str, &str> {
+    take_while1(|ch| ch.is_ascii_digit())(input)
+}
+
+/// Parser that matches an optionally signed integer, returning it as text
+pub fn integer_text(input: &str) -> PResult<&str, &str> {
+    recognize(pair(sign, digit1)).parse(input)
+}
+
+/// Parser that matches a decimal number with optional fraction and exponent parts, like
+/// `-12.5e3`, returning it as text
+pub fn float_text(input: &str) -> PResult<&str, &str> {
+    let fraction = right_from_pair(char('.'), take_while(|ch| ch.is_ascii_digit()));
+    let exponent = pair(satisfy(|ch| ch == 'e' || ch == 'E'), integer_text);
+    recognize(pair(
+        pair(integer_text, fraction.map(Some).with_default(None)),
+        exponent.map(Some).with_default(None),
+    ))
+    .parse(input)
+}
+
+/// Parser that matches an optionally signed integer and converts it to `i64`
+///
+/// Integers out of range of `i64` are reported as a failure
+pub fn number_i64(input: &str) -> PResult<&str, i64> {
+    let (next_input, text) = integer_text(input)?;
+    match text.parse() {
+        Ok(number) => Ok((next_input, number)),
+        Err(_) => Err(Error::failure(
+            input,
+            Reason::InvalidInput {
+                expected: "integer in the range of i64",
+            },
+        )),
+    }
+}
+
+/// Parser that matches a decimal number as `float_text` does and converts it to `f64`
+pub fn number_f64(input: &str) -> PResult<&str, f64> {
+    let (next_input, text) = float_text(input)?;
+    // The grammar of float_text is a subset of what f64 accepts
+    Ok((next_input, text.parse().expect("valid float syntax")))
+}
+
+fn sign(input: &str) -> PResult<&str, Option<char>> {
+    satisfy(|ch| ch == '+' || ch == '-')
+        .map(Some)
+        .with_default(None)
+        .parse(input)
+}
+
+/// Parser that matches a percent-encoded byte, like `%2F`, as used in URLs
+pub fn percent_byte(input: &str) -> PResult<&str, u8> {
+    let (next_input, _) = char('%')(input)?;
+    match next_input.get(..2) {
+        Some(hex) if hex.bytes().all(|byte| byte.is_ascii_hexdigit()) => Ok((
+            &next_input[2..],
+            u8::from_str_radix(hex, 16).expect("valid hex digits"),
+        )),
+        _ => Err(Error::new(input, ErrorCode::Predicate)),
+    }
+}
+
+/// Decodes percent-encoded text, optionally reading `+` as a space like HTML forms do
+///
+/// Returns `None` when an escape is malformed or the decoded bytes are not valid UTF-8
+pub fn percent_decode(text: &str, plus_as_space: bool) -> Option<String> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(ch) = rest.chars().next() {
+        match ch {
+            '%' => {
+                let (next_rest, byte) = percent_byte(rest).ok()?;
+                bytes.push(byte);
+                rest = next_rest;
+                continue;
+            }
+            '+' if plus_as_space => bytes.push(b' '),
+            _ => bytes.extend_from_slice(&rest.as_bytes()[..ch.len_utf8()]),
+        }
+        rest = &rest[ch.len_utf8()..];
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Parser that succeeds only at the end of input
+pub fn eof(input: &str) -> PResult<&str, ()> {
+    if input.is_empty() && is_streaming() {
+        Err(Error::new(input, ErrorCode::Incomplete))
+    } else if input.is_empty() {
+        Ok((input, ()))
+    } else {
+        Err(Error::new(input, ErrorCode::Eof))
+    }
+}
+
+/// Parser that consumes and returns the entire remaining input
+pub fn rest(input: &str) -> PResult<&str, &str> {
+    if is_streaming() {
+        return Err(Error::new(input, ErrorCode::Incomplete));
+    }
+    Ok((&input[input.len()..], input))
+}
+
+/// Parser that returns the length of the remaining input in bytes without consuming it
+pub fn rest_len(input: &str) -> PResult<&str, usize> {
+    Ok((input, input.len()))
+}
+
+/// Parser that skips any (possibly empty) run of whitespace
+pub fn whitespace(input: &str) -> PResult<&str, &str> {
+    take_while(char::is_whitespace)(input)
+}
+
+/// Parser generator for parsers that recognize an exact string followed by anything matched
+/// by the space consumer
+pub fn symbol<'a, S, SO>(space: S, tag: &'static str) -> impl Parser<&'a str, &'a str>
+where
+    S: Parser<&'a str, SO>,
+{
+    lexeme(space, self::tag(tag))
+}
+
+/// Parser that matches a line break (newline): LF or CRLF
+pub fn line_break(input: &str) -> PResult<&str, &str> {
+    if let Some(rest) = input.strip_prefix('\n') {
+        Ok((rest, "\n"))
+    } else if let Some(rest) = input.strip_prefix("\r\n") {
+        Ok((rest, "\r\n"))
+    } else if input.is_empty() || input == "\r" {
+        Err(Error::end_of_input(input, ErrorCode::LineBreak))
+    } else {
+        Err(Error::new(input, ErrorCode::LineBreak))
+    }
+}
+
+/// Parser that matches everything up to, but not including, the next line break (or the end
+/// of input)
+pub fn till_line_break(input: &str) -> PResult<&str, &str> {
+    let len = match input.find('\n') {
+        Some(lf) => input[..lf].strip_suffix('\r').unwrap_or(&input[..lf]).len(),
+        None if is_streaming() => return Err(Error::new(input, ErrorCode::Incomplete)),
+        None => input.len(),
+    };
+    Ok((&input[len..], &input[..len]))
+}
+
+/// Parser that matches a single line and consumes its line break, returning the line without it
+///
+/// The last line does not need to be terminated; empty input is not a line
+pub fn line(input: &str) -> PResult<&str, &str> {
+    if input.is_empty() {
+        return Err(Error::end_of_input(input, ErrorCode::NoInput));
+    }
+    let (next_input, content) = till_line_break(input)?;
+    let next_input = line_break(next_input).map_or(next_input, |(rest, _)| rest);
+    Ok((next_input, content))
+}
+
+/// Parser that matches a single line, returning it together with its line break
+pub fn line_with_break(input: &str) -> PResult<&str, &str> {
+    let (next_input, _) = line(input)?;
+    Ok((next_input, &input[..input.len() - next_input.len()]))
+}
@@ -0,0 +1,42 @@
+//! Parsers backed by regular expressions, for tokens that are easiest to express as a pattern
+
+use crate::{Error, ErrorCode, PResult};
+use regex::Regex;
+
+/// Compiles a pattern so that it only matches at the start of the input
+fn anchored(pattern: &str) -> Regex {
+    Regex::new(&format!("^(?:{})", pattern))
+        .unwrap_or_else(|err| panic!("invalid regex pattern {:?}: {}", pattern, err))
+}
+
+/// Parser generator for parsers that match a regular expression at the start of the input,
+/// returning the matched text
+///
+/// Panics if the pattern is not a valid regular expression.
+pub fn regex(pattern: &str) -> impl Fn(&str) -> PResult<&str, &str> {
+    let regex = anchored(pattern);
+    move |input: &str| match regex.find(input) {
+        Some(found) => Ok((&input[found.end()..], found.as_str())),
+        None => Err(Error::new(input, ErrorCode::Predicate)),
+    }
+}
+
+/// Parser generator for parsers that match a regular expression at the start of the input,
+/// returning the capture groups, where group 0 is the whole match
+///
+/// Groups that did not participate in the match are `None`. Panics if the pattern is not a
+/// valid regular expression.
+pub fn regex_captures(pattern: &str) -> impl Fn(&str) -> PResult<&str, Vec<Option<&str>>> {
+    let regex = anchored(pattern);
+    move |input: &str| match regex.captures(input) {
+        Some(captures) => {
+            let end = captures.get(0).map_or(0, |found| found.end());
+            let groups = captures
+                .iter()
+                .map(|group| group.map(|group| group.as_str()))
+                .collect();
+            Ok((&input[end..], groups))
+        }
+        None => Err(Error::new(input, ErrorCode::Predicate)),
+    }
+}
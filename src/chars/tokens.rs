@@ -0,0 +1,146 @@
+//! Parsers of common tokens: identifiers, versions, UUIDs and IP addresses
+
+use super::{char, digit1, satisfy, take_while, take_while1};
+use crate::{pair, recognize, right_from_pair, zero_or_more, Error, ErrorCode, PResult, Parser};
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+/// Parser that matches an ASCII identifier: a letter or underscore followed by any number of
+/// letters, digits and underscores
+pub fn identifier(input: &str) -> PResult<&str, &str> {
+    recognize(pair(
+        satisfy(|ch| ch.is_ascii_alphabetic() || ch == '_'),
+        take_while(|ch| ch.is_ascii_alphanumeric() || ch == '_'),
+    ))
+    .parse(input)
+}
+
+/// Semantic version, as defined by Semantic Versioning 2.0.0
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Version<'a> {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    /// Dot-separated pre-release identifiers, like `rc.1`
+    pub pre_release: Option<&'a str>,
+    /// Dot-separated build metadata identifiers, like `build.5`
+    pub build: Option<&'a str>,
+}
+
+/// Parser that matches a version number without leading zeros
+fn version_number(input: &str) -> PResult<&str, u64> {
+    let (next_input, digits) = digit1(input)?;
+    match digits.parse() {
+        Ok(number) if digits == "0" || !digits.starts_with('0') => Ok((next_input, number)),
+        _ => Err(Error::new(input, ErrorCode::Predicate)),
+    }
+}
+
+/// Parser generator for dot-separated identifiers of a version, introduced by the given prefix
+fn version_identifiers(prefix: char, numeric_rules: bool) -> impl Fn(&str) -> PResult<&str, &str> {
+    move |input: &str| {
+        let part = |input| {
+            take_while1(|ch| ch.is_ascii_alphanumeric() || ch == '-')
+                .iff(|part: &&str| {
+                    !numeric_rules
+                        || !part.bytes().all(|byte| byte.is_ascii_digit())
+                        || *part == "0"
+                        || !part.starts_with('0')
+                })
+                .parse(input)
+        };
+        let dotted = recognize(pair(&part, zero_or_more(pair(char('.'), &part))));
+        let mut identifiers = right_from_pair(char(prefix), dotted);
+        identifiers.parse(input)
+    }
+}
+
+/// Parser that matches a semantic version, like `1.2.3-rc.1+build.5`
+pub fn semver(input: &str) -> PResult<&str, Version<'_>> {
+    let core = pair(
+        version_number,
+        pair(
+            right_from_pair(char('.'), version_number),
+            right_from_pair(char('.'), version_number),
+        ),
+    );
+    let (next_input, ((major, (minor, patch)), (pre_release, build))) = pair(
+        core,
+        pair(
+            version_identifiers('-', true).map(Some).with_default(None),
+            version_identifiers('+', false).map(Some).with_default(None),
+        ),
+    )
+    .parse(input)?;
+    Ok((
+        next_input,
+        Version {
+            major,
+            minor,
+            patch,
+            pre_release,
+            build,
+        },
+    ))
+}
+
+/// Parser that matches a UUID in its hyphenated form, like
+/// `123e4567-e89b-12d3-a456-426614174000`, returning its bytes
+pub fn uuid(input: &str) -> PResult<&str, [u8; 16]> {
+    const GROUPS: [usize; 5] = [8, 4, 4, 4, 12];
+    let mut bytes = [0; 16];
+    let mut len = 0;
+    let mut rem_input = input;
+    for (i, group) in GROUPS.into_iter().enumerate() {
+        if i > 0 {
+            (rem_input, _) = char('-')(rem_input).map_err(|err| Error::new(input, err.code))?;
+        }
+        let hex = match rem_input.get(..group) {
+            Some(hex) if hex.bytes().all(|byte| byte.is_ascii_hexdigit()) => hex,
+            _ => return Err(Error::new(input, ErrorCode::Predicate)),
+        };
+        for j in (0..group).step_by(2) {
+            bytes[len] = u8::from_str_radix(&hex[j..j + 2], 16).expect("valid hex digits");
+            len += 1;
+        }
+        rem_input = &rem_input[group..];
+    }
+    Ok((rem_input, bytes))
+}
+
+/// Parser that matches a decimal octet of an IPv4 address, without leading zeros
+fn octet(input: &str) -> PResult<&str, u8> {
+    version_number
+        .iff(|number| *number <= 255)
+        .map(|number| number as u8)
+        .parse(input)
+}
+
+/// Parser that matches an IPv4 address in dotted decimal notation, like `192.168.0.1`
+pub fn ipv4(input: &str) -> PResult<&str, Ipv4Addr> {
+    let (next_input, (a, (b, (c, d)))) = pair(
+        octet,
+        pair(
+            right_from_pair(char('.'), octet),
+            pair(
+                right_from_pair(char('.'), octet),
+                right_from_pair(char('.'), octet),
+            ),
+        ),
+    )
+    .parse(input)?;
+    if next_input.starts_with(|ch: char| ch.is_ascii_digit()) {
+        return Err(Error::new(input, ErrorCode::Predicate));
+    }
+    Ok((next_input, Ipv4Addr::new(a, b, c, d)))
+}
+
+/// Parser that matches an IPv6 address in any of its textual forms, like `2001:db8::1` or
+/// `::ffff:192.0.2.1`
+pub fn ipv6(input: &str) -> PResult<&str, Ipv6Addr> {
+    let (next_input, text) =
+        take_while1(|ch| ch.is_ascii_hexdigit() || ch == ':' || ch == '.')(input)?;
+    match text.parse() {
+        Ok(addr) => Ok((next_input, addr)),
+        Err(_) => Err(Error::new(input, ErrorCode::Predicate)),
+    }
+}
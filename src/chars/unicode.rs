@@ -0,0 +1,57 @@
+//! Parsers of single characters with a given Unicode property
+
+use super::satisfy;
+#[cfg(feature = "unicode-xid")]
+use super::take_while;
+use crate::PResult;
+
+/// Parser that matches a character with the Alphabetic property
+pub fn letter(input: &str) -> PResult<&str, char> {
+    satisfy(char::is_alphabetic)(input)
+}
+
+/// Parser that matches a character with a numeric general category (Nd, Nl or No)
+pub fn numeric(input: &str) -> PResult<&str, char> {
+    satisfy(char::is_numeric)(input)
+}
+
+/// Parser that matches a character that is either alphabetic or numeric
+pub fn alphanumeric(input: &str) -> PResult<&str, char> {
+    satisfy(char::is_alphanumeric)(input)
+}
+
+/// Parser that matches a character with the Lowercase property
+pub fn lowercase(input: &str) -> PResult<&str, char> {
+    satisfy(char::is_lowercase)(input)
+}
+
+/// Parser that matches a character with the Uppercase property
+pub fn uppercase(input: &str) -> PResult<&str, char> {
+    satisfy(char::is_uppercase)(input)
+}
+
+/// Parser that matches a character with the White_Space property
+pub fn white_space(input: &str) -> PResult<&str, char> {
+    satisfy(char::is_whitespace)(input)
+}
+
+/// Parser that matches a character with the XID_Start property
+#[cfg(feature = "unicode-xid")]
+pub fn xid_start(input: &str) -> PResult<&str, char> {
+    satisfy(unicode_xid::UnicodeXID::is_xid_start)(input)
+}
+
+/// Parser that matches a character with the XID_Continue property
+#[cfg(feature = "unicode-xid")]
+pub fn xid_continue(input: &str) -> PResult<&str, char> {
+    satisfy(unicode_xid::UnicodeXID::is_xid_continue)(input)
+}
+
+/// Parser that matches an identifier as defined by Unicode Standard Annex #31: an XID_Start
+/// character followed by any number of XID_Continue characters
+#[cfg(feature = "unicode-xid")]
+pub fn identifier(input: &str) -> PResult<&str, &str> {
+    let (next_input, _) = xid_start(input)?;
+    let (next_input, _) = take_while(unicode_xid::UnicodeXID::is_xid_continue)(next_input)?;
+    Ok((next_input, &input[..input.len() - next_input.len()]))
+}
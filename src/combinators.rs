@@ -1,8 +1,65 @@
 //! Generic parser combinators
 
 use crate::base::*;
+use std::cell::{Cell, RefCell};
+
+thread_local! {
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+    static ACTIVE: RefCell<Vec<(&'static str, usize)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Parser generator for parsers that must consume the entire input
+#[must_use = "parsers do nothing unless used"]
+pub fn all_consuming<P, I, O>(mut parser: P) -> impl Parser<I, O>
+where
+    P: Parser<I, O>,
+    I: Input,
+{
+    move |input: I| match parser.parse(input)? {
+        (next_input, _) if next_input.input_len() > 0 => {
+            Err(Error::new(next_input, ErrorCode::Eof))
+        }
+        ok => Ok(ok),
+    }
+}
+
+/// Parser generator for parsers that always succeed with the given value without consuming input
+#[must_use = "parsers do nothing unless used"]
+pub fn success<I, O>(value: O) -> impl Parser<I, O>
+where
+    O: Clone,
+{
+    move |input: I| Ok((input, value.clone()))
+}
+
+/// Parser generator for parsers that always fail with the given error code
+#[must_use = "parsers do nothing unless used"]
+pub fn fail<I, O>(code: ErrorCode) -> impl Parser<I, O> {
+    move |input: I| Err(Error::new(input, code.clone()))
+}
+
+/// Parser generator for applying a parser only when the given flag is set
+#[must_use = "parsers do nothing unless used"]
+pub fn cond<P, I, O>(flag: bool, mut parser: P) -> impl Parser<I, Option<O>>
+where
+    P: Parser<I, O>,
+{
+    move |input: I| {
+        if flag {
+            parser
+                .parse(input)
+                .map(|(next_input, output)| (next_input, Some(output)))
+        } else {
+            Ok((input, None))
+        }
+    }
+}
 
 /// Parser generator for parsing a pair of tokens and returning results as a tuple
+///
+/// Errors of the right parser keep their position, so that the furthest error can be reported;
+/// parsers that try alternatives rewind the input themselves.
+#[must_use = "parsers do nothing unless used"]
 pub fn pair<P1, P2, I, O1, O2>(
     mut left_parser: P1,
     mut right_parser: P2,
@@ -10,59 +67,181 @@ pub fn pair<P1, P2, I, O1, O2>(
 where
     P1: Parser<I, O1>,
     P2: Parser<I, O2>,
-    I: Copy,
 {
     move |input: I| {
-        left_parser.parse(input).and_then(|(next_input, left)| {
-            right_parser
-                .parse(next_input)
-                .map_err(|err| Error::new(input, err.code))
-                .map(|(rem_input, right)| (rem_input, (left, right)))
-        })
+        let (next_input, left) = left_parser.parse(input)?;
+        right_parser
+            .parse(next_input)
+            .map(|(rem_input, right)| (rem_input, (left, right)))
     }
 }
 
 /// Parser generator for parsing a pair of tokens and returning only the left result
+#[must_use = "parsers do nothing unless used"]
 pub fn left_from_pair<P1, P2, I, O1, O2>(left_parser: P1, right_parser: P2) -> impl Parser<I, O1>
 where
     P1: Parser<I, O1>,
     P2: Parser<I, O2>,
-    I: Copy,
 {
     pair(left_parser, right_parser).map(|(left, _)| left)
 }
 
 /// Parser generator for parsing a pair of tokens and returning only the right result
+#[must_use = "parsers do nothing unless used"]
 pub fn right_from_pair<P1, P2, I, O1, O2>(left_parser: P1, right_parser: P2) -> impl Parser<I, O2>
 where
     P1: Parser<I, O1>,
     P2: Parser<I, O2>,
-    I: Copy,
 {
     pair(left_parser, right_parser).map(|(_, right)| right)
 }
 
+/// Parser generator for parsing a pair of tokens divided by a separator and returning
+/// results of both tokens as a tuple
+#[must_use = "parsers do nothing unless used"]
+pub fn separated_pair<P1, PS, P2, I, O1, OS, O2>(
+    left_parser: P1,
+    separator: PS,
+    right_parser: P2,
+) -> impl Parser<I, (O1, O2)>
+where
+    P1: Parser<I, O1>,
+    PS: Parser<I, OS>,
+    P2: Parser<I, O2>,
+{
+    pair(left_from_pair(left_parser, separator), right_parser)
+}
+
 /// Parser generator for parsing zero or more occurrences of a token
-pub fn zero_or_more<P, I, O>(mut parser: P) -> impl Parser<I, Vec<O>>
+///
+/// Repetition stops at the first occurrence that consumes no input; its output is discarded.
+#[must_use = "parsers do nothing unless used"]
+pub fn zero_or_more<P, I, O>(parser: P) -> impl Parser<I, Vec<O>>
+where
+    P: Parser<I, O>,
+    I: Input + Rewind,
+{
+    fold_many(parser, Vec::new, |mut outputs, next_output| {
+        outputs.push(next_output);
+        outputs
+    })
+}
+
+/// Parser generator for parsing zero or more occurrences of a token, folding the results
+/// into a single value instead of collecting them
+///
+/// An occurrence that consumes no input ends the repetition without being folded, so parsers
+/// that can match the empty input don't loop forever.
+#[must_use = "parsers do nothing unless used"]
+pub fn fold_many<P, I, O, R, Init, F>(
+    mut parser: P,
+    mut init: Init,
+    mut fold: F,
+) -> impl Parser<I, R>
 where
     P: Parser<I, O>,
+    I: Input + Rewind,
+    Init: FnMut() -> R,
+    F: FnMut(R, O) -> R,
 {
     move |mut input: I| {
-        let mut outputs = Vec::new();
-        let err = loop {
+        let mut acc = init();
+        let (err, checkpoint) = loop {
+            let len = input.input_len();
+            let checkpoint = input.checkpoint();
             match parser.parse(input) {
+                Ok((next_input, _)) if next_input.input_len() == len => {
+                    return Ok((next_input, acc));
+                }
                 Ok((next_input, next_output)) => {
                     input = next_input;
-                    outputs.push(next_output);
+                    acc = fold(acc, next_output);
                 }
-                Err(err) => break err,
+                Err(err) => break (err, checkpoint),
             }
         };
 
-        if err.is_failure() {
+        if err.is_failure() || err.is_incomplete() {
             Err(err)
         } else {
-            Ok((err.input, outputs))
+            Ok((err.input.rewind(checkpoint), acc))
+        }
+    }
+}
+
+/// Parser generator for returning the slice of input consumed by a parser instead of its result
+#[must_use = "parsers do nothing unless used"]
+pub fn recognize<P, I, O>(mut parser: P) -> impl Parser<I, I>
+where
+    P: Parser<I, O>,
+    I: Input + Copy,
+{
+    move |input: I| {
+        let (next_input, _) = parser.parse(input)?;
+        let consumed = input.input_len() - next_input.input_len();
+        Ok((next_input, input.prefix(consumed)))
+    }
+}
+
+/// Parser generator for tokens followed by insignificant input (whitespace, comments)
+///
+/// The space consumer is applied after the token and should succeed on empty input
+#[must_use = "parsers do nothing unless used"]
+pub fn lexeme<P, S, I, O, SO>(space: S, parser: P) -> impl Parser<I, O>
+where
+    P: Parser<I, O>,
+    S: Parser<I, SO>,
+{
+    left_from_pair(parser, space)
+}
+
+/// Parser generator for guarding recursive grammars against stack exhaustion
+///
+/// Nested invocations of depth-limited parsers share a per-thread depth counter; parsing fails
+/// with `ErrorCode::DepthExceeded` once the counter goes over the given limit
+#[must_use = "parsers do nothing unless used"]
+pub fn max_depth<P, I, O>(limit: usize, mut parser: P) -> impl Parser<I, O>
+where
+    P: Parser<I, O>,
+{
+    move |input: I| {
+        let depth = DEPTH.with(|cell| cell.get());
+        if depth >= limit {
+            return Err(Error::new(input, ErrorCode::DepthExceeded));
+        }
+        DEPTH.with(|cell| cell.set(depth + 1));
+        let _restore = OnDrop(|| DEPTH.with(|cell| cell.set(depth)));
+        parser.parse(input)
+    }
+}
+
+/// Parser generator for detecting left recursion in recursive grammars
+///
+/// Guarded parsers share a per-thread list of the names and input positions of the guarded
+/// parsers being run; entering a parser with the same name again at the same position fails
+/// with `ErrorCode::LeftRecursion` instead of recursing forever
+#[must_use = "parsers do nothing unless used"]
+pub fn left_recursion_guard<P, I, O>(name: &'static str, mut parser: P) -> impl Parser<I, O>
+where
+    P: Parser<I, O>,
+    I: Input,
+{
+    move |input: I| {
+        let position = input.input_len();
+        let reentered = ACTIVE.with(|active| {
+            let mut active = active.borrow_mut();
+            let reentered = active.contains(&(name, position));
+            if !reentered {
+                active.push((name, position));
+            }
+            reentered
+        });
+        if reentered {
+            return Err(Error::new(input, ErrorCode::LeftRecursion(name)));
         }
+        let _restore = OnDrop(|| {
+            ACTIVE.with(|active| active.borrow_mut().pop());
+        });
+        parser.parse(input)
     }
 }
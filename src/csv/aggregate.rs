@@ -0,0 +1,115 @@
+//! Single-pass column statistics
+
+use super::*;
+use std::collections::HashSet;
+
+/// Aggregate function computed over a column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Agg {
+    Sum,
+    Min,
+    Max,
+    Mean,
+    /// Number of distinct values, compared as text
+    CountDistinct,
+}
+
+/// Result of aggregating a column
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aggregation {
+    /// Aggregated value, `None` when there were no values to aggregate
+    pub value: Option<f64>,
+    /// Number of values that were aggregated
+    pub count: usize,
+    /// Number of empty values, and of non-numeric values for numeric aggregates
+    pub skipped: usize,
+}
+
+/// Aggregates a column of a CSV document with a header row in a single streaming pass
+pub fn aggregate<C: Into<Column>>(input: &str, column: C, agg: Agg) -> PResult<&str, Aggregation> {
+    aggregate_with(input, column, agg, &Dialect::default())
+}
+
+/// Aggregates a column of a CSV document written in the given dialect like `aggregate`
+pub fn aggregate_with<'a, C: Into<Column>>(
+    input: &'a str,
+    column: C,
+    agg: Agg,
+    dialect: &Dialect,
+) -> PResult<&'a str, Aggregation> {
+    let (rem_input, mut results) = aggregate_all_with(input, column, &[agg], dialect)?;
+    Ok((rem_input, results.remove(0)))
+}
+
+/// Computes several aggregates of a column of a CSV document written in the given dialect
+/// in a single streaming pass, returning them in the order of the given functions
+pub fn aggregate_all_with<'a, C: Into<Column>>(
+    input: &'a str,
+    column: C,
+    aggs: &[Agg],
+    dialect: &Dialect,
+) -> PResult<&'a str, Vec<Aggregation>> {
+    let mut records = records_with(input, dialect);
+    let header = match records.next() {
+        Some(header) => header?,
+        None => return Err(Error::new(input, ErrorCode::NoInput)),
+    };
+    let index = column.into().resolve(&header).ok_or_else(|| {
+        Error::failure(
+            input,
+            Reason::InvalidInput {
+                expected: "header with the aggregated column",
+            },
+        )
+    })?;
+
+    let empty = Aggregation {
+        value: None,
+        count: 0,
+        skipped: 0,
+    };
+    let mut results = vec![empty; aggs.len()];
+    // Statistics shared by all numeric aggregates and by all distinct counts
+    let mut numeric = empty;
+    let mut text = empty;
+    let (mut min, mut max, mut sum) = (f64::INFINITY, f64::NEG_INFINITY, 0.0);
+    let mut distinct = HashSet::new();
+    let count_distinct = aggs.contains(&Agg::CountDistinct);
+    for record in records {
+        let mut record = record?;
+        let field = std::mem::take(&mut record[index]);
+        if field.is_empty() {
+            numeric.skipped += 1;
+            text.skipped += 1;
+            continue;
+        }
+        text.count += 1;
+        match field.trim().parse::<f64>() {
+            Ok(number) if number.is_finite() => {
+                numeric.count += 1;
+                sum += number;
+                min = min.min(number);
+                max = max.max(number);
+            }
+            _ => numeric.skipped += 1,
+        }
+        if count_distinct {
+            distinct.insert(field);
+        }
+    }
+
+    for (result, agg) in results.iter_mut().zip(aggs) {
+        let (stats, value) = match agg {
+            Agg::Sum => (numeric, sum),
+            Agg::Min => (numeric, min),
+            Agg::Max => (numeric, max),
+            Agg::Mean => (numeric, sum / numeric.count as f64),
+            Agg::CountDistinct => (text, distinct.len() as f64),
+        };
+        *result = Aggregation {
+            value: (stats.count > 0).then_some(value),
+            ..stats
+        };
+    }
+    Ok(("", results))
+}
@@ -0,0 +1,64 @@
+//! CSV parsing into a bump arena
+
+use super::*;
+use bumpalo::collections::{String as BumpString, Vec as BumpVec};
+use bumpalo::Bump;
+
+/// Single CSV record whose fields are borrowed from the input or allocated in an arena
+pub type ArenaRecord<'b> = &'b [&'b str];
+
+/// Parses a CSV document written in the given dialect, allocating the records in the arena
+///
+/// Fields are borrowed from the input unless they contain escaped characters, in which case
+/// their text is allocated in the arena, so parsing makes no allocations outside of it and
+/// resetting the arena frees all records at once.
+pub fn parse_in_arena<'a: 'b, 'b>(
+    input: &'a str,
+    dialect: &Dialect,
+    bump: &'b Bump,
+) -> PResult<&'a str, &'b [ArenaRecord<'b>]> {
+    let record = |input: &'a str| {
+        let mut fields = BumpVec::new_in(bump);
+        let (mut rest, raw) = raw_field(dialect)(trim_blanks(input, dialect))?;
+        fields.push(arena_field(raw, dialect, bump));
+        while let Ok((next_input, _)) = delimiter(dialect)(rest) {
+            let (next_input, raw) = raw_field(dialect)(next_input)?;
+            fields.push(arena_field(raw, dialect, bump));
+            rest = next_input;
+        }
+        Ok((trim_blanks(rest, dialect), fields))
+    };
+    let mut records = BumpVec::new_in(bump);
+    let rest = for_each_record(input, dialect, record, |record| {
+        records.push(record.into_bump_slice())
+    })?;
+    Ok((rest, records.into_bump_slice()))
+}
+
+/// Text of a field given as written, copied to the arena only if it has to be unescaped
+fn arena_field<'a: 'b, 'b>(raw: &'a str, dialect: &Dialect, bump: &'b Bump) -> &'b str {
+    match dialect.escape {
+        Some(escape) if raw.contains(escape) => {
+            let mut text = BumpString::with_capacity_in(raw.len(), bump);
+            resolve_escapes_into(raw, escape, &mut text);
+            text.into_bump_str()
+        }
+        Some(_) => raw,
+        // An opening quote without a closing one was read as text by a lenient dialect
+        None if raw.starts_with('"') && raw[1..].contains('"') => {
+            let quoted = &raw[1..raw.len() - 1];
+            if !quoted.contains("\"\"") {
+                return quoted;
+            }
+            let mut text = BumpString::with_capacity_in(quoted.len(), bump);
+            for (i, part) in quoted.split("\"\"").enumerate() {
+                if i > 0 {
+                    text.push('"');
+                }
+                text.push_str(part);
+            }
+            text.into_bump_str()
+        }
+        None => raw,
+    }
+}
@@ -0,0 +1,65 @@
+//! Record boundaries for splitting documents into chunks that are parsed separately
+
+use super::*;
+
+/// Finds the start of the first record at or after `from_offset`, for splitting a document
+/// into chunks without cutting through quoted fields that contain line breaks
+///
+/// `bytes` must start at the start of a record, since quoting can only be followed from
+/// there; the bytes before `from_offset` are scanned too. A chunked reader passes the rest of
+/// the document after the previous boundary, which keeps splitting linear. Returns `None` if
+/// no record starts at or after the offset.
+pub fn find_record_boundary(bytes: &[u8], from_offset: usize, dialect: &Dialect) -> Option<usize> {
+    if from_offset == 0 {
+        return (!bytes.is_empty()).then_some(0);
+    }
+    let mut encoded = [0; 4];
+    let delimiter = dialect.delimiter.encode_utf8(&mut encoded).as_bytes();
+    let mut encoded = [0; 4];
+    let escape = dialect
+        .escape
+        .map(|escape| escape.encode_utf8(&mut encoded).as_bytes());
+    let mut field_start = true;
+    let mut quoted = false;
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let rest = &bytes[pos..];
+        if quoted {
+            match rest {
+                [b'"', b'"', ..] => pos += 2,
+                [b'"', ..] => {
+                    quoted = false;
+                    pos += 1;
+                }
+                _ => pos += 1,
+            }
+            continue;
+        }
+        if rest.starts_with(delimiter) {
+            pos += delimiter.len();
+            field_start = true;
+            continue;
+        }
+        if let Some(escape) = escape.filter(|escape| rest.starts_with(escape)) {
+            // The escaped byte is part of the field, even if it is a line break
+            pos += escape.len() + 1;
+            field_start = false;
+            continue;
+        }
+        match rest[0] {
+            b'\n' => {
+                pos += 1;
+                if pos >= from_offset && pos < bytes.len() {
+                    return Some(pos);
+                }
+                field_start = true;
+                continue;
+            }
+            b'"' if field_start && escape.is_none() => quoted = true,
+            _ => {}
+        }
+        field_start = dialect.whitespace_delimited && (rest[0] == b' ' || rest[0] == b'\t');
+        pos += 1;
+    }
+    None
+}
@@ -0,0 +1,145 @@
+//! Parsing within a limited execution budget
+
+use super::*;
+use std::time::Instant;
+
+/// Limits on how much of a document is parsed
+///
+/// Limits on records and time are checked between records, and records are only parsed if
+/// they end within the byte limit, so parsing always stops at a record boundary.
+#[derive(Default)]
+pub struct Budget<'c> {
+    max_records: Option<usize>,
+    max_bytes: Option<usize>,
+    deadline: Option<(Instant, Box<dyn FnMut() -> Instant + 'c>)>,
+}
+
+/// Limit of a budget that stopped parsing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetExceeded {
+    Records,
+    Bytes,
+    Deadline,
+}
+
+/// Records parsed within a budget
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BudgetedRecords {
+    pub records: Vec<CsvRecord>,
+    /// Limit that stopped parsing before the end of the document, if any
+    pub exceeded: Option<BudgetExceeded>,
+}
+
+impl<'c> Budget<'c> {
+    /// Creates an unlimited budget
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits the number of parsed records
+    pub fn max_records(mut self, max: usize) -> Self {
+        self.max_records = Some(max);
+        self
+    }
+
+    /// Limits the number of bytes taken up by the parsed records
+    pub fn max_bytes(mut self, max: usize) -> Self {
+        self.max_bytes = Some(max);
+        self
+    }
+
+    /// Stops parsing once the system clock reaches the given instant
+    pub fn deadline(self, deadline: Instant) -> Self {
+        self.deadline_with_clock(deadline, Instant::now)
+    }
+
+    /// Stops parsing once the given clock reaches the given instant
+    pub fn deadline_with_clock<C>(mut self, deadline: Instant, clock: C) -> Self
+    where
+        C: FnMut() -> Instant + 'c,
+    {
+        self.deadline = Some((deadline, Box::new(clock)));
+        self
+    }
+
+    fn deadline_passed(&mut self) -> bool {
+        match &mut self.deadline {
+            Some((deadline, clock)) => clock() >= *deadline,
+            None => false,
+        }
+    }
+}
+
+/// Parses a CSV document written in the given dialect until the budget runs out
+///
+/// Exceeding the budget is not an error: the records parsed so far are returned along with
+/// the limit that was hit and the unparsed remainder of the input. Errors in the parsed part
+/// of the document are reported as usual. The input is never scanned past the byte limit, so
+/// a record that does not end within it, such as one with an unterminated quote, stops
+/// parsing with `BudgetExceeded::Bytes`.
+pub fn parse_budgeted<'a>(
+    input: &'a str,
+    dialect: &Dialect,
+    budget: &mut Budget,
+) -> PResult<&'a str, BudgetedRecords> {
+    let max_bytes = budget.max_bytes.filter(|&max| max < input.len());
+    let limited = match max_bytes {
+        Some(mut end) => {
+            while !input.is_char_boundary(end) {
+                end -= 1;
+            }
+            &input[..end]
+        }
+        None => input,
+    };
+    // Errors and the remainder refer to the limited input, which ends before the input does
+    let unlimited = |rest: &'a str| &input[limited.len() - rest.len()..];
+    let record = record_with(dialect);
+    let mut rest = limited;
+    let mut fields = None;
+    let mut parsed = Vec::new();
+    let mut exceeded = None;
+    loop {
+        if rest.is_empty() {
+            if max_bytes.is_some() {
+                exceeded = Some(BudgetExceeded::Bytes);
+            }
+            break;
+        }
+        if budget.max_records.is_some_and(|max| parsed.len() >= max) {
+            exceeded = Some(BudgetExceeded::Records);
+            break;
+        }
+        if budget.deadline_passed() {
+            exceeded = Some(BudgetExceeded::Deadline);
+            break;
+        }
+        let mut next_record = |mut input: &'a str| {
+            let record = next_record_with(&mut input, &mut fields, &record)?;
+            Ok((input, record))
+        };
+        let result = if max_bytes.is_some() {
+            // More input follows the limit, so records that reach it are incomplete
+            next_record.complete_or_partial().parse(rest)
+        } else {
+            next_record(rest).map(|(rem_input, record)| (rem_input, Completeness::Complete(record)))
+        };
+        match result.map_err(|err| Error::new(unlimited(err.input), err.code))? {
+            (rem_input, Completeness::Complete(record)) => {
+                rest = rem_input;
+                parsed.push(record);
+            }
+            (_, Completeness::Partial) => {
+                exceeded = Some(BudgetExceeded::Bytes);
+                break;
+            }
+        }
+    }
+    Ok((
+        unlimited(rest),
+        BudgetedRecords {
+            records: parsed,
+            exceeded,
+        },
+    ))
+}
@@ -0,0 +1,125 @@
+//! Programmatic construction of CSV records
+
+use super::*;
+use std::fmt;
+
+/// Errors that can occur while building a record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordError {
+    /// Field at the given position contains a NUL character
+    Nul { field: usize },
+    /// Record has a different number of fields than expected
+    FieldCount { expected: usize, found: usize },
+    /// Field at the given position could be evaluated as a formula, which the output
+    /// options reject
+    Formula { field: usize },
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecordError::Nul { field } => write!(f, "NUL character in field {}", field),
+            RecordError::FieldCount { expected, found } => {
+                write!(f, "expected {} fields, found {}", expected, found)
+            }
+            RecordError::Formula { field } => {
+                write!(f, "field {} could be evaluated as a formula", field)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RecordError {}
+
+/// Builds a record field by field, validating fields as they are added
+///
+/// Fields are stored as given; quoting is applied only when the record is serialized, so
+/// values never need to be escaped by hand.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecordBuilder {
+    fields: CsvRecord,
+    reject_nul: bool,
+    expected_fields: Option<usize>,
+}
+
+impl RecordBuilder {
+    /// Creates a builder of an empty record that accepts any field
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rejects fields that contain NUL characters
+    pub fn reject_nul(mut self) -> Self {
+        self.reject_nul = true;
+        self
+    }
+
+    /// Requires the record to have the given number of fields
+    pub fn expected_fields(mut self, count: usize) -> Self {
+        self.expected_fields = Some(count);
+        self
+    }
+
+    /// Appends a field to the record
+    pub fn push<S: Into<String>>(&mut self, value: S) -> Result<&mut Self, RecordError> {
+        let value = value.into();
+        let field = self.fields.len();
+        if self.reject_nul && value.contains('\0') {
+            return Err(RecordError::Nul { field });
+        }
+        match self.expected_fields {
+            Some(expected) if field >= expected => Err(RecordError::FieldCount {
+                expected,
+                found: field + 1,
+            }),
+            _ => {
+                self.fields.push(value);
+                Ok(self)
+            }
+        }
+    }
+
+    /// Returns the number of fields added so far
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    /// Returns whether no fields have been added yet
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Finishes the record, checking the number of its fields
+    pub fn build(self) -> Result<CsvRecord, RecordError> {
+        self.check_count()?;
+        Ok(self.fields)
+    }
+
+    /// Serializes the record as a line of the given output options, including its line break
+    /// when final line breaks are enabled
+    ///
+    /// Fails if the options reject formulas and a field could be evaluated as one.
+    pub fn to_line(&self, options: &WriterOptions) -> Result<String, RecordError> {
+        self.check_count()?;
+        if options.formulas == FormulaPolicy::Reject {
+            if let Some(field) = self.fields.iter().position(|field| is_formula(field)) {
+                return Err(RecordError::Formula { field });
+            }
+        }
+        let mut writer = Writer::with_options(Vec::new(), options.clone());
+        writer
+            .write_record(&self.fields)
+            .expect("writing to a vector never fails");
+        Ok(String::from_utf8(writer.into_inner()).expect("fields are valid UTF-8"))
+    }
+
+    fn check_count(&self) -> Result<(), RecordError> {
+        match self.expected_fields {
+            Some(expected) if expected != self.fields.len() => Err(RecordError::FieldCount {
+                expected,
+                found: self.fields.len(),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,223 @@
+//! CSV parsing over raw bytes, for documents that are not entirely valid UTF-8
+//!
+//! The structure of the document is recognized on bytes and fields are returned undecoded,
+//! leaving the choice of encoding to the caller.
+
+use super::*;
+use crate::bytes::*;
+
+/// Single CSV record with undecoded fields
+pub type ByteRecord = Vec<Vec<u8>>;
+
+/// Parses a CSV document given as raw bytes
+pub fn parse_bytes(input: &[u8]) -> PResult<&[u8], Vec<ByteRecord>> {
+    parse_bytes_with(input, &Dialect::default())
+}
+
+/// Parses a CSV document given as raw bytes, decoding fields as UTF-8 and replacing invalid
+/// sequences with U+FFFD
+pub fn parse_bytes_lossy(input: &[u8]) -> PResult<&[u8], Vec<CsvRecord>> {
+    let (rem_input, records) = parse_bytes(input)?;
+    let records = records
+        .into_iter()
+        .map(|record| {
+            record
+                .iter()
+                .map(|field| String::from_utf8_lossy(field).into_owned())
+                .collect()
+        })
+        .collect();
+    Ok((rem_input, records))
+}
+
+/// Parses a CSV document given as raw bytes, written in the given dialect
+///
+/// The delimiter is matched as its UTF-8 encoding.
+pub fn parse_bytes_with<'a>(
+    input: &'a [u8],
+    dialect: &Dialect,
+) -> PResult<&'a [u8], Vec<ByteRecord>> {
+    if input.is_empty() {
+        return if dialect.allow_empty {
+            Ok((input, Vec::new()))
+        } else {
+            Err(Error::new(input, ErrorCode::NoInput))
+        };
+    }
+
+    let record = byte_record_with(dialect);
+    let (mut rem_input, first_record) = record(input)?;
+    let len = first_record.len();
+    let mut records = vec![first_record];
+    while !rem_input.is_empty() {
+        let (next_input, _) = byte_line_break(rem_input).map_err(|_| {
+            Error::failure(
+                rem_input,
+                Reason::InvalidInput {
+                    expected: "comma or a line break",
+                },
+            )
+        })?;
+        if next_input.is_empty() {
+            return Ok((next_input, records));
+        }
+        let (next_input, next_record) = record(next_input)?;
+        if next_record.len() != len {
+            return Err(Error::failure(
+                rem_input,
+                Reason::InvalidInput {
+                    expected: "more fields in this record",
+                },
+            ));
+        }
+        records.push(next_record);
+        rem_input = next_input;
+    }
+    Ok((rem_input, records))
+}
+
+/// Parser generator for single record parsers over raw bytes of the given dialect
+pub fn byte_record_with(dialect: &Dialect) -> impl Fn(&[u8]) -> PResult<&[u8], ByteRecord> + '_ {
+    move |input: &[u8]| {
+        if input.is_empty() {
+            return if dialect.allow_empty {
+                Ok((input, vec![Vec::new()]))
+            } else {
+                Err(Error::new(input, ErrorCode::NoInput))
+            };
+        }
+        let mut delimiter = [0; 4];
+        let delimiter = dialect.delimiter.encode_utf8(&mut delimiter).as_bytes();
+        let field = |input| match dialect.escape {
+            Some(escape) => byte_backslashed(input, dialect, delimiter, escape),
+            None => match quoted_len(input, dialect)? {
+                Some(len) => Ok((&input[len..], byte_unescape(&input[1..len - 1]))),
+                None => byte_unquoted(dialect, delimiter)(input),
+            },
+        };
+        let (mut rem_input, first_field) = field(trim_byte_blanks(input, dialect))?;
+        let mut fields = vec![first_field];
+        while let Some(next_input) = byte_delimiter(rem_input, dialect, delimiter) {
+            let (next_input, next_field) = field(next_input)?;
+            fields.push(next_field);
+            rem_input = next_input;
+        }
+        Ok((trim_byte_blanks(rem_input, dialect), fields))
+    }
+}
+
+/// Input after the delimiter, or `None` if the input does not start with one
+fn byte_delimiter<'a>(input: &'a [u8], dialect: &Dialect, delimiter: &[u8]) -> Option<&'a [u8]> {
+    if !dialect.whitespace_delimited {
+        return input.strip_prefix(delimiter);
+    }
+    let rest = trim_byte_blanks(input, dialect);
+    match rest.first() {
+        // Blanks at the end of a record do not start another field
+        None | Some(b'\r' | b'\n') => None,
+        Some(_) if rest.len() == input.len() => None,
+        Some(_) => Some(rest),
+    }
+}
+
+/// Input without the blanks that surround records of whitespace-delimited dialects
+fn trim_byte_blanks<'a>(input: &'a [u8], dialect: &Dialect) -> &'a [u8] {
+    if dialect.whitespace_delimited {
+        let len = input
+            .iter()
+            .take_while(|&&b| b == b' ' || b == b'\t')
+            .count();
+        &input[len..]
+    } else {
+        input
+    }
+}
+
+fn byte_line_break(input: &[u8]) -> PResult<&[u8], &[u8]> {
+    tag_bytes(b"\n")
+        .fallback_on(tag_bytes(b"\r\n"))
+        .parse(input)
+        .map_err(|_| Error::new(input, ErrorCode::LineBreak))
+}
+
+/// Content of a quoted field with its escaped quotes unescaped
+fn byte_unescape(content: &[u8]) -> Vec<u8> {
+    let mut field = Vec::with_capacity(content.len());
+    let mut pos = 0;
+    while pos < content.len() {
+        field.push(content[pos]);
+        // The second quote of an escaped quote is skipped
+        pos += if content[pos] == b'"' { 2 } else { 1 };
+    }
+    field
+}
+
+fn byte_unquoted<'d>(
+    dialect: &'d Dialect,
+    delimiter: &'d [u8],
+) -> impl Fn(&[u8]) -> PResult<&[u8], Vec<u8>> + 'd {
+    move |input: &[u8]| {
+        let len = (0..input.len())
+            .find(|&i| {
+                let rest = &input[i..];
+                rest.starts_with(delimiter)
+                    || (dialect.whitespace_delimited && (rest[0] == b' ' || rest[0] == b'\t'))
+                    || rest[0] == b'\r'
+                    || rest[0] == b'\n'
+                    || (rest[0] == b'"' && dialect.stray_quotes != StrayQuotes::Literal)
+            })
+            .unwrap_or(input.len());
+        let rem_input = &input[len..];
+        if dialect.stray_quotes == StrayQuotes::Error && rem_input.starts_with(b"\"") {
+            return Err(Error::failure(
+                rem_input,
+                Reason::InvalidInput {
+                    expected: "no quotes inside an unquoted field",
+                },
+            ));
+        }
+        Ok((rem_input, input[..len].to_vec()))
+    }
+}
+
+/// Field of a dialect with an escape character, with its escapes resolved
+fn byte_backslashed<'a>(
+    input: &'a [u8],
+    dialect: &Dialect,
+    delimiter: &[u8],
+    escape: char,
+) -> PResult<&'a [u8], Vec<u8>> {
+    let mut encoded = [0; 4];
+    let escape = escape.encode_utf8(&mut encoded).as_bytes();
+    let mut field = Vec::new();
+    let mut pos = 0;
+    while pos < input.len() {
+        let rest = &input[pos..];
+        if let Some(escaped) = rest.strip_prefix(escape) {
+            let Some(&byte) = escaped.first() else {
+                return Err(Error::failure(
+                    rest,
+                    Reason::InvalidInput {
+                        expected: "escaped character",
+                    },
+                ));
+            };
+            field.push(escaped_char(byte as char) as u8);
+            pos += escape.len() + 1;
+        } else if rest.starts_with(delimiter)
+            || (dialect.whitespace_delimited && (rest[0] == b' ' || rest[0] == b'\t'))
+            || rest[0] == b'\r'
+            || rest[0] == b'\n'
+        {
+            break;
+        } else {
+            field.push(rest[0]);
+            pos += 1;
+        }
+    }
+    // NULL, which is kept as it is
+    if input[..pos].strip_prefix(escape) == Some(b"N") {
+        field = input[..pos].to_vec();
+    }
+    Ok((&input[pos..], field))
+}
@@ -0,0 +1,207 @@
+//! Adapters that connect CSV parsing to channels between producer and consumer threads
+
+use super::reader::{last_record_len, parse_line, record_ended};
+use super::*;
+use std::io::{self, BufRead, Read, Write};
+use std::sync::mpsc::{Receiver, SyncSender};
+
+/// Source that reads the text chunks received from a channel, ending when all senders are
+/// dropped
+pub struct ChannelSource {
+    receiver: Receiver<String>,
+    chunk: String,
+    pos: usize,
+}
+
+impl Read for ChannelSource {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl BufRead for ChannelSource {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        while self.pos == self.chunk.len() {
+            match self.receiver.recv() {
+                Ok(chunk) => {
+                    self.chunk = chunk;
+                    self.pos = 0;
+                }
+                Err(_) => break,
+            }
+        }
+        Ok(&self.chunk.as_bytes()[self.pos..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos = (self.pos + amt).min(self.chunk.len());
+    }
+}
+
+/// Creates a reader of the CSV records in the text chunks received from the channel
+///
+/// Chunks may split records anywhere. Only the chunk being read is held by the reader, so a
+/// bounded channel keeps the memory use of the whole pipeline bounded.
+pub fn channel_reader(receiver: Receiver<String>) -> Reader<ChannelSource> {
+    Reader::new(ChannelSource {
+        receiver,
+        chunk: String::new(),
+        pos: 0,
+    })
+}
+
+/// Writer that parses the CSV text written to it and sends every complete record to a
+/// channel
+///
+/// Sending blocks while the bounded channel is full, which in turn blocks the writing thread.
+/// The last record is sent by `ChannelWriter::finish`, even without a line break after it.
+///
+/// A record that cannot be parsed is skipped: the write that completes it accepts the bytes
+/// up to its end, and the next call of `write`, `flush` or `finish` reports the error as
+/// `io::ErrorKind::InvalidData`, after which writing continues with the next record. A
+/// disconnected receiver is reported as `io::ErrorKind::BrokenPipe`, accepting only the
+/// bytes of the records sent before.
+pub struct ChannelWriter {
+    sender: SyncSender<CsvRecord>,
+    dialect: Dialect,
+    buffer: Vec<u8>,
+    /// Length of the buffer that is known not to end a record
+    searched: usize,
+    /// Start of the first field of the record being written that is not known to be complete
+    scanned: usize,
+    consumed: usize,
+    fields: Option<usize>,
+    /// Error of a skipped record, which is reported by the next call
+    error: Option<io::Error>,
+}
+
+/// Creates a writer that sends the records of the CSV text written to it to the channel
+pub fn channel_writer(sender: SyncSender<CsvRecord>) -> ChannelWriter {
+    channel_writer_with(sender, &Dialect::default())
+}
+
+/// Creates a writer that sends the records of the CSV text written in the given dialect to
+/// the channel
+pub fn channel_writer_with(sender: SyncSender<CsvRecord>, dialect: &Dialect) -> ChannelWriter {
+    ChannelWriter {
+        sender,
+        dialect: dialect.clone(),
+        buffer: Vec::new(),
+        searched: 0,
+        scanned: 0,
+        consumed: 0,
+        fields: None,
+        error: None,
+    }
+}
+
+impl ChannelWriter {
+    /// Parses and sends the given record
+    fn send_record(&mut self, len: usize) -> io::Result<()> {
+        let offset = self.consumed;
+        // Bad records are skipped, while unsent records stay in the buffer
+        let record = std::str::from_utf8(&self.buffer[..len])
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+            .and_then(|text| {
+                parse_line(text, offset, &mut self.fields, &self.dialect)
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))
+            });
+        let sent = record.and_then(|record| {
+            self.sender
+                .send(record)
+                .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))
+        });
+        if sent
+            .as_ref()
+            .is_err_and(|err| err.kind() == io::ErrorKind::BrokenPipe)
+        {
+            return sent;
+        }
+        self.buffer.drain(..len);
+        self.consumed += len;
+        self.searched = 0;
+        self.scanned = 0;
+        sent
+    }
+
+    /// Length of the first record of the buffer, if it is complete
+    fn complete_record(&mut self) -> Option<usize> {
+        while let Some(pos) = self.buffer[self.searched..]
+            .iter()
+            .position(|byte| *byte == b'\n')
+        {
+            self.searched += pos + 1;
+            let ended = match std::str::from_utf8(&self.buffer[..self.searched]) {
+                Ok(text) => record_ended(text, &mut self.scanned, &self.dialect),
+                // Reported when the record is parsed
+                Err(_) => true,
+            };
+            if ended {
+                return Some(self.searched);
+            }
+        }
+        None
+    }
+
+    /// Sends the record that is not followed by a line break, if any, and closes the writer
+    pub fn finish(mut self) -> io::Result<()> {
+        let error = self.error.take();
+        while !self.buffer.is_empty() {
+            let len = match std::str::from_utf8(&self.buffer) {
+                Ok(text) => last_record_len(text, &self.dialect),
+                Err(_) => self.buffer.len(),
+            };
+            if let Err(err) = self.send_record(len) {
+                return Err(error.unwrap_or(err));
+            }
+        }
+        error.map_or(Ok(()), Err)
+    }
+}
+
+impl Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if let Some(err) = self.error.take() {
+            return Err(err);
+        }
+        // Text is buffered a line at a time, so the buffer never holds more than the record
+        // being written and sending it leaves the buffer empty
+        let mut accepted = 0;
+        let mut rest = buf;
+        while !rest.is_empty() {
+            let len = match rest.iter().position(|byte| *byte == b'\n') {
+                Some(pos) => pos + 1,
+                None => rest.len(),
+            };
+            self.buffer.extend_from_slice(&rest[..len]);
+            let sent = match self.complete_record() {
+                Some(record_len) => self.send_record(record_len),
+                None => Ok(()),
+            };
+            match sent {
+                Ok(()) => {}
+                Err(err) if err.kind() == io::ErrorKind::InvalidData => {
+                    self.error = Some(err);
+                    return Ok(accepted + len);
+                }
+                Err(err) => {
+                    self.buffer.truncate(self.buffer.len() - len);
+                    self.searched = 0;
+                    self.scanned = 0;
+                    return if accepted > 0 { Ok(accepted) } else { Err(err) };
+                }
+            }
+            accepted += len;
+            rest = &rest[len..];
+        }
+        Ok(accepted)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.error.take().map_or(Ok(()), Err)
+    }
+}
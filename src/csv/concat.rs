@@ -0,0 +1,63 @@
+//! Concatenation of CSV documents
+
+use super::*;
+use std::io::Write;
+
+/// Concatenates CSV documents that each start with a header row
+///
+/// The merged header is the union of all columns in order of first appearance; records are
+/// rearranged to match it and columns missing from a document are left empty. A name that
+/// repeats within a header stands for as many columns as its most repetitions in a document.
+pub fn concat(inputs: &[&str]) -> Result<String, FileError> {
+    let mut writer = Writer::new(Vec::new());
+    concat_into(inputs, &mut writer)?;
+    Ok(String::from_utf8(writer.into_inner()).expect("records are valid UTF-8"))
+}
+
+/// Concatenates CSV documents like `concat`, streaming the merged output into a writer
+///
+/// Parse error offsets are relative to the start of the document that failed.
+pub fn concat_into<W: Write>(inputs: &[&str], writer: &mut Writer<W>) -> Result<(), FileError> {
+    let mut header: CsvRecord = CsvRecord::new();
+    let mut columns: Vec<Vec<usize>> = Vec::with_capacity(inputs.len());
+    for input in inputs {
+        let doc_header = Reader::new(input.as_bytes())
+            .read_record()?
+            .unwrap_or_default();
+        // The n-th column of a name is matched with the n-th column of that name in the merged
+        // header, so that repeated names keep all their values
+        let mut seen: Vec<&str> = Vec::with_capacity(doc_header.len());
+        let mut doc_columns = Vec::with_capacity(doc_header.len());
+        for name in &doc_header {
+            let nth = seen.iter().filter(|seen| **seen == name.as_str()).count();
+            seen.push(name);
+            let index = header
+                .iter()
+                .enumerate()
+                .filter(|(_, known)| *known == name)
+                .nth(nth)
+                .map(|(index, _)| index);
+            doc_columns.push(index.unwrap_or_else(|| {
+                header.push(name.clone());
+                header.len() - 1
+            }));
+        }
+        columns.push(doc_columns);
+    }
+
+    if header.is_empty() {
+        return Ok(());
+    }
+    writer.write_record(&header)?;
+    let mut merged = vec![String::new(); header.len()];
+    for (input, columns) in inputs.iter().zip(columns) {
+        for record in Reader::new(input.as_bytes()).skip(1) {
+            merged.iter_mut().for_each(String::clear);
+            for (field, column) in record?.into_iter().zip(&columns) {
+                merged[*column] = field;
+            }
+            writer.write_record(&merged)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,67 @@
+//! Duplicate record detection
+
+use super::*;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+
+/// Streaming duplicate detector keyed by selected columns
+///
+/// Only a 64-bit hash of every key is retained, keeping memory bounded by the number of
+/// distinct keys rather than their size. Hash collisions are possible but extremely unlikely.
+#[derive(Debug, Clone, Default)]
+pub struct Deduper {
+    key_columns: Vec<usize>,
+    seen: HashSet<u64>,
+}
+
+impl Deduper {
+    /// Creates a detector keyed by the given columns; an empty key uses all columns
+    pub fn new(key_columns: &[usize]) -> Self {
+        Deduper {
+            key_columns: key_columns.to_vec(),
+            seen: HashSet::new(),
+        }
+    }
+
+    /// Records the key of the given record, returning whether it has been seen before
+    ///
+    /// Key columns missing from the record are treated as empty
+    pub fn is_duplicate(&mut self, record: &CsvRecord) -> bool {
+        let mut hasher = DefaultHasher::new();
+        if self.key_columns.is_empty() {
+            record.hash(&mut hasher);
+        } else {
+            for column in &self.key_columns {
+                record
+                    .get(*column)
+                    .map_or("", String::as_str)
+                    .hash(&mut hasher);
+            }
+        }
+        !self.seen.insert(hasher.finish())
+    }
+}
+
+/// Records left after deduplication
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Deduplicated {
+    pub records: Vec<CsvRecord>,
+    /// Zero-based indices of the dropped records within the document
+    pub duplicates: Vec<usize>,
+}
+
+/// Parses a CSV document, dropping records whose key columns repeat an earlier record
+pub fn dedup<'a>(input: &'a str, key_columns: &[usize]) -> PResult<&'a str, Deduplicated> {
+    let mut deduper = Deduper::new(key_columns);
+    let mut result = Deduplicated::default();
+    for (index, record) in records(input).enumerate() {
+        let record = record?;
+        if deduper.is_duplicate(&record) {
+            result.duplicates.push(index);
+        } else {
+            result.records.push(record);
+        }
+    }
+    Ok(("", result))
+}
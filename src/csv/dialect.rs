@@ -0,0 +1,135 @@
+//! CSV dialects
+
+use super::LineEnding;
+
+/// Handling of double quotes that appear inside unquoted fields, like `test"quote`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StrayQuotes {
+    /// The quote ends the field, which usually makes the record invalid
+    #[default]
+    Terminate,
+    /// The quote is kept as a literal part of the field, like spreadsheet applications do
+    Literal,
+    /// The quote is reported as a parsing failure, as required by RFC 4180
+    Error,
+}
+
+/// Conventions that vary between CSV producers
+///
+/// The delimiter must not be a double quote or a line break character. Dialects are plain
+/// data, so they can be cloned and shared between threads freely.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct Dialect {
+    pub delimiter: char,
+    pub stray_quotes: StrayQuotes,
+    /// Accept an empty document as zero records and an empty record as one empty field,
+    /// as the grammar allows, instead of reporting `ErrorCode::NoInput`
+    pub allow_empty: bool,
+    /// Line break written after records; both line breaks are accepted when parsing
+    pub line_ending: LineEnding,
+    /// Field value that stands for a missing value
+    pub null_token: Option<String>,
+    /// Longest accepted content of a quoted field, in bytes; longer fields are reported as a
+    /// failure without scanning past the limit, e.g. when a quote is never closed
+    pub max_quoted_len: Option<usize>,
+    /// Separate fields by any run of spaces and tabs instead of the delimiter, like `awk`
+    /// does, ignoring blanks at the start and the end of records
+    pub whitespace_delimited: bool,
+    /// Escape character of dialects that do not quote fields, like `\` in MySQL; when set,
+    /// double quotes are ordinary characters and the escape character makes the following
+    /// delimiter or line break part of the field
+    pub escape: Option<char>,
+}
+
+impl Dialect {
+    /// Creates the RFC 4180 dialect with a different field delimiter
+    pub fn with_delimiter(delimiter: char) -> Self {
+        Dialect {
+            delimiter,
+            ..Default::default()
+        }
+    }
+
+    /// Dialect of Microsoft Excel, which keeps quotes inside unquoted fields and writes empty
+    /// cells as empty records
+    pub fn excel() -> Self {
+        Dialect {
+            stray_quotes: StrayQuotes::Literal,
+            allow_empty: true,
+            ..Default::default()
+        }
+    }
+
+    /// Dialect of CSV files produced by Unix tools, which terminate records with `\n`
+    pub fn unix() -> Self {
+        Dialect {
+            line_ending: LineEnding::Lf,
+            ..Default::default()
+        }
+    }
+
+    /// Dialect of PostgreSQL `COPY ... WITH (FORMAT csv)`, which writes NULL as an empty field
+    ///
+    /// Parsed records do not keep track of quoting, so a quoted empty string is read as NULL too.
+    pub fn postgres() -> Self {
+        Dialect {
+            line_ending: LineEnding::Lf,
+            null_token: Some(String::new()),
+            ..Default::default()
+        }
+    }
+
+    /// Dialect of MySQL `SELECT ... INTO OUTFILE` and `LOAD DATA` with default options, which
+    /// separates fields with tabs, does not quote fields, escapes special characters with
+    /// backslashes and writes NULL as `\N`
+    pub fn mysql() -> Self {
+        Dialect {
+            delimiter: '\t',
+            stray_quotes: StrayQuotes::Literal,
+            line_ending: LineEnding::Lf,
+            null_token: Some("\\N".to_string()),
+            escape: Some('\\'),
+            ..Default::default()
+        }
+    }
+
+    /// Dialect of column-aligned reports and `/proc`-style files, whose fields are separated
+    /// by runs of spaces and tabs; fields that contain blanks are quoted
+    ///
+    /// Records are written with a single space between fields, so empty fields and fields
+    /// with tabs do not survive writing and reading back.
+    pub fn whitespace() -> Self {
+        Dialect {
+            delimiter: ' ',
+            line_ending: LineEnding::Lf,
+            whitespace_delimited: true,
+            ..Default::default()
+        }
+    }
+
+    /// Indicates whether the field stands for a missing value in this dialect
+    pub fn is_null(&self, field: &str) -> bool {
+        self.null_token.as_deref() == Some(field)
+    }
+}
+
+impl Default for Dialect {
+    fn default() -> Self {
+        Dialect {
+            delimiter: ',',
+            stray_quotes: StrayQuotes::default(),
+            allow_empty: false,
+            line_ending: LineEnding::default(),
+            null_token: None,
+            max_quoted_len: None,
+            whitespace_delimited: false,
+            escape: None,
+        }
+    }
+}
@@ -0,0 +1,93 @@
+//! Comparison of CSV documents
+
+use super::*;
+use std::collections::{HashMap, VecDeque};
+
+/// Changed value of a single cell
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CellChange {
+    pub column: usize,
+    pub old: String,
+    pub new: String,
+}
+
+/// Record present in both documents with different values
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangedRecord {
+    pub key: Vec<String>,
+    pub cells: Vec<CellChange>,
+}
+
+/// Differences between two CSV documents
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Diff {
+    /// Records only in the new document, in its order
+    pub added: Vec<CsvRecord>,
+    /// Records only in the old document, in its order
+    pub removed: Vec<CsvRecord>,
+    /// Records in both documents whose non-key cells differ, in the order of the old document
+    pub changed: Vec<ChangedRecord>,
+}
+
+impl Diff {
+    /// Indicates whether the documents have the same content
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Compares two CSV documents, matching records by the values of the key columns
+///
+/// Header rows are not treated specially; they match each other like any other record.
+/// Records with repeated keys are matched in order of appearance.
+pub fn diff<'a>(old: &'a str, new: &'a str, key_columns: &[usize]) -> PResult<&'a str, Diff> {
+    let (_, old) = parse_string(old)?;
+    let (_, new) = parse_string(new)?;
+    let key = |record: &CsvRecord| -> Vec<String> {
+        key_columns
+            .iter()
+            .map(|column| record.get(*column).cloned().unwrap_or_default())
+            .collect()
+    };
+
+    let mut unmatched: HashMap<Vec<String>, VecDeque<usize>> = HashMap::new();
+    for (index, record) in new.iter().enumerate() {
+        unmatched.entry(key(record)).or_default().push_back(index);
+    }
+
+    let mut diff = Diff::default();
+    let mut matched = vec![false; new.len()];
+    for record in old {
+        let record_key = key(&record);
+        match unmatched.get_mut(&record_key).and_then(VecDeque::pop_front) {
+            Some(index) => {
+                matched[index] = true;
+                let cells = changed_cells(&record, &new[index]);
+                if !cells.is_empty() {
+                    diff.changed.push(ChangedRecord {
+                        key: record_key,
+                        cells,
+                    });
+                }
+            }
+            None => diff.removed.push(record),
+        }
+    }
+    diff.added = new
+        .into_iter()
+        .zip(matched)
+        .filter(|(_, matched)| !matched)
+        .map(|(record, _)| record)
+        .collect();
+    Ok(("", diff))
+}
+
+fn changed_cells(old: &CsvRecord, new: &CsvRecord) -> Vec<CellChange> {
+    (0..old.len().max(new.len()))
+        .filter_map(|column| {
+            let old = old.get(column).cloned().unwrap_or_default();
+            let new = new.get(column).cloned().unwrap_or_default();
+            (old != new).then_some(CellChange { column, old, new })
+        })
+        .collect()
+}
@@ -0,0 +1,39 @@
+//! Owned errors for CSV parsing over files and streams
+
+use crate::base::*;
+use std::str::Utf8Error;
+use std::{fmt, io};
+
+/// Errors that can occur while parsing a CSV file or stream
+#[derive(Debug)]
+pub enum FileError {
+    Io(io::Error),
+    Utf8(Utf8Error),
+    Parse { offset: usize, code: ErrorCode },
+}
+
+impl fmt::Display for FileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileError::Io(err) => write!(f, "I/O error: {}", err),
+            FileError::Utf8(err) => write!(f, "invalid UTF-8: {}", err),
+            FileError::Parse { offset, code } => {
+                write!(f, "parse error at byte {}: {}", offset, code)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FileError {}
+
+impl From<io::Error> for FileError {
+    fn from(err: io::Error) -> Self {
+        FileError::Io(err)
+    }
+}
+
+impl From<Utf8Error> for FileError {
+    fn from(err: Utf8Error) -> Self {
+        FileError::Utf8(err)
+    }
+}
@@ -0,0 +1,81 @@
+//! Fields that remember whether they were quoted in the source document
+
+use super::*;
+
+/// Single CSV field, as written in the document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Field<'a> {
+    /// Quoted field, without its quotes and with escaped quotes unescaped
+    Quoted(String),
+    /// Unquoted field, borrowed from the input
+    Raw(&'a str),
+    /// Field with escapes of a dialect with an escape character, with the escapes resolved
+    Escaped(String),
+}
+
+impl Field<'_> {
+    /// Returns the text of the field
+    pub fn as_str(&self) -> &str {
+        match self {
+            Field::Quoted(text) | Field::Escaped(text) => text,
+            Field::Raw(text) => text,
+        }
+    }
+
+    /// Indicates whether the field was quoted
+    pub fn is_quoted(&self) -> bool {
+        matches!(self, Field::Quoted(_))
+    }
+
+    /// Converts the field into its text
+    pub fn into_string(self) -> String {
+        match self {
+            Field::Quoted(text) | Field::Escaped(text) => text,
+            Field::Raw(text) => text.to_string(),
+        }
+    }
+}
+
+/// Parser generator for single field parsers of the given dialect that keep track of quoting
+pub fn field_as_written(dialect: &Dialect) -> impl Fn(&str) -> PResult<&str, Field<'_>> + '_ {
+    move |input: &str| match quoted_len(input, dialect)? {
+        Some(len) => Ok((&input[len..], Field::Quoted(unescape(&input[..len])))),
+        None => {
+            let (rem_input, text) = unquoted(dialect)(input)?;
+            match dialect.escape {
+                Some(escape) if text.contains(escape) => {
+                    Ok((rem_input, Field::Escaped(resolve_escapes(text, escape))))
+                }
+                _ => Ok((rem_input, Field::Raw(text))),
+            }
+        }
+    }
+}
+
+/// Parser generator for single record parsers of the given dialect that keep track of quoting
+pub fn record_as_written(dialect: &Dialect) -> impl Fn(&str) -> PResult<&str, Vec<Field<'_>>> + '_ {
+    move |input: &str| {
+        if input.is_empty() && !dialect.allow_empty {
+            return Err(Error::new(input, ErrorCode::NoInput));
+        }
+        let field = field_as_written(dialect);
+        let (next_input, first_field) = field(trim_blanks(input, dialect))?;
+        let (next_input, mut fields) =
+            zero_or_more(right_from_pair(delimiter(dialect), &field)).parse(next_input)?;
+        fields.insert(0, first_field);
+        Ok((trim_blanks(next_input, dialect), fields))
+    }
+}
+
+/// Parses a CSV document written in the given dialect into records that keep track of
+/// quoting, borrowing their unquoted fields from the input
+pub fn parse_as_written<'a>(
+    input: &'a str,
+    dialect: &Dialect,
+) -> PResult<&'a str, Vec<Vec<Field<'a>>>> {
+    let mut records = Vec::new();
+    let rest = for_each_record(input, dialect, record_as_written(dialect), |record| {
+        records.push(record)
+    })?;
+    Ok((rest, records))
+}
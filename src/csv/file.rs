@@ -0,0 +1,79 @@
+//! Memory-mapped CSV file parsing
+
+use super::*;
+use memmap2::Mmap;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::str::Utf8Error;
+
+/// CSV file mapped into memory
+///
+/// The file contents are never read into a buffer; parsers run directly over the mapped
+/// bytes. `MappedFile::records_as_written` hands out records whose unquoted fields borrow from
+/// the map for as long as it is alive, while `MappedFile::parse` copies every field into owned
+/// records.
+pub struct MappedFile {
+    map: Mmap,
+}
+
+impl MappedFile {
+    /// Maps the file at the given path into memory
+    ///
+    /// This is a safe function over the unsafe `Mmap::map`: the map assumes that the file is
+    /// not modified or truncated while it is mapped, which the caller has to ensure, e.g. by
+    /// mapping only files that no other process writes to.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: the map is read-only; modifying the file while it is mapped is the caller's
+        // responsibility, same as with any other memory-mapped file
+        let map = unsafe { Mmap::map(&file)? };
+        Ok(MappedFile { map })
+    }
+
+    /// Returns the mapped contents as a string slice
+    pub fn as_str(&self) -> Result<&str, Utf8Error> {
+        std::str::from_utf8(&self.map)
+    }
+
+    /// Parses the mapped contents as a CSV document into owned records
+    pub fn parse(&self) -> Result<PResult<&str, Vec<CsvRecord>>, Utf8Error> {
+        self.as_str().map(parse_string)
+    }
+
+    /// Parses the mapped contents as a CSV document written in the given dialect into records
+    /// whose unquoted fields borrow from the map
+    ///
+    /// Only quoted and escaped fields, whose text differs from the file, are allocated.
+    pub fn records_as_written(
+        &self,
+        dialect: &Dialect,
+    ) -> Result<PResult<&str, Vec<Vec<Field<'_>>>>, Utf8Error> {
+        Ok(parse_as_written(self.as_str()?, dialect))
+    }
+}
+
+/// Parses a CSV file into owned records without reading it into an intermediate buffer
+pub fn parse_file<P: AsRef<Path>>(path: P) -> Result<Vec<CsvRecord>, FileError> {
+    let file = MappedFile::open(path)?;
+    parse_detached(file.as_str()?, &Dialect::default())
+}
+
+/// Parses a CSV file, notifying the observer as it goes
+///
+/// `ParseObserver::bytes_consumed` receives the size of the file as the total, which makes it
+/// suitable for progress bars.
+pub fn parse_file_observed<P, O>(path: P, observer: &mut O) -> Result<Vec<CsvRecord>, FileError>
+where
+    P: AsRef<Path>,
+    O: ParseObserver + ?Sized,
+{
+    let file = MappedFile::open(path)?;
+    let input = file.as_str()?;
+    parse_observed(input, &Dialect::default(), observer)
+        .map(|(_, records)| records)
+        .map_err(|err| FileError::Parse {
+            offset: input.len() - err.input.len(),
+            code: err.code,
+        })
+}
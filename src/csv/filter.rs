@@ -0,0 +1,47 @@
+//! Record filtering ahead of field materialization
+
+use super::*;
+
+/// Parses a CSV document keeping only the records whose raw text satisfies the predicate
+///
+/// Records are first only recognized; fields are unescaped and allocated just for the records
+/// that pass the predicate. All records still have to have the same number of fields.
+pub fn parse_where<F>(input: &str, mut predicate: F) -> PResult<&str, Vec<CsvRecord>>
+where
+    F: FnMut(&str) -> bool,
+{
+    let mut records = Vec::new();
+    let mut expected_len = None;
+    let mut next_input = input;
+    loop {
+        let (rem_input, (raw, len)) = counted_raw_record(next_input, &Dialect::default())?;
+        if *expected_len.get_or_insert(len) != len {
+            return Err(Error::failure(
+                next_input,
+                Reason::InvalidInput {
+                    expected: "more fields in this record",
+                },
+            ));
+        }
+        if predicate(raw) {
+            records.push(record(raw)?.1);
+        }
+
+        if rem_input.is_empty() {
+            break;
+        }
+        next_input = match line_break(rem_input) {
+            Ok(("", _)) => break,
+            Ok((rem_input, _)) => rem_input,
+            Err(_) => {
+                return Err(Error::failure(
+                    rem_input,
+                    Reason::InvalidInput {
+                        expected: "comma or a line break",
+                    },
+                ))
+            }
+        };
+    }
+    Ok(("", records))
+}
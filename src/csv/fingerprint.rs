@@ -0,0 +1,38 @@
+//! Content fingerprints of CSV documents
+
+use super::*;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Computes a hash of the logical content of a CSV document
+///
+/// Documents that differ only in quoting, line endings or the trailing line break have the
+/// same fingerprint. The hash (64-bit FNV-1a over a length-prefixed encoding of the fields)
+/// is stable across platforms and crate versions, so fingerprints can be persisted.
+pub fn fingerprint(input: &str) -> PResult<&str, u64> {
+    let mut hash = Fnv1a(FNV_OFFSET_BASIS);
+    for record in records(input) {
+        let record = record?;
+        hash.write_len(record.len());
+        for field in &record {
+            hash.write_len(field.len());
+            hash.write(field.as_bytes());
+        }
+    }
+    Ok(("", hash.0))
+}
+
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn write_len(&mut self, len: usize) {
+        self.write(&(len as u64).to_le_bytes());
+    }
+}
@@ -0,0 +1,57 @@
+//! Grouping of records by the value of a key column
+
+use super::*;
+use std::collections::HashMap;
+
+/// Parses a CSV document with a header row, grouping its records by the value of the given
+/// column
+pub fn group_by<C: Into<Column>>(
+    input: &str,
+    column: C,
+) -> PResult<&str, HashMap<String, Vec<CsvRecord>>> {
+    group_fold(input, column, Vec::new, |mut group, record| {
+        group.push(record);
+        group
+    })
+}
+
+/// Parses a CSV document with a header row in a single streaming pass, folding the records of
+/// every value of the given column into a single value
+///
+/// Only one accumulated value per distinct key is kept in memory, not the records.
+pub fn group_fold<C, A, Init, F>(
+    input: &str,
+    column: C,
+    mut init: Init,
+    mut fold: F,
+) -> PResult<&str, HashMap<String, A>>
+where
+    C: Into<Column>,
+    Init: FnMut() -> A,
+    F: FnMut(A, CsvRecord) -> A,
+{
+    let mut records = records(input);
+    let header = match records.next() {
+        Some(header) => header?,
+        None => return Err(Error::new(input, ErrorCode::NoInput)),
+    };
+    let index = column.into().resolve(&header).ok_or_else(|| {
+        Error::failure(
+            input,
+            Reason::InvalidInput {
+                expected: "header with the key column",
+            },
+        )
+    })?;
+
+    let mut groups: HashMap<String, A> = HashMap::new();
+    for record in records {
+        let record = record?;
+        let (key, group) = match groups.remove_entry(&record[index]) {
+            Some(entry) => entry,
+            None => (record[index].clone(), init()),
+        };
+        groups.insert(key, fold(group, record));
+    }
+    Ok(("", groups))
+}
@@ -0,0 +1,65 @@
+//! Interning of repeated field values
+
+use super::*;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Set of distinct strings that repeated field values share
+///
+/// An interner can be kept across documents, so that values repeated between them share an
+/// allocation as well.
+#[derive(Debug, Clone, Default)]
+pub struct Interner {
+    strings: HashSet<Arc<str>>,
+}
+
+impl Interner {
+    /// Creates an empty interner
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the shared copy of the given string, allocating it on first use
+    pub fn intern(&mut self, text: &str) -> Arc<str> {
+        if let Some(interned) = self.strings.get(text) {
+            return Arc::clone(interned);
+        }
+        let interned: Arc<str> = Arc::from(text);
+        self.strings.insert(Arc::clone(&interned));
+        interned
+    }
+
+    /// Returns the number of distinct strings
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// Indicates whether no string was interned yet
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+}
+
+/// Single CSV record whose fields are shared with equal fields of other records
+pub type InternedRecord = Vec<Arc<str>>;
+
+/// Parses a CSV document written in the given dialect, interning the fields
+///
+/// Unquoted fields are interned without being copied first, so a value repeated throughout
+/// a column is allocated only once.
+pub fn parse_interned<'a>(
+    input: &'a str,
+    dialect: &Dialect,
+    interner: &mut Interner,
+) -> PResult<&'a str, Vec<InternedRecord>> {
+    let mut records = Vec::new();
+    let rest = for_each_record(input, dialect, record_as_written(dialect), |record| {
+        records.push(
+            record
+                .iter()
+                .map(|field| interner.intern(field.as_str()))
+                .collect(),
+        )
+    })?;
+    Ok((rest, records))
+}
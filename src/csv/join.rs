@@ -0,0 +1,207 @@
+//! Joining of CSV documents on key columns
+
+use super::*;
+use std::collections::{HashMap, HashSet};
+
+/// Records that a join outputs, besides those matched in both documents
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JoinKind {
+    /// Only records matched in both documents
+    Inner,
+    /// Also left records without a match, with empty right fields
+    Left,
+    /// Also left and right records without a match, with empty fields for the other document
+    Outer,
+}
+
+/// Handling of non-key columns that have the same name in both documents
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Collision {
+    /// Both columns are kept, with the given suffixes appended to the left and right names
+    Suffix(String, String),
+    /// Only the column of the left document is kept
+    KeepLeft,
+    /// Only the column of the right document is kept
+    KeepRight,
+}
+
+impl Default for Collision {
+    fn default() -> Self {
+        Collision::Suffix("_left".to_string(), "_right".to_string())
+    }
+}
+
+/// Joins two CSV documents with header rows on the given key columns, suffixing colliding
+/// column names with `_left` and `_right`
+pub fn join<'a, K, C>(
+    left: &'a str,
+    right: &'a str,
+    on: K,
+    kind: JoinKind,
+) -> PResult<&'a str, Vec<CsvRecord>>
+where
+    K: IntoIterator<Item = C>,
+    C: Into<Column>,
+{
+    join_with(left, right, on, kind, &Collision::default())
+}
+
+/// Joins two CSV documents with header rows on the given key columns
+///
+/// The output starts with a header of the key columns, named as in the left document, followed
+/// by the other columns of the left and then of the right document. Records are output in the
+/// order of the left document, and every pair of records with equal keys is output; unmatched
+/// right records of an outer join come last.
+pub fn join_with<'a, K, C>(
+    left: &'a str,
+    right: &'a str,
+    on: K,
+    kind: JoinKind,
+    collision: &Collision,
+) -> PResult<&'a str, Vec<CsvRecord>>
+where
+    K: IntoIterator<Item = C>,
+    C: Into<Column>,
+{
+    let on: Vec<Column> = on.into_iter().map(Into::into).collect();
+    let mut left = Side::parse(left, &on)?;
+    let mut right = Side::parse(right, &on)?;
+
+    let left_names = left.column_names();
+    let right_names = right.column_names();
+    let (left_suffix, right_suffix) = match collision {
+        Collision::Suffix(left_suffix, right_suffix) => (Some(left_suffix), Some(right_suffix)),
+        Collision::KeepLeft => {
+            right
+                .columns
+                .retain(|i| !left_names.contains(right.header[*i].as_str()));
+            (None, None)
+        }
+        Collision::KeepRight => {
+            left.columns
+                .retain(|i| !right_names.contains(left.header[*i].as_str()));
+            (None, None)
+        }
+    };
+    let rename = |name: &String, others: &HashSet<String>, suffix: Option<&String>| match suffix {
+        Some(suffix) if others.contains(name) => format!("{}{}", name, suffix),
+        _ => name.clone(),
+    };
+    let mut header: CsvRecord = left.keys.iter().map(|i| left.header[*i].clone()).collect();
+    header.extend(
+        left.columns
+            .iter()
+            .map(|i| rename(&left.header[*i], &right_names, left_suffix)),
+    );
+    header.extend(
+        right
+            .columns
+            .iter()
+            .map(|i| rename(&right.header[*i], &left_names, right_suffix)),
+    );
+
+    let mut by_key: HashMap<Vec<&str>, Vec<usize>> = HashMap::new();
+    for (index, record) in right.records.iter().enumerate() {
+        by_key.entry(right.key(record)).or_default().push(index);
+    }
+
+    let mut joined = vec![header];
+    let mut matched = vec![false; right.records.len()];
+    for record in &left.records {
+        let key = left.key(record);
+        let matches = by_key.get(&key).map_or(&[][..], Vec::as_slice);
+        if matches.is_empty() && kind != JoinKind::Inner {
+            joined.push(joined_record(&key, &left, Some(record), &right, None));
+        }
+        for index in matches {
+            matched[*index] = true;
+            let right_record = &right.records[*index];
+            joined.push(joined_record(
+                &key,
+                &left,
+                Some(record),
+                &right,
+                Some(right_record),
+            ));
+        }
+    }
+    if kind == JoinKind::Outer {
+        for (record, _) in right
+            .records
+            .iter()
+            .zip(matched)
+            .filter(|(_, matched)| !matched)
+        {
+            let key = right.key(record);
+            joined.push(joined_record(&key, &left, None, &right, Some(record)));
+        }
+    }
+    Ok(("", joined))
+}
+
+/// Parsed document taking part in a join
+struct Side {
+    header: CsvRecord,
+    records: Vec<CsvRecord>,
+    /// Positions of the key columns, in the order of the join keys
+    keys: Vec<usize>,
+    /// Positions of the other columns that are output
+    columns: Vec<usize>,
+}
+
+impl Side {
+    fn parse<'a>(input: &'a str, on: &[Column]) -> Result<Side, Error<&'a str>> {
+        let (_, mut records) = parse_string(input)?;
+        let header = records.remove(0);
+        let keys = on
+            .iter()
+            .map(|column| column.resolve(&header))
+            .collect::<Option<Vec<_>>>()
+            .filter(|keys| !keys.is_empty())
+            .ok_or_else(|| {
+                Error::failure(
+                    input,
+                    Reason::InvalidInput {
+                        expected: "header with the key columns",
+                    },
+                )
+            })?;
+        let columns = (0..header.len()).filter(|i| !keys.contains(i)).collect();
+        Ok(Side {
+            header,
+            records,
+            keys,
+            columns,
+        })
+    }
+
+    fn column_names(&self) -> HashSet<String> {
+        self.columns
+            .iter()
+            .map(|i| self.header[*i].clone())
+            .collect()
+    }
+
+    fn key<'r>(&self, record: &'r CsvRecord) -> Vec<&'r str> {
+        self.keys.iter().map(|i| record[*i].as_str()).collect()
+    }
+
+    fn fields<'s>(&'s self, record: Option<&'s CsvRecord>) -> impl Iterator<Item = String> + 's {
+        self.columns
+            .iter()
+            .map(move |i| record.map_or_else(String::new, |record| record[*i].clone()))
+    }
+}
+
+fn joined_record(
+    key: &[&str],
+    left: &Side,
+    left_record: Option<&CsvRecord>,
+    right: &Side,
+    right_record: Option<&CsvRecord>,
+) -> CsvRecord {
+    let mut record: CsvRecord = key.iter().map(|field| field.to_string()).collect();
+    record.extend(left.fields(left_record));
+    record.extend(right.fields(right_record));
+    record
+}
@@ -0,0 +1,100 @@
+//! CSV to JSON conversion
+
+use super::*;
+use std::fmt::{self, Write};
+
+/// Options for converting CSV documents to JSON
+#[derive(Debug, Clone, Default)]
+pub struct JsonOptions {
+    /// Emit numeric and boolean fields as JSON numbers and booleans instead of strings
+    pub coerce_types: bool,
+    /// Emit empty fields as `null` instead of empty strings
+    pub empty_as_null: bool,
+}
+
+/// Converts a CSV document into a JSON array of objects keyed by the header row
+pub fn to_json<'a>(input: &'a str, options: &JsonOptions) -> Result<String, Error<&'a str>> {
+    let (_, records) = parse_string(input)?;
+    let mut json = String::new();
+    write_json(&records, options, &mut json).expect("writing to a String cannot fail");
+    Ok(json)
+}
+
+/// Writes parsed records as a JSON array of objects, using the first record as the header row
+pub fn write_json<W: Write>(
+    records: &[CsvRecord],
+    options: &JsonOptions,
+    out: &mut W,
+) -> fmt::Result {
+    let (header, rows) = match records.split_first() {
+        Some(split) => split,
+        None => return out.write_str("[]"),
+    };
+
+    out.write_char('[')?;
+    for (i, row) in rows.iter().enumerate() {
+        if i > 0 {
+            out.write_char(',')?;
+        }
+        out.write_char('{')?;
+        for (j, (key, value)) in header.iter().zip(row).enumerate() {
+            if j > 0 {
+                out.write_char(',')?;
+            }
+            write_string(key, out)?;
+            out.write_char(':')?;
+            write_value(value, options, out)?;
+        }
+        out.write_char('}')?;
+    }
+    out.write_char(']')
+}
+
+fn write_value<W: Write>(value: &str, options: &JsonOptions, out: &mut W) -> fmt::Result {
+    if value.is_empty() && options.empty_as_null {
+        out.write_str("null")
+    } else if options.coerce_types && (is_number(value) || value == "true" || value == "false") {
+        out.write_str(value)
+    } else {
+        write_string(value, out)
+    }
+}
+
+pub(crate) fn write_string<W: Write>(value: &str, out: &mut W) -> fmt::Result {
+    out.write_char('"')?;
+    for ch in value.chars() {
+        match ch {
+            '"' => out.write_str("\\\"")?,
+            '\\' => out.write_str("\\\\")?,
+            '\n' => out.write_str("\\n")?,
+            '\r' => out.write_str("\\r")?,
+            '\t' => out.write_str("\\t")?,
+            ch if (ch as u32) < 0x20 => write!(out, "\\u{:04x}", ch as u32)?,
+            ch => out.write_char(ch)?,
+        }
+    }
+    out.write_char('"')
+}
+
+/// Checks the JSON number grammar, so that e.g. identifiers with leading zeros stay strings
+fn is_number(value: &str) -> bool {
+    let digits = take_while(|ch| ch.is_ascii_digit());
+    let rest = value.strip_prefix('-').unwrap_or(value);
+    let rest = match digits(rest) {
+        Ok((rest, "0")) => rest,
+        Ok((rest, int)) if !int.is_empty() && !int.starts_with('0') => rest,
+        _ => return false,
+    };
+    let rest = match rest.strip_prefix('.').map(&digits) {
+        Some(Ok((_, ""))) => return false,
+        Some(Ok((rest, _))) => rest,
+        _ => rest,
+    };
+    match rest.strip_prefix(['e', 'E']) {
+        Some(exp) => {
+            let exp = exp.strip_prefix(['+', '-']).unwrap_or(exp);
+            matches!(digits(exp), Ok(("", exp_digits)) if !exp_digits.is_empty())
+        }
+        None => rest.is_empty(),
+    }
+}
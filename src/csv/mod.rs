@@ -0,0 +1,531 @@
+//! CSV-related parsers
+//!
+//! CSV EBNF grammar, derived from [RFC4180](https://www.rfc-editor.org/rfc/rfc4180)
+//!
+//! CSVDATA = RECORD (LINEBREAK RECORD)* [LINEBREAK]
+//! RECORD = FIELD (COMMA FIELD)*
+//! FIELD = ESCAPED | NON-ESCAPED
+//! ESCAPED = DQUOTE (TEXT | COMMA | CR | LF | DDQUOTE)* DQUOTE
+//! NON-ESCAPED = TEXT*
+//! CR = "\r" (* %x2C *)
+//! LF = "\n" (* %x0A *)
+//! COMMA = "," (* %x2C *)
+//! DQUOTE = """ (* %x22 *)
+//! DDQUOTE = """"
+//! LINEBREAK = LF | CRLF
+//! (* TEXT is any encoding that does not encode CR, LF, COMMA or DQUOTE *)
+
+use crate::base::*;
+use crate::chars::*;
+use crate::combinators::*;
+
+mod aggregate;
+#[cfg(feature = "bumpalo")]
+mod arena;
+mod boundary;
+mod budget;
+mod builder;
+mod bytes;
+mod channel;
+mod concat;
+mod dedup;
+mod dialect;
+mod diff;
+mod error;
+mod field;
+#[cfg(feature = "mmap")]
+mod file;
+mod filter;
+mod fingerprint;
+mod group;
+mod intern;
+mod join;
+pub(crate) mod json;
+mod multi;
+mod numbered;
+mod observer;
+mod owned;
+mod preamble;
+mod projection;
+mod reader;
+mod records;
+mod report;
+mod sample;
+mod sanitize;
+mod shared;
+mod sort;
+mod storage;
+mod table;
+mod trailer;
+mod transform;
+mod transpose;
+mod typed;
+mod writer;
+
+pub use self::aggregate::*;
+#[cfg(feature = "bumpalo")]
+pub use self::arena::*;
+pub use self::boundary::*;
+pub use self::budget::*;
+pub use self::builder::*;
+pub use self::bytes::*;
+pub use self::channel::*;
+pub use self::concat::*;
+pub use self::dedup::*;
+pub use self::dialect::*;
+pub use self::diff::*;
+pub use self::error::*;
+pub use self::field::*;
+#[cfg(feature = "mmap")]
+pub use self::file::*;
+pub use self::filter::*;
+pub use self::fingerprint::*;
+pub use self::group::*;
+pub use self::intern::*;
+pub use self::join::*;
+pub use self::json::*;
+pub use self::multi::*;
+pub use self::numbered::*;
+pub use self::observer::*;
+pub use self::owned::*;
+pub use self::preamble::*;
+pub use self::projection::*;
+pub use self::reader::*;
+pub use self::records::*;
+pub use self::report::*;
+pub use self::sample::*;
+pub use self::sanitize::*;
+pub use self::shared::*;
+pub use self::sort::*;
+pub use self::storage::*;
+pub use self::table::*;
+pub use self::trailer::*;
+pub use self::transform::*;
+pub use self::transpose::*;
+pub use self::typed::*;
+pub use self::writer::*;
+
+pub fn parse_string(input: &str) -> PResult<&str, Vec<CsvRecord>> {
+    parse_with(input, &Dialect::default())
+}
+
+/// Parses a CSV document written in the given dialect
+pub fn parse_with<'a>(input: &'a str, dialect: &Dialect) -> PResult<&'a str, Vec<CsvRecord>> {
+    if input.is_empty() && dialect.allow_empty {
+        return Ok((input, Vec::new()));
+    }
+    // Records after the first never match empty input, so that the trailing line break
+    // is not followed by an extra empty record
+    let record = non_empty_record(dialect);
+    let (trailing, records) = (&record)
+        .flat_map(|next_input, first_record| {
+            let len = first_record.len();
+            let mut records = vec![first_record];
+            let (rem_input, other_records) = zero_or_more(right_from_pair(
+                line_break,
+                (&record).iff_or_invalid(move |rec| rec.len() == len),
+            ))
+            .parse(next_input)?;
+            records.extend(other_records);
+            Ok((rem_input, records))
+        })
+        .parse(input)
+        .map_err(|err| match err {
+            Error {
+                input,
+                code:
+                    ErrorCode::Failure(Reason::InvalidInput {
+                        expected: "unknown",
+                    }),
+            } => Error::failure(
+                input,
+                Reason::InvalidInput {
+                    expected: "more fields in this record",
+                },
+            ),
+            err => err,
+        })?;
+
+    // Parse optional line break at the end.
+    if !trailing.is_empty() {
+        match line_break.parse(trailing) {
+            Ok(("", _))
+            | Err(Error {
+                input: "",
+                code: ErrorCode::LineBreak,
+            }) => Ok(("", records)),
+
+            // Parser stumbled upon an invalid character or something is seriously wrong with
+            // the parser implementation; assuming the first one
+            _ => Err(Error::failure(
+                trailing,
+                Reason::InvalidInput {
+                    expected: "comma or a line break",
+                },
+            )),
+        }
+    } else {
+        Ok(("", records))
+    }
+}
+
+/// Single CSV record (line) parser
+pub type CsvRecord = Vec<String>;
+
+/// Single CSV record parser
+pub fn record(input: &str) -> PResult<&str, CsvRecord> {
+    record_with(&Dialect::default())(input)
+}
+
+/// Parser generator for single record parsers of the given dialect
+pub fn record_with(dialect: &Dialect) -> impl Fn(&str) -> PResult<&str, CsvRecord> + '_ {
+    move |input: &str| {
+        if input.is_empty() && dialect.allow_empty {
+            Ok((input, vec![String::new()]))
+        } else {
+            non_empty_record(dialect)(input)
+        }
+    }
+}
+
+/// Parses a single record of the given dialect into the given record, reusing its fields and
+/// their allocations
+///
+/// The record is left with as many fields as were parsed; after an error, its contents are
+/// unspecified.
+pub fn parse_record_into<'a>(
+    input: &'a str,
+    dialect: &Dialect,
+    record: &mut CsvRecord,
+) -> PResult<&'a str, ()> {
+    if input.is_empty() && !dialect.allow_empty {
+        return Err(Error::new(input, ErrorCode::NoInput));
+    }
+    let mut rest = trim_blanks(input, dialect);
+    let mut count = 0;
+    loop {
+        if count == record.len() {
+            record.push(String::new());
+        }
+        let field = &mut record[count];
+        field.clear();
+        rest = field_into(rest, dialect, field)?;
+        count += 1;
+        match delimiter(dialect)(rest) {
+            Ok((next_input, _)) => rest = next_input,
+            Err(_) => break,
+        }
+    }
+    record.truncate(count);
+    Ok((trim_blanks(rest, dialect), ()))
+}
+
+/// Appends the text of the field at the start of the input to the output, returning the
+/// remaining input
+fn field_into<'a>(
+    input: &'a str,
+    dialect: &Dialect,
+    out: &mut String,
+) -> Result<&'a str, Error<&'a str>> {
+    if let Some(len) = quoted_len(input, dialect)? {
+        for (i, part) in input[1..len - 1].split("\"\"").enumerate() {
+            if i > 0 {
+                out.push('"');
+            }
+            out.push_str(part);
+        }
+        return Ok(&input[len..]);
+    }
+    let (rem_input, text) = unquoted(dialect)(input)?;
+    match dialect.escape {
+        Some(escape) => resolve_escapes_into(text, escape, out),
+        None => out.push_str(text),
+    }
+    Ok(rem_input)
+}
+
+fn non_empty_record(dialect: &Dialect) -> impl Fn(&str) -> PResult<&str, CsvRecord> + '_ {
+    move |input: &str| {
+        if !input.is_empty() {
+            let field = field_with(dialect);
+            (&field)
+                .parse(trim_blanks(input, dialect))
+                .and_then(|(next_input, first_field)| {
+                    let mut fields: CsvRecord = CsvRecord::new();
+                    fields.push(first_field);
+                    zero_or_more(right_from_pair(delimiter(dialect), &field))
+                        .parse(next_input)
+                        .map(|(rem_input, other_fields)| {
+                            fields.extend(other_fields);
+                            (trim_blanks(rem_input, dialect), fields)
+                        })
+                })
+        } else {
+            // Empty string is a valid record by CSV grammar, it's essentially a one empty field,
+            // however it is only accepted when the dialect allows it
+            Err(Error::new(input, ErrorCode::NoInput))
+        }
+    }
+}
+
+/// Single CSV record parser that only recognizes the record and returns its raw text,
+/// without materializing any fields
+pub fn raw_record(input: &str) -> PResult<&str, &str> {
+    counted_raw_record(input, &Dialect::default()).map(|(rem_input, (raw, _))| (rem_input, raw))
+}
+
+/// Recognizes a single record, returning its raw text along with the number of fields
+fn counted_raw_record<'a>(input: &'a str, dialect: &Dialect) -> PResult<&'a str, (&'a str, usize)> {
+    if input.is_empty() {
+        return Err(Error::new(input, ErrorCode::NoInput));
+    }
+    pair(
+        raw_field(dialect),
+        fold_many(
+            right_from_pair(delimiter(dialect), raw_field(dialect)),
+            || 1,
+            |count, _| count + 1,
+        ),
+    )
+    .parse(trim_blanks(input, dialect))
+    .map(|(rem_input, (_, count))| {
+        let rem_input = trim_blanks(rem_input, dialect);
+        (rem_input, (&input[..input.len() - rem_input.len()], count))
+    })
+}
+
+/// Single CSV field parser
+pub fn field(input: &str) -> PResult<&str, String> {
+    field_with(&Dialect::default())(input)
+}
+
+/// Parser generator for single field parsers of the given dialect
+///
+/// The first character decides whether the field is parsed as quoted or unquoted, without
+/// backtracking from one to the other.
+pub fn field_with(dialect: &Dialect) -> impl Fn(&str) -> PResult<&str, String> + '_ {
+    move |input: &str| match quoted_len(input, dialect)? {
+        Some(len) => Ok((&input[len..], unescape(&input[..len]))),
+        None => field_unquoted(dialect)(input),
+    }
+}
+
+/// Parser generator for parsers of quoted fields of the given dialect, which output the
+/// field without its quotes and with escaped quotes unescaped
+pub fn field_quoted(dialect: &Dialect) -> impl Fn(&str) -> PResult<&str, String> + '_ {
+    move |input: &str| {
+        if !input.starts_with('"') {
+            return Err(Error::end_of_input(input, ErrorCode::Char('"')));
+        }
+        match quoted_len(input, dialect)? {
+            Some(len) => Ok((&input[len..], unescape(&input[..len]))),
+            None => Err(Error::failure(
+                input,
+                Reason::InvalidInput {
+                    expected: "closing quote",
+                },
+            )),
+        }
+    }
+}
+
+/// Parser generator for parsers of unquoted fields of the given dialect, which output the
+/// field with escapes resolved in dialects with an escape character
+pub fn field_unquoted(dialect: &Dialect) -> impl Fn(&str) -> PResult<&str, String> + '_ {
+    move |input: &str| {
+        let (rem_input, text) = unquoted(dialect)(input)?;
+        match dialect.escape {
+            Some(escape) => Ok((rem_input, resolve_escapes(text, escape))),
+            None => Ok((rem_input, text.to_string())),
+        }
+    }
+}
+
+/// Text of a field of a dialect with an escape character, given with its escapes
+///
+/// The escape character followed by `0`, `b`, `n`, `r`, `t` or `Z` stands for NUL, backspace,
+/// line feed, carriage return, tab and Ctrl-Z, and followed by any other character for that
+/// character. A field that consists only of an escaped `N` is kept as it is, since it stands
+/// for NULL.
+fn resolve_escapes(text: &str, escape: char) -> String {
+    let mut resolved = String::with_capacity(text.len());
+    resolve_escapes_into(text, escape, &mut resolved);
+    resolved
+}
+
+/// Appends the text of a field of a dialect with an escape character to the output, as
+/// `resolve_escapes` returns it
+fn resolve_escapes_into<E: Extend<char>>(text: &str, escape: char, out: &mut E) {
+    let mut chars = text.chars();
+    if chars.next() == Some(escape) && chars.as_str() == "N" {
+        out.extend(text.chars());
+        return;
+    }
+    let mut chars = text.chars();
+    out.extend(std::iter::from_fn(|| match chars.next()? {
+        ch if ch == escape => chars.next().map(escaped_char),
+        ch => Some(ch),
+    }));
+}
+
+/// Character that the given character stands for after an escape character
+fn escaped_char(ch: char) -> char {
+    match ch {
+        '0' => '\0',
+        'b' => '\u{8}',
+        'n' => '\n',
+        'r' => '\r',
+        't' => '\t',
+        'Z' => '\u{1a}',
+        ch => ch,
+    }
+}
+
+/// Text of a quoted field given with its quotes
+fn unescape(quoted: &str) -> String {
+    quoted[1..quoted.len() - 1].replace("\"\"", "\"")
+}
+
+fn delimiter(dialect: &Dialect) -> impl Fn(&str) -> PResult<&str, char> + '_ {
+    move |input: &str| {
+        if !dialect.whitespace_delimited {
+            return char(dialect.delimiter)(input);
+        }
+        let rest = input.trim_start_matches(is_blank);
+        let code = ErrorCode::Char(dialect.delimiter);
+        // Blanks at the end of a record do not start another field
+        if rest.is_empty() {
+            Err(Error::end_of_input(input, code))
+        } else if rest.len() == input.len() || rest.starts_with(['\r', '\n']) {
+            Err(Error::new(input, code))
+        } else {
+            Ok((rest, dialect.delimiter))
+        }
+    }
+}
+
+fn is_blank(ch: char) -> bool {
+    ch == ' ' || ch == '\t'
+}
+
+/// Input without the blanks that surround records of whitespace-delimited dialects
+fn trim_blanks<'a>(input: &'a str, dialect: &Dialect) -> &'a str {
+    if dialect.whitespace_delimited {
+        input.trim_start_matches(is_blank)
+    } else {
+        input
+    }
+}
+
+fn is_special(ch: char, dialect: &Dialect) -> bool {
+    ch == dialect.delimiter
+        || (dialect.whitespace_delimited && is_blank(ch))
+        || ch == '\r'
+        || ch == '\n'
+        || (ch == '"' && dialect.stray_quotes != StrayQuotes::Literal && dialect.escape.is_none())
+}
+
+/// Text of an unquoted field, handling quotes inside it as configured by the dialect
+fn unquoted(dialect: &Dialect) -> impl Fn(&str) -> PResult<&str, &str> + '_ {
+    move |input: &str| {
+        if let Some(escape) = dialect.escape {
+            return escaped_text(input, dialect, escape);
+        }
+        let (rem_input, text) = take_while(|ch| !is_special(ch, dialect))(input)?;
+        if dialect.stray_quotes == StrayQuotes::Error && rem_input.starts_with('"') {
+            Err(Error::failure(
+                rem_input,
+                Reason::InvalidInput {
+                    expected: "no quotes inside an unquoted field",
+                },
+            ))
+        } else {
+            Ok((rem_input, text))
+        }
+    }
+}
+
+/// Text of a field of a dialect with an escape character, with its escapes left in place
+fn escaped_text<'a>(input: &'a str, dialect: &Dialect, escape: char) -> PResult<&'a str, &'a str> {
+    let mut chars = input.char_indices();
+    while let Some((pos, ch)) = chars.next() {
+        if ch == escape {
+            if chars.next().is_none() && !is_streaming() {
+                return Err(Error::failure(
+                    &input[pos..],
+                    Reason::InvalidInput {
+                        expected: "escaped character",
+                    },
+                ));
+            }
+        } else if is_special(ch, dialect) {
+            return Ok((&input[pos..], &input[..pos]));
+        }
+    }
+    if is_streaming() {
+        Err(Error::new(input, ErrorCode::Incomplete))
+    } else {
+        Ok(("", input))
+    }
+}
+
+/// Length of the quoted field at the start of the input, including its quotes, or `None`
+/// if the field is not quoted
+///
+/// The field is scanned once; an unterminated quote is reported as a failure instead of
+/// letting the caller rescan the rest of the document, except in dialects that keep stray
+/// quotes, where the field is then treated as unquoted. Text and raw bytes are scanned alike.
+fn quoted_len<'a, T>(input: &'a T, dialect: &Dialect) -> Result<Option<usize>, Error<&'a T>>
+where
+    T: AsRef<[u8]> + ?Sized,
+{
+    let bytes = input.as_ref();
+    if !bytes.starts_with(b"\"") || dialect.escape.is_some() {
+        return Ok(None);
+    }
+    // Quotes are only searched for within the opening quote, the longest content and the
+    // closing quote
+    let end = dialect
+        .max_quoted_len
+        .map_or(bytes.len(), |max| bytes.len().min(max.saturating_add(2)));
+    let mut pos = 1;
+    loop {
+        match bytes[pos..end].iter().position(|&byte| byte == b'"') {
+            Some(offset) => pos += offset + 1,
+            None if end < bytes.len() => return Err(quoted_too_long(input)),
+            None if is_streaming() => return Err(Error::new(input, ErrorCode::Incomplete)),
+            None if dialect.stray_quotes == StrayQuotes::Literal => return Ok(None),
+            None => {
+                return Err(Error::failure(
+                    input,
+                    Reason::InvalidInput {
+                        expected: "closing quote",
+                    },
+                ))
+            }
+        }
+        match bytes.get(pos) {
+            // Escaped quote, which is part of the content
+            Some(b'"') if pos < end => pos += 1,
+            Some(b'"') => return Err(quoted_too_long(input)),
+            None if is_streaming() => return Err(Error::new(input, ErrorCode::Incomplete)),
+            _ => return Ok(Some(pos)),
+        }
+    }
+}
+
+fn quoted_too_long<T: ?Sized>(input: &T) -> Error<&T> {
+    Error::failure(
+        input,
+        Reason::InvalidInput {
+            expected: "quoted field within the size limit",
+        },
+    )
+}
+
+fn raw_field(dialect: &Dialect) -> impl Fn(&str) -> PResult<&str, &str> + '_ {
+    move |input: &str| match quoted_len(input, dialect)? {
+        Some(len) => Ok((&input[len..], &input[..len])),
+        None => unquoted(dialect)(input),
+    }
+}
@@ -0,0 +1,71 @@
+//! Parsing of files that contain several CSV tables separated by blank lines
+
+use super::*;
+
+/// Single table of a multi-table document
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Document {
+    pub header: CsvRecord,
+    /// Records after the header, each with as many fields as the header
+    pub records: Vec<CsvRecord>,
+}
+
+/// Parses a document of CSV tables separated by one or more blank lines
+pub fn parse_multi(input: &str) -> PResult<&str, Vec<Document>> {
+    parse_multi_with(input, &Dialect::default())
+}
+
+/// Parses a document of CSV tables written in the given dialect, separated by one or more
+/// blank lines
+///
+/// Every table starts with a header, and its records must have as many fields as its header.
+/// Blank lines inside quoted fields don't separate tables.
+pub fn parse_multi_with<'a>(input: &'a str, dialect: &Dialect) -> PResult<&'a str, Vec<Document>> {
+    let record = non_empty_record(dialect);
+    let mut documents = Vec::new();
+    let mut rest = input;
+    loop {
+        while let Ok((next_input, _)) = line_break(rest) {
+            rest = next_input;
+        }
+        if rest.is_empty() {
+            break;
+        }
+
+        let (next_input, header) = record(rest)?;
+        rest = next_input;
+        let mut records = Vec::new();
+        while let Ok((next_input, _)) = line_break(rest) {
+            // A blank line, or the end of input, ends the table
+            if next_input.is_empty() || line_break(next_input).is_ok() {
+                break;
+            }
+            let (next_input, next_record) = record(next_input)?;
+            if next_record.len() != header.len() {
+                return Err(Error::failure(
+                    rest,
+                    Reason::InvalidInput {
+                        expected: "more fields in this record",
+                    },
+                ));
+            }
+            records.push(next_record);
+            rest = next_input;
+        }
+        if !rest.is_empty() && line_break(rest).is_err() {
+            return Err(Error::failure(
+                rest,
+                Reason::InvalidInput {
+                    expected: "comma or a line break",
+                },
+            ));
+        }
+        documents.push(Document { header, records });
+    }
+
+    if documents.is_empty() && !dialect.allow_empty {
+        return Err(Error::new(input, ErrorCode::NoInput));
+    }
+    Ok((rest, documents))
+}
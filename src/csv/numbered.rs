@@ -0,0 +1,57 @@
+//! Records tagged with source line numbers
+
+use super::*;
+
+/// CSV record tagged with the 1-based line of the source it starts on
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NumberedRecord {
+    pub line: usize,
+    pub fields: CsvRecord,
+}
+
+/// Parses a CSV document, tagging each record with its source line number
+pub fn parse_numbered(input: &str) -> PResult<&str, Vec<NumberedRecord>> {
+    parse_numbered_with(input, &Dialect::default())
+}
+
+/// Parses a CSV document written in the given dialect, tagging each record with its source
+/// line number
+pub fn parse_numbered_with<'a>(
+    input: &'a str,
+    dialect: &Dialect,
+) -> PResult<&'a str, Vec<NumberedRecord>> {
+    let mut records = records_with(input, dialect);
+    let mut numbered = Vec::new();
+    let mut line = 1;
+    let mut start = input;
+    while let Some(fields) = records.next().transpose()? {
+        numbered.push(NumberedRecord { line, fields });
+        // Counted in the source, since escaped line breaks are decoded into the fields
+        let rest = records.remaining();
+        line += start[..start.len() - rest.len()].matches('\n').count();
+        start = rest;
+    }
+    Ok((records.remaining(), numbered))
+}
+
+/// Tags consecutive records of a document with their source line numbers
+///
+/// Quoted fields keep their line breaks verbatim, so every LF found in a field is a line of
+/// the source spanned by its record. That does not hold for dialects with an escape
+/// character, whose records are numbered by `parse_numbered_with` instead.
+pub fn number_records(records: Vec<CsvRecord>) -> Vec<NumberedRecord> {
+    let mut line = 1;
+    records
+        .into_iter()
+        .map(|fields| {
+            let numbered = NumberedRecord { line, fields };
+            line += 1 + numbered
+                .fields
+                .iter()
+                .map(|field| field.matches('\n').count())
+                .sum::<usize>();
+            numbered
+        })
+        .collect()
+}
@@ -0,0 +1,47 @@
+//! Callbacks for observing long-running parses
+
+use super::*;
+
+/// Receives notifications while a CSV document is being parsed
+///
+/// All methods do nothing by default, so implementations only override what they need.
+pub trait ParseObserver {
+    /// Called for every parsed record, in document order
+    fn on_record(&mut self, _record: &CsvRecord) {}
+
+    /// Called when parsing fails, right before the error is returned
+    fn on_error(&mut self, _error: &Error<&str>) {}
+
+    /// Called for every warning, once the whole document is parsed
+    fn on_warning(&mut self, _warning: &ParseWarning) {}
+
+    /// Called after every record with the number of bytes parsed so far and the input length
+    fn bytes_consumed(&mut self, _consumed: usize, _total: usize) {}
+}
+
+/// Parses a CSV document written in the given dialect, notifying the observer as it goes
+pub fn parse_observed<'a, O: ParseObserver + ?Sized>(
+    input: &'a str,
+    dialect: &Dialect,
+    observer: &mut O,
+) -> PResult<&'a str, Vec<CsvRecord>> {
+    let mut records = records_with(input, dialect);
+    let mut parsed = Vec::new();
+    while let Some(record) = records.next() {
+        match record {
+            Ok(record) => {
+                observer.on_record(&record);
+                observer.bytes_consumed(input.len() - records.remaining().len(), input.len());
+                parsed.push(record);
+            }
+            Err(err) => {
+                observer.on_error(&err);
+                return Err(err);
+            }
+        }
+    }
+    for warning in &inspect(input, dialect).warnings {
+        observer.on_warning(warning);
+    }
+    Ok((records.remaining(), parsed))
+}
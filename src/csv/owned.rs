@@ -0,0 +1,77 @@
+//! Parsing of owned input into results that do not borrow from it
+
+use super::*;
+
+/// Parses a CSV document held in an owned string
+///
+/// Records and errors own all of their data, so the result can outlive the input buffer;
+/// errors carry the byte offset at which parsing failed
+pub fn parse_string_owned(input: String) -> Result<Vec<CsvRecord>, FileError> {
+    parse_owned_with(input, &Dialect::default())
+}
+
+/// Parses a CSV document held in an owned string, written in the given dialect
+pub fn parse_owned_with(input: String, dialect: &Dialect) -> Result<Vec<CsvRecord>, FileError> {
+    parse_detached(&input, dialect)
+}
+
+/// Parses a CSV document held in an owned byte buffer, which must be valid UTF-8
+pub fn parse_bytes_owned(input: Vec<u8>) -> Result<Vec<CsvRecord>, FileError> {
+    let input = String::from_utf8(input).map_err(|err| err.utf8_error())?;
+    parse_string_owned(input)
+}
+
+/// Parses a whole document, replacing the borrowed error input with its byte offset
+pub(crate) fn parse_detached(input: &str, dialect: &Dialect) -> Result<Vec<CsvRecord>, FileError> {
+    parse_with(input, dialect)
+        .map(|(_, records)| records)
+        .map_err(|err| FileError::Parse {
+            offset: input.len() - err.input.len(),
+            code: err.code,
+        })
+}
+
+/// Iterator over the records of a CSV document that owns its input
+///
+/// Enforces the same rules as `Records`, but yields errors that do not borrow from the input,
+/// so the iterator can be stored or moved freely
+pub struct OwnedRecords {
+    input: String,
+    dialect: Dialect,
+    consumed: usize,
+    fields: Option<usize>,
+    done: bool,
+}
+
+/// Iterates over the records of a CSV document held in an owned string
+pub fn records_owned(input: String, dialect: &Dialect) -> OwnedRecords {
+    OwnedRecords {
+        input,
+        dialect: dialect.clone(),
+        consumed: 0,
+        fields: None,
+        done: false,
+    }
+}
+
+impl Iterator for OwnedRecords {
+    type Item = Result<CsvRecord, OwnedError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let mut records =
+            records_with(&self.input[self.consumed..], &self.dialect).resume(self.fields);
+        let next = records.next();
+        match &next {
+            Some(Ok(_)) => {
+                self.consumed = self.input.len() - records.remaining().len();
+                self.fields = records.expected_fields();
+                self.done = records.remaining().is_empty();
+            }
+            Some(Err(_)) | None => self.done = true,
+        }
+        next.map(|next| next.map_err(|err| err.to_owned_error(self.input.len())))
+    }
+}
@@ -0,0 +1,52 @@
+//! Skipping of metadata lines that precede the header of a CSV document
+
+use super::*;
+
+/// Parser generator for parsers that skip the given number of lines, returning them
+///
+/// Lines are skipped verbatim, without applying the CSV grammar. Fails if the input has fewer
+/// lines.
+pub fn skip_lines(n: usize) -> impl Fn(&str) -> PResult<&str, &str> {
+    move |input: &str| {
+        let mut rem_input = input;
+        for _ in 0..n {
+            (rem_input, _) = line_with_break(rem_input)?;
+        }
+        Ok((rem_input, &input[..input.len() - rem_input.len()]))
+    }
+}
+
+/// Parser generator for parsers that skip lines up to the first one that satisfies the
+/// predicate, returning the skipped lines
+///
+/// The predicate receives lines without their line breaks. Fails if no line satisfies it.
+pub fn skip_until<F>(predicate: F) -> impl Fn(&str) -> PResult<&str, &str>
+where
+    F: Fn(&str) -> bool,
+{
+    move |input: &str| {
+        let mut rem_input = input;
+        loop {
+            let (next_input, line) = line(rem_input)?;
+            if predicate(line) {
+                return Ok((rem_input, &input[..input.len() - rem_input.len()]));
+            }
+            rem_input = next_input;
+        }
+    }
+}
+
+/// Parses a CSV document written in the given dialect after skipping its preamble with the
+/// given parser, returning the preamble along with the records
+pub fn parse_after<'a, P>(
+    input: &'a str,
+    mut preamble: P,
+    dialect: &Dialect,
+) -> PResult<&'a str, (&'a str, Vec<CsvRecord>)>
+where
+    P: Parser<&'a str, &'a str>,
+{
+    let (next_input, skipped) = preamble.parse(input)?;
+    let (rem_input, records) = parse_with(next_input, dialect)?;
+    Ok((rem_input, (skipped, records)))
+}
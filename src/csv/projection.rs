@@ -0,0 +1,123 @@
+//! Column selection, re-ordering and renaming while parsing
+
+use super::*;
+
+/// Column of a CSV document, referenced by position or by header name
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Column {
+    Index(usize),
+    Name(String),
+}
+
+impl Column {
+    /// Returns the position of this column in a document with the given header
+    pub fn resolve(&self, header: &CsvRecord) -> Option<usize> {
+        match self {
+            Column::Index(index) => Some(*index).filter(|index| *index < header.len()),
+            Column::Name(name) => header.iter().position(|field| field == name),
+        }
+    }
+}
+
+impl From<usize> for Column {
+    fn from(index: usize) -> Self {
+        Column::Index(index)
+    }
+}
+
+impl From<&str> for Column {
+    fn from(name: &str) -> Self {
+        Column::Name(name.to_string())
+    }
+}
+
+/// Mapping of source columns to the columns of the output, in output order
+///
+/// Source columns that are not mentioned are dropped.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Projection {
+    columns: Vec<(Column, Option<String>)>,
+}
+
+impl Projection {
+    /// Creates a projection without any columns
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a source column to the output, keeping its header name
+    pub fn select<C: Into<Column>>(mut self, source: C) -> Self {
+        self.columns.push((source.into(), None));
+        self
+    }
+
+    /// Appends a source column to the output under a new header name
+    pub fn rename<C: Into<Column>>(mut self, source: C, name: &str) -> Self {
+        self.columns.push((source.into(), Some(name.to_string())));
+        self
+    }
+
+    /// Resolves the source index of every output column against the given header
+    fn resolve(&self, header: &CsvRecord) -> Option<Vec<usize>> {
+        self.columns
+            .iter()
+            .map(|(column, _)| column.resolve(header))
+            .collect()
+    }
+}
+
+/// Parses a CSV document with a header row, projecting every record as it is parsed
+///
+/// The first record of the output is the projected and renamed header row.
+pub fn parse_projected<'a>(
+    input: &'a str,
+    projection: &Projection,
+) -> PResult<&'a str, Vec<CsvRecord>> {
+    parse_projected_with(input, projection, &Dialect::default())
+}
+
+/// Parses a CSV document written in the given dialect like `parse_projected`
+pub fn parse_projected_with<'a>(
+    input: &'a str,
+    projection: &Projection,
+    dialect: &Dialect,
+) -> PResult<&'a str, Vec<CsvRecord>> {
+    let mut records = records_with(input, dialect);
+    let header = match records.next() {
+        Some(header) => header?,
+        None => return Err(Error::new(input, ErrorCode::NoInput)),
+    };
+    let indices = projection.resolve(&header).ok_or_else(|| {
+        Error::failure(
+            input,
+            Reason::InvalidInput {
+                expected: "header with all projected columns",
+            },
+        )
+    })?;
+
+    let mut output = vec![project(header, &indices)];
+    for (out, (_, name)) in output[0].iter_mut().zip(&projection.columns) {
+        if let Some(name) = name {
+            *out = name.clone();
+        }
+    }
+    for record in records {
+        output.push(project(record?, &indices));
+    }
+    Ok(("", output))
+}
+
+/// Moves the selected fields out of a record, cloning only columns selected more than once
+fn project(mut record: CsvRecord, indices: &[usize]) -> CsvRecord {
+    let mut projected: CsvRecord = CsvRecord::with_capacity(indices.len());
+    for (i, index) in indices.iter().enumerate() {
+        let field = match indices[..i].iter().position(|earlier| earlier == index) {
+            Some(earlier) => projected[earlier].clone(),
+            None => std::mem::take(&mut record[*index]),
+        };
+        projected.push(field);
+    }
+    projected
+}
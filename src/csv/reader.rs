@@ -0,0 +1,388 @@
+//! Streaming CSV reader
+
+use super::*;
+use std::cell::Cell;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::rc::Rc;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// Reads CSV records one at a time from a buffered source
+///
+/// Only the record being parsed is kept in memory, so arbitrarily large inputs can be
+/// processed. Records spanning multiple lines (quoted fields with embedded line breaks) are
+/// reassembled before parsing.
+pub struct Reader<R> {
+    inner: R,
+    buffer: String,
+    /// Start of the first field of the buffer that is not known to be complete
+    scanned: usize,
+    consumed: usize,
+    fields: Option<usize>,
+    dialect: Dialect,
+    total: Option<Total>,
+}
+
+/// Progress of a reader, saved to resume reading later, e.g. after a restart
+///
+/// The state is serializable with the `serde` feature. A reader interrupted by an I/O error
+/// while reading a record spanning multiple lines keeps the lines read so far, which are
+/// saved as the partially read record.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct ParserState {
+    /// Number of bytes of (decompressed) input taken up by the records read
+    pub offset: usize,
+    /// Number of fields every record must have, once the first record is read
+    pub fields: Option<usize>,
+    /// Dialect the input is parsed with
+    pub dialect: Dialect,
+    /// Text of the record being read, which follows the offset in the input
+    pub partial: String,
+}
+
+/// Known size of the input, used for progress reporting
+enum Total {
+    /// Size of the input that is parsed
+    Parsed(u64),
+    /// Size of the file the input is decompressed from, along with the number of file bytes
+    /// read so far
+    Source(u64, Rc<Cell<u64>>),
+}
+
+impl<R: BufRead> Reader<R> {
+    /// Creates a new reader over the given source
+    pub fn new(inner: R) -> Self {
+        Reader {
+            inner,
+            buffer: String::new(),
+            scanned: 0,
+            consumed: 0,
+            fields: None,
+            dialect: Dialect::default(),
+            total: None,
+        }
+    }
+
+    /// Parses the input in the given dialect instead of the default one
+    pub fn with_dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// Sets the total length of the source in bytes, enabling progress reporting
+    pub fn with_total_len(mut self, total: u64) -> Self {
+        self.total = Some(Total::Parsed(total));
+        self
+    }
+
+    /// Number of bytes of (decompressed) input consumed so far
+    pub fn bytes_consumed(&self) -> usize {
+        self.consumed
+    }
+
+    /// Returns the progress of this reader, from which reading can be resumed with
+    /// `Reader::resume`
+    pub fn state(&self) -> ParserState {
+        ParserState {
+            offset: self.consumed,
+            fields: self.fields,
+            dialect: self.dialect.clone(),
+            partial: self.buffer.clone(),
+        }
+    }
+
+    /// Fraction of the input consumed so far, between 0 and 1, if the total length is known
+    ///
+    /// For files opened with `Reader::open`, progress is measured over the bytes read from the
+    /// file (compressed or not) and runs ahead of parsing by at most the size of the read
+    /// buffers.
+    pub fn progress(&self) -> Option<f64> {
+        let (done, total) = match self.total.as_ref()? {
+            Total::Parsed(total) => (self.consumed as u64, *total),
+            Total::Source(total, read) => (read.get(), *total),
+        };
+        Some(if total == 0 {
+            1.0
+        } else {
+            (done as f64 / total as f64).min(1.0)
+        })
+    }
+
+    /// Reads the next record, returning `None` at the end of input
+    pub fn read_record(&mut self) -> Result<Option<CsvRecord>, FileError> {
+        let mut record = CsvRecord::new();
+        Ok(self.read_record_into(&mut record)?.then_some(record))
+    }
+
+    /// Reads the next record into the given record, reusing its fields and their
+    /// allocations, and returns `false` at the end of input
+    pub fn read_record_into(&mut self, record: &mut CsvRecord) -> Result<bool, FileError> {
+        // Text read before an I/O error stays in the buffer, and reading the record continues
+        // after it
+        let mut end_of_input = false;
+        loop {
+            if self.inner.read_line(&mut self.buffer)? == 0 {
+                end_of_input = true;
+                break;
+            }
+            if record_ended(&self.buffer, &mut self.scanned, &self.dialect) {
+                break;
+            }
+        }
+
+        if self.buffer.is_empty() {
+            return Ok(false);
+        }
+
+        let len = if end_of_input {
+            last_record_len(&self.buffer, &self.dialect)
+        } else {
+            self.buffer.len()
+        };
+        let result = parse_line_into(
+            &self.buffer[..len],
+            self.consumed,
+            &mut self.fields,
+            &self.dialect,
+            record,
+        );
+        self.consumed += len;
+        self.buffer.drain(..len);
+        self.scanned = 0;
+        result.map(|()| true)
+    }
+
+    /// Returns the underlying source
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Continues from the given state, whose offset the source is already positioned after
+    fn restore(mut self, state: &ParserState) -> Self {
+        self.consumed = state.offset;
+        self.fields = state.fields;
+        self.dialect = state.dialect.clone();
+        self.buffer.clone_from(&state.partial);
+        self
+    }
+}
+
+/// Indicates whether the text read into the buffer holds a whole record, scanning its fields
+/// from the first one that is not known to be complete
+///
+/// A record ends with a line break that is neither inside a quoted field nor escaped. Fields
+/// are recognized as the parser does, so stray quotes kept as text by the dialect do not
+/// open a quoted field. Malformed records end where the parser stops, which reports them.
+pub(super) fn record_ended(buffer: &str, scanned: &mut usize, dialect: &Dialect) -> bool {
+    if !buffer.ends_with('\n') {
+        return false;
+    }
+    let mut rest = trim_blanks(&buffer[*scanned..], dialect);
+    loop {
+        match raw_field(dialect).complete_or_partial().parse(rest) {
+            Ok((next_input, Completeness::Complete(_))) => rest = next_input,
+            Ok((_, Completeness::Partial)) => {
+                *scanned = buffer.len() - rest.len();
+                return false;
+            }
+            Err(_) => return true,
+        }
+        match delimiter(dialect)(rest) {
+            Ok((next_input, _)) => rest = next_input,
+            Err(_) => return true,
+        }
+    }
+}
+
+/// Length of the first record of the text left at the end of input, with its line break
+///
+/// An opening quote that is never closed is only known to be text at the end of input, in
+/// dialects that keep stray quotes, so the text can then hold several records.
+pub(super) fn last_record_len(buffer: &str, dialect: &Dialect) -> usize {
+    let mut record = CsvRecord::new();
+    match parse_record_into(buffer, dialect, &mut record) {
+        Ok((rest, ())) => match line_break(rest) {
+            Ok((rest, _)) => buffer.len() - rest.len(),
+            Err(_) => buffer.len(),
+        },
+        Err(_) => buffer.len(),
+    }
+}
+
+/// Parses the text of a single record, optionally followed by a line break, which starts at
+/// the given offset of the input
+///
+/// The record must have the given number of fields, which is set by the first record.
+pub(super) fn parse_line(
+    input: &str,
+    offset: usize,
+    fields: &mut Option<usize>,
+    dialect: &Dialect,
+) -> Result<CsvRecord, FileError> {
+    let mut record = CsvRecord::new();
+    parse_line_into(input, offset, fields, dialect, &mut record)?;
+    Ok(record)
+}
+
+fn parse_line_into(
+    input: &str,
+    offset: usize,
+    fields: &mut Option<usize>,
+    dialect: &Dialect,
+    record: &mut CsvRecord,
+) -> Result<(), FileError> {
+    let error_at = |rest: &str, code| FileError::Parse {
+        offset: offset + input.len() - rest.len(),
+        code,
+    };
+
+    let (rest, ()) =
+        parse_record_into(input, dialect, record).map_err(|err| error_at(err.input, err.code))?;
+    if !rest.is_empty() && !matches!(line_break(rest), Ok(("", _))) {
+        return Err(error_at(
+            rest,
+            ErrorCode::Failure(Reason::InvalidInput {
+                expected: "comma or a line break",
+            }),
+        ));
+    }
+    if *fields.get_or_insert(record.len()) != record.len() {
+        return Err(error_at(
+            input,
+            ErrorCode::Failure(Reason::InvalidInput {
+                expected: "more fields in this record",
+            }),
+        ));
+    }
+    Ok(())
+}
+
+impl<R: BufRead + Seek> Reader<R> {
+    /// Creates a reader that continues where the reader with the given state stopped, seeking
+    /// the source past the saved offset and partially read record
+    ///
+    /// The source must be the same uncompressed input the state was saved from; compressed
+    /// input is rejected with `io::ErrorKind::InvalidInput`, since offsets refer to the
+    /// decompressed text, and is resumed with `Reader::resume_decompressed` instead.
+    pub fn resume(mut inner: R, state: &ParserState) -> io::Result<Self> {
+        let head = inner.fill_buf()?;
+        if head.starts_with(GZIP_MAGIC) || head.starts_with(ZSTD_MAGIC) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "compressed input cannot be resumed by seeking",
+            ));
+        }
+        let offset = state.offset + state.partial.len();
+        inner.seek(SeekFrom::Start(offset as u64))?;
+        Ok(Reader::new(inner).restore(state))
+    }
+}
+
+impl Reader<Box<dyn BufRead>> {
+    /// Opens a CSV file for reading, decompressing it if needed
+    ///
+    /// The size of the file is used for progress reporting.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let total = file.metadata()?.len();
+        let read = Rc::new(Cell::new(0));
+        let counted = Counted {
+            inner: file,
+            read: Rc::clone(&read),
+        };
+        let mut reader = Self::decompress(BufReader::new(counted))?;
+        reader.total = Some(Total::Source(total, read));
+        Ok(reader)
+    }
+
+    /// Creates a reader that transparently decompresses its source
+    ///
+    /// The compression format is detected from the leading magic bytes. Gzip input is
+    /// recognized with the `gzip` feature and Zstandard input with the `zstd` feature; any
+    /// other input is read as plain text.
+    pub fn decompress<R: BufRead + 'static>(mut inner: R) -> io::Result<Self> {
+        let head = inner.fill_buf()?;
+        let source: Box<dyn BufRead> = if head.starts_with(GZIP_MAGIC) {
+            gzip_source(inner)?
+        } else if head.starts_with(ZSTD_MAGIC) {
+            zstd_source(inner)?
+        } else {
+            Box::new(inner)
+        };
+        Ok(Reader::new(source))
+    }
+
+    /// Creates a reader that continues where the reader with the given state stopped,
+    /// decompressing the source like `Reader::decompress` and skipping the saved offset and
+    /// partially read record
+    ///
+    /// The skipped input is decompressed again, so resuming takes time proportional to the
+    /// offset.
+    pub fn resume_decompressed<R: BufRead + 'static>(
+        inner: R,
+        state: &ParserState,
+    ) -> io::Result<Self> {
+        let mut reader = Self::decompress(inner)?;
+        let offset = (state.offset + state.partial.len()) as u64;
+        let skipped = io::copy(&mut (&mut reader.inner).take(offset), &mut io::sink())?;
+        if skipped < offset {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "input ends before the saved offset",
+            ));
+        }
+        Ok(reader.restore(state))
+    }
+}
+
+/// Source that counts the bytes read from it
+struct Counted<R> {
+    inner: R,
+    read: Rc<Cell<u64>>,
+}
+
+impl<R: Read> Read for Counted<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.inner.read(buf)?;
+        self.read.set(self.read.get() + len as u64);
+        Ok(len)
+    }
+}
+
+#[cfg(feature = "gzip")]
+fn gzip_source<R: BufRead + 'static>(inner: R) -> io::Result<Box<dyn BufRead>> {
+    Ok(Box::new(BufReader::new(
+        flate2::bufread::MultiGzDecoder::new(inner),
+    )))
+}
+
+#[cfg(not(feature = "gzip"))]
+fn gzip_source<R: BufRead + 'static>(inner: R) -> io::Result<Box<dyn BufRead>> {
+    Ok(Box::new(inner))
+}
+
+#[cfg(feature = "zstd")]
+fn zstd_source<R: BufRead + 'static>(inner: R) -> io::Result<Box<dyn BufRead>> {
+    Ok(Box::new(BufReader::new(zstd::Decoder::with_buffer(inner)?)))
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_source<R: BufRead + 'static>(inner: R) -> io::Result<Box<dyn BufRead>> {
+    Ok(Box::new(inner))
+}
+
+impl<R: BufRead> Iterator for Reader<R> {
+    type Item = Result<CsvRecord, FileError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record().transpose()
+    }
+}
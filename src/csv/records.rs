@@ -0,0 +1,171 @@
+//! Record-at-a-time iteration over in-memory CSV documents
+
+use super::*;
+use std::ops::Deref;
+
+/// Iterator over the records of a CSV document held in memory
+///
+/// Enforces the same rules as `parse_with`, but hands out records one at a time so that they
+/// can be processed without materializing the whole document. Iteration stops after the first
+/// error.
+pub struct Records<'a> {
+    input: &'a str,
+    dialect: Dialect,
+    fields: Option<usize>,
+    done: bool,
+}
+
+/// Iterates over the records of a CSV document
+pub fn records(input: &str) -> Records<'_> {
+    records_with(input, &Dialect::default())
+}
+
+/// Iterates over the records of a CSV document written in the given dialect
+pub fn records_with<'a>(input: &'a str, dialect: &Dialect) -> Records<'a> {
+    Records {
+        input,
+        dialect: dialect.clone(),
+        fields: None,
+        done: input.is_empty() && dialect.allow_empty,
+    }
+}
+
+impl<'a> Records<'a> {
+    /// Returns the input that has not been parsed yet
+    pub fn remaining(&self) -> &'a str {
+        self.input
+    }
+
+    /// Returns the number of fields every record must have, once the first one is parsed
+    pub fn expected_fields(&self) -> Option<usize> {
+        self.fields
+    }
+
+    /// Continues an iteration that was interrupted after records with the given number of
+    /// fields had already been handed out
+    pub fn resume(mut self, fields: Option<usize>) -> Self {
+        self.fields = fields;
+        self
+    }
+
+    /// Parses the next record into the given record, reusing its fields and their
+    /// allocations, and returns `false` at the end of input
+    ///
+    /// Mixing this with `Iterator::next` is allowed; iteration stops after the first error.
+    pub fn read_into(&mut self, record: &mut CsvRecord) -> Result<bool, Error<&'a str>> {
+        if self.done {
+            return Ok(false);
+        }
+        let result =
+            parse_record_into(self.input, &self.dialect, record).and_then(|(rem_input, ())| {
+                finish_record(&mut self.input, rem_input, record.len(), &mut self.fields)
+            });
+        self.done = result.is_err() || self.input.is_empty();
+        result.map(|()| true)
+    }
+
+    fn next_record(&mut self) -> Result<CsvRecord, Error<&'a str>> {
+        let record = next_record_with(
+            &mut self.input,
+            &mut self.fields,
+            record_with(&self.dialect),
+        );
+        self.done = self.input.is_empty();
+        record
+    }
+}
+
+/// Parses all records of a document with the given record parser, handing them out in order
+///
+/// This is the document grammar of `parse_with` for record types other than `CsvRecord`: an
+/// empty document is accepted only by dialects that allow it, every record must have as many
+/// fields as the first, and a single trailing line break is allowed.
+pub(super) fn for_each_record<'a, R, T>(
+    input: &'a str,
+    dialect: &Dialect,
+    record: impl Fn(&'a str) -> PResult<&'a str, R>,
+    mut each: impl FnMut(R),
+) -> Result<&'a str, Error<&'a str>>
+where
+    R: Deref<Target = [T]>,
+{
+    if input.is_empty() {
+        return if dialect.allow_empty {
+            Ok(input)
+        } else {
+            Err(Error::new(input, ErrorCode::NoInput))
+        };
+    }
+    let mut rest = input;
+    let mut fields = None;
+    while !rest.is_empty() {
+        each(next_record_with(&mut rest, &mut fields, &record)?);
+    }
+    Ok(rest)
+}
+
+/// Parses the record at the start of the input with the given record parser, checking that
+/// it has as many fields as the previous records and consuming its line break
+pub(super) fn next_record_with<'a, R, T>(
+    input: &mut &'a str,
+    fields: &mut Option<usize>,
+    record: impl Fn(&'a str) -> PResult<&'a str, R>,
+) -> Result<R, Error<&'a str>>
+where
+    R: Deref<Target = [T]>,
+{
+    let start = *input;
+    let (rem_input, record) = record(start)?;
+    finish_record(input, rem_input, record.len(), fields)?;
+    Ok(record)
+}
+
+/// Checks that the record parsed from the start of the input has as many fields as the
+/// previous records, and consumes the rest of the record with its line break
+fn finish_record<'a>(
+    input: &mut &'a str,
+    rem_input: &'a str,
+    len: usize,
+    fields: &mut Option<usize>,
+) -> Result<(), Error<&'a str>> {
+    if *fields.get_or_insert(len) != len {
+        return Err(Error::failure(
+            *input,
+            Reason::InvalidInput {
+                expected: "more fields in this record",
+            },
+        ));
+    }
+
+    *input = if rem_input.is_empty() {
+        rem_input
+    } else {
+        // Incomplete line breaks, like a CR at the end of partial input, are passed through
+        let (rem_input, _) = line_break(rem_input).map_err(|err| {
+            if err.is_incomplete() {
+                return err;
+            }
+            Error::failure(
+                rem_input,
+                Reason::InvalidInput {
+                    expected: "comma or a line break",
+                },
+            )
+        })?;
+        rem_input
+    };
+    Ok(())
+}
+
+impl<'a> Iterator for Records<'a> {
+    type Item = Result<CsvRecord, Error<&'a str>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let next = self.next_record();
+        self.done |= next.is_err();
+        Some(next)
+    }
+}
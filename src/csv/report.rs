@@ -0,0 +1,121 @@
+//! Reports on what the parser encountered in a document
+
+use super::*;
+use std::time::{Duration, Instant};
+
+/// Suspicious but valid input found while parsing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ParseWarning {
+    /// The document starts with a byte order mark, which is kept in the first field
+    ByteOrderMark,
+    /// Records are terminated by both `\n` and `\r\n`
+    MixedLineEndings,
+    /// An unquoted field ends with whitespace, which is kept in the field; refers to the
+    /// first such record, counting from zero
+    TrailingWhitespace { record: usize },
+    /// A double quote that does not delimit a quoted field was kept as text, as the dialect's
+    /// `StrayQuotes::Literal` allows; refers to the first such record, counting from zero
+    StrayQuote { record: usize },
+    /// Blanks at the start or the end of a record were dropped, as whitespace-delimited
+    /// dialects do; refers to the first such record, counting from zero
+    TrimmedWhitespace { record: usize },
+}
+
+/// Summary of a successful parse
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParseReport {
+    pub records: usize,
+    pub fields: usize,
+    /// Number of fields enclosed in double quotes
+    pub quoted_fields: usize,
+    pub warnings: Vec<ParseWarning>,
+    /// Time spent parsing and inspecting the document
+    pub elapsed: Duration,
+}
+
+/// Parses a CSV document written in the given dialect, reporting on the parsed input
+pub fn parse_reported<'a>(
+    input: &'a str,
+    dialect: &Dialect,
+) -> PResult<&'a str, (Vec<CsvRecord>, ParseReport)> {
+    let start = Instant::now();
+    let (rem_input, records) = parse_with(input, dialect)?;
+    let mut report = inspect(input, dialect);
+    report.records = records.len();
+    report.fields = records.iter().map(Vec::len).sum();
+    report.elapsed = start.elapsed();
+    Ok((rem_input, (records, report)))
+}
+
+/// Walks the raw text of an already validated document
+pub(super) fn inspect<'a>(input: &'a str, dialect: &Dialect) -> ParseReport {
+    let mut report = ParseReport::default();
+    if input.starts_with('\u{feff}') {
+        report.warnings.push(ParseWarning::ByteOrderMark);
+    }
+
+    let (mut lf, mut crlf) = (false, false);
+    let mut trailing_whitespace = None;
+    let mut stray_quote = None;
+    let mut trimmed_whitespace = None;
+    let mut trim = |text: &'a str, record: usize| {
+        let trimmed = trim_blanks(text, dialect);
+        if trimmed.len() < text.len() {
+            trimmed_whitespace.get_or_insert(record);
+        }
+        trimmed
+    };
+    let mut record = 0;
+    let mut rest = trim(input, record);
+    while let Ok((next_input, raw)) = raw_field(dialect)(rest) {
+        // An opening quote without a closing one was read as text by a lenient dialect
+        if dialect.escape.is_none() && raw.starts_with('"') && raw[1..].contains('"') {
+            report.quoted_fields += 1;
+        } else {
+            if dialect.escape.is_none() && raw.contains('"') {
+                stray_quote.get_or_insert(record);
+            }
+            if raw.ends_with([' ', '\t']) {
+                trailing_whitespace.get_or_insert(record);
+            }
+        }
+        rest = next_input;
+        if let Ok((next_input, _)) = delimiter(dialect)(rest) {
+            rest = next_input;
+        } else if let Some(next_input) = trim(rest, record).strip_prefix("\r\n") {
+            crlf = true;
+            record += 1;
+            rest = trim(next_input, record);
+        } else if let Some(next_input) = trim(rest, record).strip_prefix('\n') {
+            lf = true;
+            record += 1;
+            rest = trim(next_input, record);
+        } else {
+            trim(rest, record);
+            break;
+        }
+        if rest.is_empty() {
+            break;
+        }
+    }
+
+    if lf && crlf {
+        report.warnings.push(ParseWarning::MixedLineEndings);
+    }
+    if let Some(record) = trailing_whitespace {
+        report
+            .warnings
+            .push(ParseWarning::TrailingWhitespace { record });
+    }
+    if let Some(record) = stray_quote {
+        report.warnings.push(ParseWarning::StrayQuote { record });
+    }
+    if let Some(record) = trimmed_whitespace {
+        report
+            .warnings
+            .push(ParseWarning::TrimmedWhitespace { record });
+    }
+    report
+}
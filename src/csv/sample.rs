@@ -0,0 +1,203 @@
+//! Previews of CSV documents: head, tail and random samples
+
+use super::*;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Size of the blocks read from the end of a file by `tail_file`
+const TAIL_BLOCK_LEN: u64 = 64 * 1024;
+
+/// Parses only the first `n` records of a CSV document, returning the unparsed remainder
+pub fn head(input: &str, n: usize) -> PResult<&str, Vec<CsvRecord>> {
+    head_with(input, n, &Dialect::default())
+}
+
+/// Parses only the first `n` records of a CSV document written in the given dialect,
+/// returning the unparsed remainder
+pub fn head_with<'a>(
+    input: &'a str,
+    n: usize,
+    dialect: &Dialect,
+) -> PResult<&'a str, Vec<CsvRecord>> {
+    let mut records = records_with(input, dialect);
+    let head = records.by_ref().take(n).collect::<Result<Vec<_>, _>>()?;
+    Ok((records.remaining(), head))
+}
+
+/// Returns the last `n` records of a CSV document, keeping at most `n` records in memory
+pub fn tail(input: &str, n: usize) -> PResult<&str, Vec<CsvRecord>> {
+    tail_with(input, n, &Dialect::default())
+}
+
+/// Returns the last `n` records of a CSV document written in the given dialect, keeping at
+/// most `n` records in memory
+pub fn tail_with<'a>(
+    input: &'a str,
+    n: usize,
+    dialect: &Dialect,
+) -> PResult<&'a str, Vec<CsvRecord>> {
+    let mut tail = VecDeque::with_capacity(n);
+    for record in records_with(input, dialect) {
+        let record = record?;
+        if n == 0 {
+            continue;
+        }
+        if tail.len() == n {
+            tail.pop_front();
+        }
+        tail.push_back(record);
+    }
+    Ok(("", tail.into()))
+}
+
+/// Returns the last `n` records of a CSV file, reading it backwards from the end
+///
+/// Only the final records are read and parsed, however large the file is.
+pub fn tail_file<P: AsRef<Path>>(path: P, n: usize) -> Result<Vec<CsvRecord>, FileError> {
+    tail_file_with(path, n, &Dialect::default())
+}
+
+/// Returns the last `n` records of a CSV file written in the given dialect, reading it
+/// backwards from the end
+///
+/// A line break ends a record if it is followed by an even number of double quotes up to the
+/// end of the file, which holds for every valid document unless the dialect keeps stray
+/// quotes. Error offsets are counted from the start of the file.
+pub fn tail_file_with<P: AsRef<Path>>(
+    path: P,
+    n: usize,
+    dialect: &Dialect,
+) -> Result<Vec<CsvRecord>, FileError> {
+    if n == 0 {
+        return Ok(Vec::new());
+    }
+    let mut file = File::open(path)?;
+    let len = file.seek(SeekFrom::End(0))?;
+    let escape = dialect.escape.map(|escape| escape as u32);
+    let is_escape = |byte: &u8| Some(u32::from(*byte)) == escape;
+    // Blocks read so far, from the end of the file backwards; they are joined once the start
+    // of the last records is found
+    let mut blocks = Vec::new();
+    let mut pos = len;
+    let mut quotes = 0;
+    let mut found = 0;
+    // Start of the record after a line break, and the escapes before it, when the escapes
+    // could continue before the blocks read so far
+    let mut pending: Option<(u64, usize)> = None;
+    let start = 'scan: loop {
+        if pos == 0 {
+            break 0;
+        }
+        let block_len = pos.min(TAIL_BLOCK_LEN);
+        pos -= block_len;
+        file.seek(SeekFrom::Start(pos))?;
+        let mut block = vec![0; block_len as usize];
+        file.read_exact(&mut block)?;
+
+        if let Some((record_start, escapes)) = pending.take() {
+            let more = block
+                .iter()
+                .rev()
+                .take_while(|byte| is_escape(byte))
+                .count();
+            if more == block.len() && pos > 0 {
+                pending = Some((record_start, escapes + more));
+            } else if (escapes + more) % 2 == 0 {
+                found += 1;
+                if found == n {
+                    blocks.push(block);
+                    break 'scan record_start;
+                }
+            }
+        }
+        let mut index = if pending.is_some() { 0 } else { block.len() };
+        while index > 0 {
+            index -= 1;
+            match block[index] {
+                b'"' if escape.is_none() => quotes += 1,
+                // The final line break only terminates the last record
+                b'\n' if quotes % 2 == 0 && pos + index as u64 + 1 < len => {
+                    if escape.is_some() {
+                        let escapes = block[..index]
+                            .iter()
+                            .rev()
+                            .take_while(|byte| is_escape(byte))
+                            .count();
+                        // The escapes could continue before the bytes read so far
+                        if escapes == index && pos > 0 {
+                            pending = Some((pos + index as u64 + 1, escapes));
+                            break;
+                        }
+                        if escapes % 2 == 1 {
+                            continue;
+                        }
+                    }
+                    found += 1;
+                    if found == n {
+                        blocks.push(block);
+                        break 'scan pos + index as u64 + 1;
+                    }
+                }
+                _ => {}
+            }
+        }
+        blocks.push(block);
+    };
+
+    blocks.reverse();
+    let tail = blocks.concat();
+    let offset = (start - pos) as usize;
+    let input = std::str::from_utf8(&tail[offset..])?;
+    parse_detached(input, dialect).map_err(|err| match err {
+        FileError::Parse { offset, code } => FileError::Parse {
+            offset: start as usize + offset,
+            code,
+        },
+        err => err,
+    })
+}
+
+/// Returns `n` records chosen uniformly at random, in document order
+///
+/// Uses reservoir sampling, so at most `n` records are kept in memory. The same seed always
+/// selects the same records.
+pub fn sample(input: &str, n: usize, seed: u64) -> PResult<&str, Vec<CsvRecord>> {
+    let mut rng = SplitMix64(seed);
+    let mut reservoir: Vec<(usize, CsvRecord)> = Vec::with_capacity(n);
+    for (index, record) in records(input).enumerate() {
+        let record = record?;
+        if reservoir.len() < n {
+            reservoir.push((index, record));
+        } else {
+            let slot = rng.below(index as u64 + 1) as usize;
+            if slot < n {
+                reservoir[slot] = (index, record);
+            }
+        }
+    }
+    reservoir.sort_by_key(|(index, _)| *index);
+    Ok((
+        "",
+        reservoir.into_iter().map(|(_, record)| record).collect(),
+    ))
+}
+
+/// Small, seedable pseudo-random number generator
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `0..bound`; the modulo bias is negligible for record counts
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+}
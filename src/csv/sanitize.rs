@@ -0,0 +1,50 @@
+//! Detection and neutralization of spreadsheet formula injection
+//!
+//! Follows the OWASP guidance on CSV injection: fields starting with `=`, `+`, `-`, `@`, a tab
+//! or a carriage return may be evaluated as formulas by spreadsheet applications. Negative
+//! numbers are flagged as well, since the guidance makes no exception for them.
+
+use super::*;
+use std::borrow::Cow;
+
+/// Handling of fields that a spreadsheet application could evaluate as formulas
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FormulaPolicy {
+    /// Fields are written as they are
+    #[default]
+    Allow,
+    /// Fields are prefixed with a single quote, so that they are shown as text
+    Neutralize,
+    /// Records with such fields are rejected
+    Reject,
+}
+
+/// Returns whether a spreadsheet application could evaluate the field as a formula
+pub fn is_formula(field: &str) -> bool {
+    field.starts_with(['=', '+', '-', '@', '\t', '\r'])
+}
+
+/// Prefixes the field with a single quote if it could be evaluated as a formula
+pub fn neutralize_formula(field: &str) -> Cow<'_, str> {
+    if is_formula(field) {
+        Cow::Owned(format!("'{}", field))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+/// Returns the record and field positions of all fields that could be evaluated as formulas
+pub fn find_formulas(records: &[CsvRecord]) -> Vec<(usize, usize)> {
+    records
+        .iter()
+        .enumerate()
+        .flat_map(|(i, record)| {
+            record
+                .iter()
+                .enumerate()
+                .filter(|(_, field)| is_formula(field))
+                .map(move |(j, _)| (i, j))
+        })
+        .collect()
+}
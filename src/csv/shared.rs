@@ -0,0 +1,49 @@
+//! Parsers built from a configuration shared between threads
+
+use super::*;
+use std::sync::Arc;
+
+/// Document parser that shares its dialect with other instances
+///
+/// Cloning only bumps a reference count, so a thread pool can hand one instance to every
+/// worker while keeping a single configuration object
+#[derive(Debug, Clone)]
+pub struct CsvParser {
+    dialect: Arc<Dialect>,
+}
+
+impl CsvParser {
+    /// Creates a parser for documents in the given dialect
+    pub fn new(dialect: Dialect) -> Self {
+        CsvParser {
+            dialect: Arc::new(dialect),
+        }
+    }
+
+    /// Creates a parser that shares an existing dialect
+    pub fn shared(dialect: Arc<Dialect>) -> Self {
+        CsvParser { dialect }
+    }
+
+    /// Returns the dialect used by this parser
+    pub fn dialect(&self) -> &Dialect {
+        &self.dialect
+    }
+
+    /// Iterates over the records of a document
+    pub fn records<'a>(&self, input: &'a str) -> Records<'a> {
+        records_with(input, &self.dialect)
+    }
+}
+
+impl Default for CsvParser {
+    fn default() -> Self {
+        CsvParser::new(Dialect::default())
+    }
+}
+
+impl<'a> Parser<&'a str, Vec<CsvRecord>> for CsvParser {
+    fn parse(&mut self, input: &'a str) -> PResult<&'a str, Vec<CsvRecord>> {
+        parse_with(input, &self.dialect)
+    }
+}
@@ -0,0 +1,215 @@
+//! Sorting of parsed CSV records
+
+use super::*;
+use std::cmp::Ordering;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+/// How the values of a sort key are compared
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compare {
+    Lexicographic,
+    /// Numbers compare by value and sort before values that are not numbers
+    Numeric,
+}
+
+/// Sort direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    Ascending,
+    Descending,
+}
+
+/// Placement of empty (null) values, regardless of the sort direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Nulls {
+    First,
+    Last,
+}
+
+/// Column to sort records by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortKey {
+    pub column: usize,
+    pub compare: Compare,
+    pub order: Order,
+    pub nulls: Nulls,
+}
+
+impl SortKey {
+    /// Creates a key sorting the column lexicographically in ascending order, nulls last
+    pub fn new(column: usize) -> Self {
+        SortKey {
+            column,
+            compare: Compare::Lexicographic,
+            order: Order::Ascending,
+            nulls: Nulls::Last,
+        }
+    }
+
+    /// Compares values of this key as numbers
+    pub fn numeric(mut self) -> Self {
+        self.compare = Compare::Numeric;
+        self
+    }
+
+    /// Sorts this key in descending order
+    pub fn descending(mut self) -> Self {
+        self.order = Order::Descending;
+        self
+    }
+
+    /// Places empty values of this key first
+    pub fn nulls_first(mut self) -> Self {
+        self.nulls = Nulls::First;
+        self
+    }
+
+    fn value<'r>(&self, record: &'r CsvRecord) -> Option<&'r str> {
+        record
+            .get(self.column)
+            .map(String::as_str)
+            .filter(|value| !value.is_empty())
+    }
+
+    fn cmp(&self, left: &CsvRecord, right: &CsvRecord) -> Ordering {
+        match (self.value(left), self.value(right)) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) if self.nulls == Nulls::First => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) if self.nulls == Nulls::First => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (Some(left), Some(right)) => {
+                let ordering = match self.compare {
+                    Compare::Lexicographic => left.cmp(right),
+                    Compare::Numeric => cmp_numeric(left, right),
+                };
+                match self.order {
+                    Order::Ascending => ordering,
+                    Order::Descending => ordering.reverse(),
+                }
+            }
+        }
+    }
+}
+
+fn cmp_numeric(left: &str, right: &str) -> Ordering {
+    match (left.trim().parse::<f64>(), right.trim().parse::<f64>()) {
+        (Ok(left), Ok(right)) => left.total_cmp(&right),
+        (Ok(_), Err(_)) => Ordering::Less,
+        (Err(_), Ok(_)) => Ordering::Greater,
+        (Err(_), Err(_)) => left.cmp(right),
+    }
+}
+
+fn cmp_records(by: &[SortKey], left: &CsvRecord, right: &CsvRecord) -> Ordering {
+    by.iter()
+        .map(|key| key.cmp(left, right))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+/// Sorts records by the given keys, keeping the relative order of equal records
+pub fn sort(records: &mut [CsvRecord], by: &[SortKey]) {
+    records.sort_by(|left, right| cmp_records(by, left, right));
+}
+
+/// Sorts records that do not fit in memory
+///
+/// Records are read from the reader in chunks of at most `chunk_len`; every chunk is sorted
+/// and spilled to a temporary file, and the files are then merged into the writer. When
+/// `header` is set, the first record is written out first and not sorted.
+pub fn sort_external<R, W>(
+    mut reader: Reader<R>,
+    writer: &mut Writer<W>,
+    by: &[SortKey],
+    chunk_len: usize,
+    header: bool,
+) -> Result<(), FileError>
+where
+    R: BufRead,
+    W: Write,
+{
+    if header {
+        if let Some(header) = reader.read_record()? {
+            writer.write_record(&header)?;
+        }
+    }
+
+    let mut chunks = SpillFiles::default();
+    let mut chunk = Vec::with_capacity(chunk_len.max(1));
+    loop {
+        let record = reader.read_record()?;
+        let done = record.is_none();
+        chunk.extend(record);
+        if chunk.len() >= chunk_len.max(1) || (done && !chunk.is_empty()) {
+            sort(&mut chunk, by);
+            chunks.spill(&chunk)?;
+            chunk.clear();
+        }
+        if done {
+            break;
+        }
+    }
+
+    let mut sources = chunks
+        .paths
+        .iter()
+        .map(|path| Ok(Reader::new(BufReader::new(File::open(path)?))))
+        .collect::<Result<Vec<_>, FileError>>()?;
+    let mut heads = sources
+        .iter_mut()
+        .map(Reader::read_record)
+        .collect::<Result<Vec<_>, _>>()?;
+    loop {
+        // Chunks are few compared to records, so a linear scan for the smallest head is enough
+        let next = heads
+            .iter()
+            .enumerate()
+            .filter_map(|(i, head)| head.as_ref().map(|record| (i, record)))
+            .min_by(|(_, left), (_, right)| cmp_records(by, left, right))
+            .map(|(i, _)| i);
+        match next {
+            Some(i) => {
+                let record = std::mem::replace(&mut heads[i], sources[i].read_record()?);
+                writer.write_record(record.expect("head is present"))?;
+            }
+            None => break,
+        }
+    }
+    Ok(())
+}
+
+/// Temporary files holding sorted chunks, removed when dropped
+#[derive(Default)]
+struct SpillFiles {
+    paths: Vec<PathBuf>,
+}
+
+impl SpillFiles {
+    fn spill(&mut self, records: &[CsvRecord]) -> Result<(), FileError> {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "libparse-sort-{}-{}.csv",
+            std::process::id(),
+            NEXT_ID.fetch_add(1, AtomicOrdering::Relaxed)
+        ));
+        self.paths.push(path.clone());
+        let mut writer = Writer::new(BufWriter::new(File::create(path)?));
+        for record in records {
+            writer.write_record(record)?;
+        }
+        writer.into_inner().flush()?;
+        Ok(())
+    }
+}
+
+impl Drop for SpillFiles {
+    fn drop(&mut self) {
+        for path in &self.paths {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
@@ -0,0 +1,46 @@
+//! Records backed by collections other than `Vec`
+
+use super::*;
+use std::ops::Deref;
+
+/// Single CSV record that keeps up to 16 fields inline, without allocating the list of fields
+#[cfg(feature = "smallvec")]
+pub type SmallRecord = smallvec::SmallVec<[String; 16]>;
+
+/// Parses a CSV document written in the given dialect, collecting the fields of each record
+/// into the given collection type
+///
+/// Any collection of strings that can be built from an iterator works, such as
+/// `Box<[String]>` for records without spare capacity.
+pub fn parse_collected<'a, R>(input: &'a str, dialect: &Dialect) -> PResult<&'a str, Vec<R>>
+where
+    R: FromIterator<String> + Deref<Target = [String]>,
+{
+    let field = field_with(dialect);
+    let record = |input: &'a str| {
+        let mut rest = trim_blanks(input, dialect);
+        let mut first = true;
+        let fields = std::iter::from_fn(|| {
+            if !first {
+                rest = delimiter(dialect)(rest).ok()?.0;
+            }
+            first = false;
+            Some(field(rest).map(|(next_input, field)| {
+                rest = next_input;
+                field
+            }))
+        })
+        .collect::<Result<R, _>>()?;
+        Ok((trim_blanks(rest, dialect), fields))
+    };
+    let mut records = Vec::new();
+    let rest = for_each_record(input, dialect, record, |record| records.push(record))?;
+    Ok((rest, records))
+}
+
+/// Parses a CSV document written in the given dialect into records that keep up to 16
+/// fields inline
+#[cfg(feature = "smallvec")]
+pub fn parse_small<'a>(input: &'a str, dialect: &Dialect) -> PResult<&'a str, Vec<SmallRecord>> {
+    parse_collected(input, dialect)
+}
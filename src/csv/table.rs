@@ -0,0 +1,133 @@
+//! Table rendering of parsed records for terminal and markdown display
+
+use super::*;
+
+/// Output style of a rendered table
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TableStyle {
+    #[default]
+    Plain,
+    Markdown,
+}
+
+/// Options for rendering records as a table
+#[derive(Debug, Clone, Default)]
+pub struct TableOptions {
+    pub style: TableStyle,
+    /// Treat the first record as a header row and underline it
+    pub header: bool,
+    /// Truncate cells wider than this many columns, marking them with an ellipsis
+    pub max_width: Option<usize>,
+}
+
+/// Renders records as a table with aligned columns
+///
+/// Widths are measured in terminal columns with the `unicode-width` feature, so that wide
+/// CJK characters and emoji take two columns and combining marks none; otherwise every `char`
+/// takes one column. Line breaks and tabs inside fields are shown escaped so that every record
+/// stays on one line
+pub fn render_table(records: &[CsvRecord], options: &TableOptions) -> String {
+    let cells: Vec<Vec<String>> = records
+        .iter()
+        .map(|record| record.iter().map(|field| cell(field, options)).collect())
+        .collect();
+    let columns = cells.iter().map(Vec::len).max().unwrap_or(0);
+    let mut widths = vec![0; columns];
+    for row in &cells {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(display_width(cell));
+        }
+    }
+    if options.style == TableStyle::Markdown {
+        // Markdown separator rows need at least three dashes
+        widths.iter_mut().for_each(|width| *width = (*width).max(3));
+    }
+
+    let mut table = String::new();
+    for (i, row) in cells.iter().enumerate() {
+        render_row(&mut table, row, &widths, options.style);
+        if i == 0 && (options.header || options.style == TableStyle::Markdown) {
+            render_separator(&mut table, &widths, options.style);
+        }
+    }
+    table
+}
+
+fn cell(field: &str, options: &TableOptions) -> String {
+    let mut text = field
+        .replace('\r', "\\r")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t");
+    if options.style == TableStyle::Markdown {
+        text = text.replace('|', "\\|");
+    }
+    match options.max_width {
+        Some(max) if display_width(&text) > max => truncate(&text, max),
+        _ => text,
+    }
+}
+
+/// Beginning of the text that fits in the given width together with a trailing ellipsis
+fn truncate(text: &str, max: usize) -> String {
+    if max == 0 {
+        return String::new();
+    }
+    let mut width = 0;
+    let mut truncated = String::new();
+    for ch in text.chars() {
+        width += char_width(ch);
+        if width > max - 1 {
+            break;
+        }
+        truncated.push(ch);
+    }
+    truncated.push('…');
+    truncated
+}
+
+/// Number of terminal columns the text takes up
+#[cfg(feature = "unicode-width")]
+fn display_width(text: &str) -> usize {
+    unicode_width::UnicodeWidthStr::width(text)
+}
+
+#[cfg(not(feature = "unicode-width"))]
+fn display_width(text: &str) -> usize {
+    text.chars().count()
+}
+
+#[cfg(feature = "unicode-width")]
+fn char_width(ch: char) -> usize {
+    unicode_width::UnicodeWidthChar::width(ch).unwrap_or(0)
+}
+
+#[cfg(not(feature = "unicode-width"))]
+fn char_width(_: char) -> usize {
+    1
+}
+
+fn render_row(table: &mut String, row: &[String], widths: &[usize], style: TableStyle) {
+    let empty = String::new();
+    let cells = widths.iter().enumerate().map(|(i, width)| {
+        let cell = row.get(i).unwrap_or(&empty);
+        format!("{}{}", cell, " ".repeat(width - display_width(cell)))
+    });
+    let line = match style {
+        TableStyle::Plain => cells.collect::<Vec<_>>().join(" | "),
+        TableStyle::Markdown => format!("| {} |", cells.collect::<Vec<_>>().join(" | ")),
+    };
+    table.push_str(line.trim_end());
+    table.push('\n');
+}
+
+fn render_separator(table: &mut String, widths: &[usize], style: TableStyle) {
+    let dashes = widths
+        .iter()
+        .map(|width| "-".repeat(*width))
+        .collect::<Vec<_>>();
+    match style {
+        TableStyle::Plain => table.push_str(&dashes.join("-+-")),
+        TableStyle::Markdown => table.push_str(&format!("| {} |", dashes.join(" | "))),
+    }
+    table.push('\n');
+}
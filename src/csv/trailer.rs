@@ -0,0 +1,62 @@
+//! Separation of trailer records, like totals or checksums, from the end of CSV documents
+
+use super::*;
+
+/// CSV document split into its regular records and the trailer records at its end
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WithTrailer {
+    pub records: Vec<CsvRecord>,
+    pub trailer: Vec<CsvRecord>,
+}
+
+/// Parses a CSV document written in the given dialect, separating trailer records recognized
+/// by the predicate
+///
+/// Trailer records may have any number of fields, but must all come after the regular
+/// records. The first record is never a trailer.
+pub fn parse_with_trailer<'a, F>(
+    input: &'a str,
+    dialect: &Dialect,
+    is_trailer: F,
+) -> PResult<&'a str, WithTrailer>
+where
+    F: Fn(&CsvRecord) -> bool,
+{
+    let record = record_with(dialect);
+    let (mut rem_input, first_record) = record(input)?;
+    let len = first_record.len();
+    let mut records = vec![first_record];
+    let mut trailer = Vec::new();
+    while !rem_input.is_empty() {
+        let (next_input, _) = line_break(rem_input).map_err(|_| {
+            Error::failure(
+                rem_input,
+                Reason::InvalidInput {
+                    expected: "comma or a line break",
+                },
+            )
+        })?;
+        if next_input.is_empty() {
+            rem_input = next_input;
+            break;
+        }
+        let (next_input, next_record) = record(next_input)?;
+        let expected = if is_trailer(&next_record) {
+            trailer.push(next_record);
+            None
+        } else if !trailer.is_empty() {
+            Some("trailer records at the end of the document")
+        } else if next_record.len() != len {
+            Some("more fields in this record")
+        } else {
+            records.push(next_record);
+            None
+        };
+        if let Some(expected) = expected {
+            return Err(Error::failure(rem_input, Reason::InvalidInput { expected }));
+        }
+        rem_input = next_input;
+    }
+    Ok((rem_input, WithTrailer { records, trailer }))
+}
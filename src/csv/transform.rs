@@ -0,0 +1,139 @@
+//! Per-column value transformations applied while parsing
+
+use super::*;
+
+type Transform<'t> = Box<dyn Fn(String) -> String + 't>;
+
+/// Transformations of field values, registered per column
+///
+/// Transformations of the same column run in the order they were registered. Columns
+/// referenced by name are resolved against the header row, which is left untransformed.
+#[derive(Default)]
+pub struct Transforms<'t> {
+    header: bool,
+    columns: Vec<(Column, Transform<'t>)>,
+}
+
+impl<'t> Transforms<'t> {
+    /// Creates an empty set of transformations for a document without a header row
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Treats the first record as a header row
+    pub fn with_header(mut self) -> Self {
+        self.header = true;
+        self
+    }
+
+    /// Registers a custom transformation of a column
+    pub fn column<C, F>(mut self, column: C, transform: F) -> Self
+    where
+        C: Into<Column>,
+        F: Fn(String) -> String + 't,
+    {
+        self.columns.push((column.into(), Box::new(transform)));
+        self
+    }
+
+    /// Trims whitespace around the values of a column
+    pub fn trim<C: Into<Column>>(self, column: C) -> Self {
+        self.column(column, |value| {
+            let trimmed = value.trim();
+            if trimmed.len() == value.len() {
+                value
+            } else {
+                trimmed.to_string()
+            }
+        })
+    }
+
+    /// Converts the values of a column to uppercase
+    pub fn uppercase<C: Into<Column>>(self, column: C) -> Self {
+        self.column(column, |value| value.to_uppercase())
+    }
+
+    /// Converts the values of a column to lowercase
+    pub fn lowercase<C: Into<Column>>(self, column: C) -> Self {
+        self.column(column, |value| value.to_lowercase())
+    }
+
+    /// Replaces all occurrences of a pattern in the values of a column
+    pub fn replace<C: Into<Column>>(self, column: C, from: &'t str, to: &'t str) -> Self {
+        self.column(column, move |value| {
+            if value.contains(from) {
+                value.replace(from, to)
+            } else {
+                value
+            }
+        })
+    }
+
+    /// Replaces all matches of a regular expression in the values of a column
+    ///
+    /// The replacement may refer to capture groups, like `$1` or `${name}`. Panics if the
+    /// pattern is not a valid regular expression.
+    #[cfg(feature = "regex")]
+    pub fn replace_regex<C: Into<Column>>(self, column: C, pattern: &str, to: &'t str) -> Self {
+        let regex = regex::Regex::new(pattern).expect("invalid regular expression");
+        self.column(column, move |value| match regex.replace_all(&value, to) {
+            std::borrow::Cow::Borrowed(_) => value,
+            std::borrow::Cow::Owned(replaced) => replaced,
+        })
+    }
+
+    /// Resolves the index of every transformed column, against the header if there is one
+    fn resolve(&self, header: Option<&CsvRecord>) -> Option<Vec<usize>> {
+        self.columns
+            .iter()
+            .map(|(column, _)| match (column, header) {
+                (Column::Index(index), None) => Some(*index),
+                (column, Some(header)) => column.resolve(header),
+                (Column::Name(_), None) => None,
+            })
+            .collect()
+    }
+
+    fn apply(&self, record: &mut CsvRecord, indices: &[usize]) {
+        for ((_, transform), index) in self.columns.iter().zip(indices) {
+            if let Some(field) = record.get_mut(*index) {
+                *field = transform(std::mem::take(field));
+            }
+        }
+    }
+}
+
+/// Parses a CSV document written in the given dialect, transforming every record as it is
+/// parsed
+pub fn parse_transformed<'a>(
+    input: &'a str,
+    dialect: &Dialect,
+    transforms: &Transforms,
+) -> PResult<&'a str, Vec<CsvRecord>> {
+    let mut records = records_with(input, dialect);
+    let mut output = Vec::new();
+    let header = if transforms.header {
+        match records.next() {
+            Some(header) => Some(header?),
+            None => return Err(Error::new(input, ErrorCode::NoInput)),
+        }
+    } else {
+        None
+    };
+    let indices = transforms.resolve(header.as_ref()).ok_or_else(|| {
+        Error::failure(
+            input,
+            Reason::InvalidInput {
+                expected: "header with all transformed columns",
+            },
+        )
+    })?;
+
+    output.extend(header);
+    for record in records {
+        let mut record = record?;
+        transforms.apply(&mut record, &indices);
+        output.push(record);
+    }
+    Ok(("", output))
+}
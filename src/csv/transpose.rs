@@ -0,0 +1,27 @@
+//! Transposition of documents whose header is the first column instead of the first row
+
+use super::*;
+
+/// Swaps the rows and columns of the records
+///
+/// Records shorter than the longest record are padded with empty fields.
+pub fn transpose(records: Vec<CsvRecord>) -> Vec<CsvRecord> {
+    let width = records.iter().map(Vec::len).max().unwrap_or(0);
+    let mut columns = vec![Vec::with_capacity(records.len()); width];
+    for record in records {
+        let len = record.len();
+        for (column, field) in columns.iter_mut().zip(record) {
+            column.push(field);
+        }
+        for column in &mut columns[len..] {
+            column.push(String::new());
+        }
+    }
+    columns
+}
+
+/// Parses a CSV document written in the given dialect whose first column holds the header,
+/// returning it transposed so that the header is the first record
+pub fn parse_transposed<'a>(input: &'a str, dialect: &Dialect) -> PResult<&'a str, Vec<CsvRecord>> {
+    parse_with(input, dialect).map(|(rem_input, records)| (rem_input, transpose(records)))
+}
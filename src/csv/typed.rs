@@ -0,0 +1,375 @@
+//! Typed view of CSV documents, coercing columns to values of declared types
+
+use super::*;
+use std::fmt;
+
+/// Conventions for writing numbers, which vary between locales
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct NumberFormat {
+    pub decimal_separator: char,
+    /// Separator of digit groups in the integer part, like `.` in `1.234,56`
+    pub thousands_separator: Option<char>,
+}
+
+impl NumberFormat {
+    /// Format used in most of continental Europe: `1.234,56`
+    pub fn european() -> Self {
+        NumberFormat {
+            decimal_separator: ',',
+            thousands_separator: Some('.'),
+        }
+    }
+
+    /// Parses a number written in this format
+    ///
+    /// Thousands separators are optional, but when present they must separate groups of
+    /// exactly three digits.
+    pub fn parse_f64(&self, text: &str) -> Option<f64> {
+        let normalized = self.normalize(text)?;
+        let (_, number) = all_consuming(number_f64).parse(normalized.as_str()).ok()?;
+        Some(number)
+    }
+
+    /// Parses an integer written in this format
+    pub fn parse_i64(&self, text: &str) -> Option<i64> {
+        let normalized = self.normalize(text)?;
+        let (_, number) = all_consuming(number_i64).parse(normalized.as_str()).ok()?;
+        Some(number)
+    }
+
+    /// Rewrites a number into the format understood by the `chars` number parsers
+    fn normalize(&self, text: &str) -> Option<String> {
+        let (integer, fraction) = match text.split_once(self.decimal_separator) {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (text, None),
+        };
+        let mut normalized = String::with_capacity(text.len());
+        match self.thousands_separator {
+            Some(separator) if integer.contains(separator) => {
+                let digits = integer.trim_start_matches(['+', '-']);
+                normalized.push_str(&integer[..integer.len() - digits.len()]);
+                let mut groups = digits.split(separator);
+                let first = groups.next()?;
+                if first.is_empty() || first.len() > 3 {
+                    return None;
+                }
+                normalized.push_str(first);
+                for group in groups {
+                    if group.len() != 3 {
+                        return None;
+                    }
+                    normalized.push_str(group);
+                }
+            }
+            _ => normalized.push_str(integer),
+        }
+        if let Some(fraction) = fraction {
+            normalized.push('.');
+            normalized.push_str(fraction);
+        }
+        Some(normalized)
+    }
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        NumberFormat {
+            decimal_separator: '.',
+            thousands_separator: None,
+        }
+    }
+}
+
+/// Type that the values of a column are coerced to
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ColumnType {
+    Text,
+    Integer,
+    Float,
+    /// Values from the truthy and falsy token sets of the schema
+    Boolean,
+    /// One of the allowed values, kept as text
+    Enum(Vec<String>),
+}
+
+impl ColumnType {
+    /// Creates an enum column type with the given allowed values
+    pub fn one_of(values: &[&str]) -> Self {
+        ColumnType::Enum(values.iter().map(|value| value.to_string()).collect())
+    }
+}
+
+/// Constraint on the fields of a column, checked before they are coerced to its type
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Constraint {
+    /// Fields are at most this many characters long
+    MaxLen(usize),
+    /// Fields are not empty
+    NonEmpty,
+}
+
+impl Constraint {
+    fn accepts(&self, text: &str) -> bool {
+        match self {
+            Constraint::MaxLen(max) => text.chars().nth(*max).is_none(),
+            Constraint::NonEmpty => !text.is_empty(),
+        }
+    }
+}
+
+impl fmt::Display for Constraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Constraint::MaxLen(max) => write!(f, "at most {} characters", max),
+            Constraint::NonEmpty => write!(f, "not empty"),
+        }
+    }
+}
+
+/// Field that violates a constraint of its column
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CellError {
+    /// Index of the record, counting from the header row, which is record 0
+    pub record: usize,
+    pub column: usize,
+    /// Name of the column in the header row
+    pub header: String,
+    /// Beginning of the field, truncated to a few characters
+    pub excerpt: String,
+    pub constraint: Constraint,
+}
+
+impl fmt::Display for CellError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "record {}, column {} ({:?}): {:?} is not {}",
+            self.record, self.column, self.header, self.excerpt, self.constraint
+        )
+    }
+}
+
+impl std::error::Error for CellError {}
+
+/// Value of a typed field
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Value {
+    /// Empty field of a non-text column
+    Null,
+    Text(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+}
+
+/// Declared column types of a document; columns that are not declared are kept as text
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct Schema {
+    columns: Vec<(Column, ColumnType)>,
+    constraints: Vec<(Column, Constraint)>,
+    number_format: NumberFormat,
+    truthy: Vec<String>,
+    falsy: Vec<String>,
+    dialect: Dialect,
+}
+
+impl Schema {
+    /// Creates a schema without declared columns
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declares the type of a column
+    pub fn column<C: Into<Column>>(mut self, column: C, column_type: ColumnType) -> Self {
+        self.columns.push((column.into(), column_type));
+        self
+    }
+
+    /// Adds a constraint on the fields of a column
+    pub fn constrain<C: Into<Column>>(mut self, column: C, constraint: Constraint) -> Self {
+        self.constraints.push((column.into(), constraint));
+        self
+    }
+
+    /// Sets the tokens of boolean columns, compared ignoring case; the defaults are `true`
+    /// and `false`
+    pub fn bool_tokens(mut self, truthy: &[&str], falsy: &[&str]) -> Self {
+        self.truthy = truthy.iter().map(|token| token.to_lowercase()).collect();
+        self.falsy = falsy.iter().map(|token| token.to_lowercase()).collect();
+        self
+    }
+
+    /// Sets the format of numbers in numeric columns
+    pub fn number_format(mut self, format: NumberFormat) -> Self {
+        self.number_format = format;
+        self
+    }
+
+    /// Sets the dialect of the document, whose null token is read as `Value::Null` in every
+    /// column
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    fn coerce(&self, column_type: &ColumnType, text: String) -> Result<Value, String> {
+        if self.dialect.is_null(&text) || (text.is_empty() && *column_type != ColumnType::Text) {
+            return Ok(Value::Null);
+        }
+        let value = match column_type {
+            ColumnType::Text => return Ok(Value::Text(text)),
+            ColumnType::Integer => self.number_format.parse_i64(&text).map(Value::Integer),
+            ColumnType::Float => self.number_format.parse_f64(&text).map(Value::Float),
+            ColumnType::Boolean => {
+                let token = text.to_lowercase();
+                if self.truthy.contains(&token) {
+                    Some(Value::Boolean(true))
+                } else if self.falsy.contains(&token) {
+                    Some(Value::Boolean(false))
+                } else {
+                    None
+                }
+            }
+            ColumnType::Enum(values) if values.contains(&text) => return Ok(Value::Text(text)),
+            ColumnType::Enum(_) => None,
+        };
+        value.ok_or(text)
+    }
+}
+
+impl Default for Schema {
+    fn default() -> Self {
+        Schema {
+            columns: Vec::new(),
+            constraints: Vec::new(),
+            number_format: NumberFormat::default(),
+            truthy: vec!["true".to_string()],
+            falsy: vec!["false".to_string()],
+            dialect: Dialect::default(),
+        }
+    }
+}
+
+/// Document with a header row and typed data records
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TypedRecords {
+    pub header: CsvRecord,
+    pub rows: Vec<Vec<Value>>,
+}
+
+/// Errors that can occur while parsing a typed document
+#[derive(Debug, PartialEq, Eq)]
+pub enum TypedError<'a> {
+    Parse(Error<&'a str>),
+    /// A declared column is not in the header
+    MissingColumn(Column),
+    /// A field could not be coerced to the type of its column; `record` counts from the
+    /// header row, which is record 0
+    Coercion {
+        record: usize,
+        column: usize,
+        value: String,
+        expected: ColumnType,
+    },
+    /// A field violates a constraint of its column
+    Cell(CellError),
+}
+
+impl fmt::Display for TypedError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypedError::Parse(err) => write!(f, "parse error: {}", err.code),
+            TypedError::MissingColumn(column) => write!(f, "missing column {:?}", column),
+            TypedError::Coercion {
+                record,
+                column,
+                value,
+                expected,
+            } => write!(
+                f,
+                "record {}, column {}: cannot read {:?} as {:?}",
+                record, column, value, expected
+            ),
+            TypedError::Cell(err) => err.fmt(f),
+        }
+    }
+}
+
+impl<'a> From<Error<&'a str>> for TypedError<'a> {
+    fn from(err: Error<&'a str>) -> Self {
+        TypedError::Parse(err)
+    }
+}
+
+/// Parses a CSV document with a header row, coercing fields to the types of their columns
+///
+/// Fields are checked against the constraints of their columns before they are coerced.
+pub fn parse_typed<'a>(input: &'a str, schema: &Schema) -> Result<TypedRecords, TypedError<'a>> {
+    let mut records = records_with(input, &schema.dialect);
+    let header = match records.next() {
+        Some(header) => header?,
+        None => return Err(Error::new(input, ErrorCode::NoInput).into()),
+    };
+    let mut types = vec![ColumnType::Text; header.len()];
+    for (column, column_type) in &schema.columns {
+        let index = column
+            .resolve(&header)
+            .ok_or_else(|| TypedError::MissingColumn(column.clone()))?;
+        types[index] = column_type.clone();
+    }
+
+    let mut constraints = vec![Vec::new(); header.len()];
+    for (column, constraint) in &schema.constraints {
+        let index = column
+            .resolve(&header)
+            .ok_or_else(|| TypedError::MissingColumn(column.clone()))?;
+        constraints[index].push(constraint);
+    }
+
+    let mut rows = Vec::new();
+    for (i, record) in records.enumerate() {
+        let record = record?;
+        for (column, (text, constraints)) in record.iter().zip(&constraints).enumerate() {
+            if let Some(constraint) = constraints.iter().find(|c| !c.accepts(text)) {
+                return Err(TypedError::Cell(CellError {
+                    record: i + 1,
+                    column,
+                    header: header[column].clone(),
+                    excerpt: crate::base::excerpt(text).to_string(),
+                    constraint: (*constraint).clone(),
+                }));
+            }
+        }
+        let row = record
+            .into_iter()
+            .zip(&types)
+            .enumerate()
+            .map(|(column, (text, column_type))| {
+                schema
+                    .coerce(column_type, text)
+                    .map_err(|value| TypedError::Coercion {
+                        record: i + 1,
+                        column,
+                        value,
+                        expected: column_type.clone(),
+                    })
+            })
+            .collect::<Result<_, _>>()?;
+        rows.push(row);
+    }
+    Ok(TypedRecords { header, rows })
+}
@@ -0,0 +1,215 @@
+//! CSV writer
+
+use super::{is_formula, neutralize_formula, Dialect, FormulaPolicy};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// Line break written after every record
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LineEnding {
+    #[default]
+    CrLf,
+    Lf,
+}
+
+impl LineEnding {
+    fn as_bytes(self) -> &'static [u8] {
+        match self {
+            LineEnding::CrLf => b"\r\n",
+            LineEnding::Lf => b"\n",
+        }
+    }
+}
+
+/// Options of the CSV output, independent of the dialect of the parsed input
+///
+/// The delimiter must not be a double quote or a line break character.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct WriterOptions {
+    pub delimiter: char,
+    pub line_ending: LineEnding,
+    /// Escape character written before delimiters, line breaks and itself instead of quoting
+    /// fields, as in dialects with an escape character
+    pub escape: Option<char>,
+    /// Terminate the last record with a line break, instead of only separating records
+    pub final_line_break: bool,
+    /// Handling of fields that spreadsheet applications could evaluate as formulas
+    pub formulas: FormulaPolicy,
+}
+
+impl Default for WriterOptions {
+    fn default() -> Self {
+        WriterOptions {
+            delimiter: ',',
+            line_ending: LineEnding::default(),
+            escape: None,
+            final_line_break: true,
+            formulas: FormulaPolicy::default(),
+        }
+    }
+}
+
+impl From<&Dialect> for WriterOptions {
+    fn from(dialect: &Dialect) -> Self {
+        WriterOptions {
+            delimiter: dialect.delimiter,
+            line_ending: dialect.line_ending,
+            escape: dialect.escape,
+            ..Default::default()
+        }
+    }
+}
+
+/// Writes CSV records to an output, quoting fields where needed
+///
+/// Every record is written to the output with a single `write_all` call, so an output that
+/// is interrupted between records never holds a partially written record.
+pub struct Writer<W> {
+    inner: W,
+    options: WriterOptions,
+    /// Whether the output ends in the middle of a line, which must be terminated before the
+    /// next record is written
+    pending_line_break: bool,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> Writer<W> {
+    /// Creates a new writer over the given output
+    pub fn new(inner: W) -> Self {
+        Self::with_options(inner, WriterOptions::default())
+    }
+
+    /// Creates a new writer over the given output, with the given output options
+    pub fn with_options(inner: W, options: WriterOptions) -> Self {
+        Writer {
+            inner,
+            options,
+            pending_line_break: false,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Writes a single record terminated by a line break, unless final line breaks are
+    /// disabled, in which case the line break is written before the next record
+    pub fn write_record<I, S>(&mut self, fields: I) -> io::Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.buffer.clear();
+        let line_ending = self.options.line_ending.as_bytes();
+        if self.pending_line_break {
+            self.buffer.extend_from_slice(line_ending);
+        }
+        let delimiter = self.options.delimiter;
+        for (i, field) in fields.into_iter().enumerate() {
+            if i > 0 {
+                let mut encoded = [0; 4];
+                self.buffer
+                    .extend_from_slice(delimiter.encode_utf8(&mut encoded).as_bytes());
+            }
+            let field = field.as_ref();
+            match self.options.formulas {
+                FormulaPolicy::Allow => write_field(&mut self.buffer, field, &self.options),
+                FormulaPolicy::Neutralize => {
+                    write_field(&mut self.buffer, &neutralize_formula(field), &self.options)
+                }
+                FormulaPolicy::Reject if is_formula(field) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("field {} could be evaluated as a formula", i),
+                    ));
+                }
+                FormulaPolicy::Reject => write_field(&mut self.buffer, field, &self.options),
+            }
+        }
+        if self.options.final_line_break {
+            self.buffer.extend_from_slice(line_ending);
+        }
+        self.inner.write_all(&self.buffer)?;
+        self.pending_line_break = !self.options.final_line_break;
+        Ok(())
+    }
+
+    /// Writes all given records
+    pub fn write_records<R, S>(&mut self, records: &[R]) -> io::Result<()>
+    where
+        R: AsRef<[S]>,
+        S: AsRef<str>,
+    {
+        for record in records {
+            self.write_record(record.as_ref())?;
+        }
+        Ok(())
+    }
+
+    /// Returns whether the output ends with a complete line
+    pub fn ends_with_line_break(&self) -> bool {
+        !self.pending_line_break
+    }
+
+    /// Flushes the underlying output
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+
+    /// Returns the underlying output
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl Writer<File> {
+    /// Opens a file for appending records, creating it if it does not exist
+    ///
+    /// If the file does not end with a line break, one is written before the first record.
+    pub fn append<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)?;
+        let mut writer = Writer::new(file.try_clone()?);
+        if file.seek(SeekFrom::End(0))? > 0 {
+            let mut last = [0];
+            file.seek(SeekFrom::End(-1))?;
+            file.read_exact(&mut last)?;
+            writer.pending_line_break = last[0] != b'\n';
+        }
+        Ok(writer)
+    }
+}
+
+fn write_field(out: &mut Vec<u8>, field: &str, options: &WriterOptions) {
+    let delimiter = options.delimiter;
+    if let Some(escape) = options.escape {
+        // NULL, which readers of the dialect keep as it is
+        if field.strip_prefix(escape) == Some("N") {
+            out.extend_from_slice(field.as_bytes());
+            return;
+        }
+        let mut escaped = String::with_capacity(field.len());
+        for ch in field.chars() {
+            match ch {
+                '\n' => escaped.extend([escape, 'n']),
+                '\r' => escaped.extend([escape, 'r']),
+                ch if ch == delimiter || ch == escape => escaped.extend([escape, ch]),
+                ch => escaped.push(ch),
+            }
+        }
+        out.extend_from_slice(escaped.as_bytes());
+    } else if field.contains([delimiter, '"', '\r', '\n']) {
+        out.push(b'"');
+        out.extend_from_slice(field.replace('"', "\"\"").as_bytes());
+        out.push(b'"');
+    } else {
+        out.extend_from_slice(field.as_bytes());
+    }
+}
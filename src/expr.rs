@@ -0,0 +1,152 @@
+//! Expression parsing with operator precedence
+//!
+//! `precedence` implements precedence climbing: operands are parsed by an atom parser, and
+//! prefix and infix operators are recognized by operator parsers that also tell how tightly
+//! each operator binds and how to apply it. `arithmetic` is an example grammar built on it.
+
+use crate::base::*;
+use crate::chars::*;
+use crate::combinators::*;
+
+/// Associativity of an infix operator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Assoc {
+    /// `a - b - c` is `(a - b) - c`
+    Left,
+    /// `a ^ b ^ c` is `a ^ (b ^ c)`
+    Right,
+}
+
+/// Prefix operator, as output by the prefix operator parser of `precedence`
+#[derive(Debug, Clone, Copy)]
+pub struct Prefix<O> {
+    /// Precedence of the operator; operators with a higher precedence bind tighter
+    pub precedence: u8,
+    pub apply: fn(O) -> O,
+}
+
+/// Infix operator, as output by the infix operator parser of `precedence`
+#[derive(Debug, Clone, Copy)]
+pub struct Infix<O> {
+    /// Precedence of the operator; operators with a higher precedence bind tighter
+    pub precedence: u8,
+    pub assoc: Assoc,
+    pub apply: fn(O, O) -> O,
+}
+
+/// Parser generator for expressions of operands parsed by `atom`, combined with operators
+/// parsed by `prefix_ops` and `infix_ops`
+///
+/// An operand of a prefix operator extends over infix operators of a higher precedence only,
+/// so `-a ^ b` is `-(a ^ b)` if `^` binds tighter than unary `-`. Grammars with parenthesized
+/// subexpressions call the expression parser recursively from the atom parser.
+#[must_use = "parsers do nothing unless used"]
+pub fn precedence<A, PP, IP, I, O>(
+    mut atom: A,
+    mut prefix_ops: PP,
+    mut infix_ops: IP,
+) -> impl Parser<I, O>
+where
+    A: Parser<I, O>,
+    PP: Parser<I, Prefix<O>>,
+    IP: Parser<I, Infix<O>>,
+    I: Rewind,
+{
+    move |input: I| climb(&mut atom, &mut prefix_ops, &mut infix_ops, 0, input)
+}
+
+fn climb<A, PP, IP, I, O>(
+    atom: &mut A,
+    prefix_ops: &mut PP,
+    infix_ops: &mut IP,
+    min_precedence: u8,
+    input: I,
+) -> PResult<I, O>
+where
+    A: Parser<I, O>,
+    PP: Parser<I, Prefix<O>>,
+    IP: Parser<I, Infix<O>>,
+    I: Rewind,
+{
+    let checkpoint = input.checkpoint();
+    let (mut input, mut lhs) = match prefix_ops.parse(input) {
+        Ok((next_input, op)) => {
+            let (next_input, operand) =
+                climb(atom, prefix_ops, infix_ops, op.precedence, next_input)?;
+            (next_input, (op.apply)(operand))
+        }
+        Err(err) if err.is_failure() || err.is_incomplete() => return Err(err),
+        Err(err) => atom.parse(err.input.rewind(checkpoint))?,
+    };
+
+    loop {
+        let checkpoint = input.checkpoint();
+        match infix_ops.parse(input) {
+            Ok((next_input, op)) if op.precedence >= min_precedence => {
+                let min_precedence = match op.assoc {
+                    Assoc::Left => op.precedence.saturating_add(1),
+                    Assoc::Right => op.precedence,
+                };
+                let (next_input, rhs) =
+                    climb(atom, prefix_ops, infix_ops, min_precedence, next_input)?;
+                lhs = (op.apply)(lhs, rhs);
+                input = next_input;
+            }
+            Ok((next_input, _)) => return Ok((next_input.rewind(checkpoint), lhs)),
+            Err(err) if err.is_failure() || err.is_incomplete() => return Err(err),
+            Err(err) => return Ok((err.input.rewind(checkpoint), lhs)),
+        }
+    }
+}
+
+fn symbol_ws<'a>(tag: &'static str) -> impl Parser<&'a str, &'a str> {
+    symbol(whitespace, tag)
+}
+
+/// Deepest nesting of parentheses accepted by `arithmetic`
+const MAX_NESTING: usize = 128;
+
+fn parenthesized(input: &str) -> PResult<&str, f64> {
+    let nested = max_depth(MAX_NESTING, arithmetic);
+    right_from_pair(symbol_ws("("), left_from_pair(nested, symbol_ws(")"))).parse(input)
+}
+
+fn arithmetic_prefix(input: &str) -> PResult<&str, Prefix<f64>> {
+    symbol_ws("-")
+        .map(|_| Prefix {
+            precedence: 3,
+            apply: |x: f64| -x,
+        })
+        .parse(input)
+}
+
+fn arithmetic_infix(input: &str) -> PResult<&str, Infix<f64>> {
+    let (next_input, op) = lexeme(whitespace, satisfy(|ch| "+-*/^".contains(ch))).parse(input)?;
+    let (precedence, assoc, apply): (u8, Assoc, fn(f64, f64) -> f64) = match op {
+        '+' => (1, Assoc::Left, |a, b| a + b),
+        '-' => (1, Assoc::Left, |a, b| a - b),
+        '*' => (2, Assoc::Left, |a, b| a * b),
+        '/' => (2, Assoc::Left, |a, b| a / b),
+        _ => (4, Assoc::Right, f64::powf),
+    };
+    Ok((
+        next_input,
+        Infix {
+            precedence,
+            assoc,
+            apply,
+        },
+    ))
+}
+
+/// Parser that evaluates an arithmetic expression of numbers, parentheses, unary `-` and the
+/// binary operators `+`, `-`, `*`, `/` and `^` (exponentiation), with the usual precedence
+///
+/// Whitespace is allowed after every token; the remaining input is returned unparsed.
+/// Parentheses nested more than 128 levels deep fail with `ErrorCode::DepthExceeded` instead
+/// of exhausting the stack.
+pub fn arithmetic(input: &str) -> PResult<&str, f64> {
+    let atom = lexeme(whitespace, number_f64).fallback_on(parenthesized);
+    let (input, _) = whitespace(input)?;
+    precedence(atom, arithmetic_prefix, arithmetic_infix).parse(input)
+}
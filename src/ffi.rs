@@ -0,0 +1,283 @@
+//! C bindings for CSV parsing
+//!
+//! All strings cross the boundary as UTF-8 buffers given by a pointer and a length; fields
+//! handed out by the library are not NUL-terminated and stay valid until the handle that owns
+//! them is freed. Functions return one of the `LIBPARSE_*` status codes.
+//!
+//! A shared library can be built with
+//! `cargo rustc --release --lib --features ffi --crate-type cdylib`.
+
+use crate::csv::{self, CsvRecord, Dialect, OwnedRecords};
+use std::slice;
+
+/// The call succeeded
+pub const LIBPARSE_OK: i32 = 0;
+/// An iterator has no more records
+pub const LIBPARSE_DONE: i32 = 1;
+/// A required pointer argument was null
+pub const LIBPARSE_ERR_NULL: i32 = -1;
+/// The input is not valid UTF-8
+pub const LIBPARSE_ERR_UTF8: i32 = -2;
+/// The input is not a valid CSV document
+pub const LIBPARSE_ERR_PARSE: i32 = -3;
+/// A record or field index is out of bounds
+pub const LIBPARSE_ERR_INDEX: i32 = -4;
+
+/// Fully parsed CSV document
+pub struct LibparseDocument {
+    records: Vec<CsvRecord>,
+}
+
+/// Record-at-a-time iterator over a CSV document, which owns a copy of the input
+pub struct LibparseIter {
+    records: OwnedRecords,
+    error_offset: usize,
+    current: CsvRecord,
+}
+
+/// Reads a UTF-8 buffer given by the caller
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes, or be null if `len` is zero
+unsafe fn input_str<'a>(data: *const u8, len: usize) -> Result<&'a str, i32> {
+    if len == 0 {
+        return Ok("");
+    }
+    if data.is_null() {
+        return Err(LIBPARSE_ERR_NULL);
+    }
+    std::str::from_utf8(slice::from_raw_parts(data, len)).map_err(|_| LIBPARSE_ERR_UTF8)
+}
+
+/// Writes a field through the caller's output pointers
+///
+/// # Safety
+///
+/// `out_data` and `out_len` must be null or valid for writes
+unsafe fn write_field(
+    field: Option<&String>,
+    out_data: *mut *const u8,
+    out_len: *mut usize,
+) -> i32 {
+    if out_data.is_null() || out_len.is_null() {
+        return LIBPARSE_ERR_NULL;
+    }
+    match field {
+        Some(field) => {
+            *out_data = field.as_ptr();
+            *out_len = field.len();
+            LIBPARSE_OK
+        }
+        None => LIBPARSE_ERR_INDEX,
+    }
+}
+
+/// Parses a whole CSV document
+///
+/// On success, stores a new document in `out_doc` that must be released with
+/// `libparse_document_free`. On a parse error, stores the byte offset of the error in
+/// `out_error_offset` when it is not null.
+///
+/// # Safety
+///
+/// `input` must point to `len` readable bytes; `out_doc` must be valid for writes and
+/// `out_error_offset` must be null or valid for writes
+#[no_mangle]
+pub unsafe extern "C" fn libparse_csv_parse(
+    input: *const u8,
+    len: usize,
+    out_doc: *mut *mut LibparseDocument,
+    out_error_offset: *mut usize,
+) -> i32 {
+    if out_doc.is_null() {
+        return LIBPARSE_ERR_NULL;
+    }
+    let input = match input_str(input, len) {
+        Ok(input) => input,
+        Err(status) => return status,
+    };
+    match csv::parse_string(input) {
+        Ok((_, records)) => {
+            *out_doc = Box::into_raw(Box::new(LibparseDocument { records }));
+            LIBPARSE_OK
+        }
+        Err(err) => {
+            if !out_error_offset.is_null() {
+                *out_error_offset = input.len() - err.input.len();
+            }
+            LIBPARSE_ERR_PARSE
+        }
+    }
+}
+
+/// Returns the number of records in a document
+///
+/// # Safety
+///
+/// `doc` must be a live document returned by `libparse_csv_parse`
+#[no_mangle]
+pub unsafe extern "C" fn libparse_document_len(doc: *const LibparseDocument) -> usize {
+    doc.as_ref().map_or(0, |doc| doc.records.len())
+}
+
+/// Returns the number of fields of a record in a document, or zero if there is no such record
+///
+/// # Safety
+///
+/// `doc` must be a live document returned by `libparse_csv_parse`
+#[no_mangle]
+pub unsafe extern "C" fn libparse_document_record_len(
+    doc: *const LibparseDocument,
+    record: usize,
+) -> usize {
+    doc.as_ref()
+        .and_then(|doc| doc.records.get(record))
+        .map_or(0, Vec::len)
+}
+
+/// Gets a field of a record in a document
+///
+/// # Safety
+///
+/// `doc` must be a live document returned by `libparse_csv_parse`; `out_data` and `out_len`
+/// must be valid for writes
+#[no_mangle]
+pub unsafe extern "C" fn libparse_document_field(
+    doc: *const LibparseDocument,
+    record: usize,
+    field: usize,
+    out_data: *mut *const u8,
+    out_len: *mut usize,
+) -> i32 {
+    match doc.as_ref() {
+        Some(doc) => write_field(
+            doc.records.get(record).and_then(|record| record.get(field)),
+            out_data,
+            out_len,
+        ),
+        None => LIBPARSE_ERR_NULL,
+    }
+}
+
+/// Releases a document
+///
+/// # Safety
+///
+/// `doc` must be null or a document returned by `libparse_csv_parse` that was not freed yet
+#[no_mangle]
+pub unsafe extern "C" fn libparse_document_free(doc: *mut LibparseDocument) {
+    if !doc.is_null() {
+        drop(Box::from_raw(doc));
+    }
+}
+
+/// Creates an iterator over the records of a CSV document, copying the input
+///
+/// The iterator must be released with `libparse_csv_iter_free`.
+///
+/// # Safety
+///
+/// `input` must point to `len` readable bytes; `out_iter` must be valid for writes
+#[no_mangle]
+pub unsafe extern "C" fn libparse_csv_iter_new(
+    input: *const u8,
+    len: usize,
+    out_iter: *mut *mut LibparseIter,
+) -> i32 {
+    if out_iter.is_null() {
+        return LIBPARSE_ERR_NULL;
+    }
+    let input = match input_str(input, len) {
+        Ok(input) => input,
+        Err(status) => return status,
+    };
+    *out_iter = Box::into_raw(Box::new(LibparseIter {
+        records: csv::records_owned(input.to_string(), &Dialect::default()),
+        error_offset: 0,
+        current: CsvRecord::new(),
+    }));
+    LIBPARSE_OK
+}
+
+/// Advances an iterator to the next record
+///
+/// Returns `LIBPARSE_OK` when a record is available through `libparse_csv_iter_field`,
+/// `LIBPARSE_DONE` at the end of the document and `LIBPARSE_ERR_PARSE` on invalid input, after
+/// which the iterator is exhausted.
+///
+/// # Safety
+///
+/// `iter` must be a live iterator returned by `libparse_csv_iter_new`
+#[no_mangle]
+pub unsafe extern "C" fn libparse_csv_iter_next(iter: *mut LibparseIter) -> i32 {
+    let iter = match iter.as_mut() {
+        Some(iter) => iter,
+        None => return LIBPARSE_ERR_NULL,
+    };
+    match iter.records.next() {
+        Some(Ok(record)) => {
+            iter.current = record;
+            LIBPARSE_OK
+        }
+        Some(Err(err)) => {
+            iter.error_offset = err.offset;
+            iter.current.clear();
+            LIBPARSE_ERR_PARSE
+        }
+        None => LIBPARSE_DONE,
+    }
+}
+
+/// Returns the number of fields of the current record of an iterator
+///
+/// # Safety
+///
+/// `iter` must be a live iterator returned by `libparse_csv_iter_new`
+#[no_mangle]
+pub unsafe extern "C" fn libparse_csv_iter_record_len(iter: *const LibparseIter) -> usize {
+    iter.as_ref().map_or(0, |iter| iter.current.len())
+}
+
+/// Gets a field of the current record of an iterator, valid until the next call to
+/// `libparse_csv_iter_next`
+///
+/// # Safety
+///
+/// `iter` must be a live iterator returned by `libparse_csv_iter_new`; `out_data` and
+/// `out_len` must be valid for writes
+#[no_mangle]
+pub unsafe extern "C" fn libparse_csv_iter_field(
+    iter: *const LibparseIter,
+    field: usize,
+    out_data: *mut *const u8,
+    out_len: *mut usize,
+) -> i32 {
+    match iter.as_ref() {
+        Some(iter) => write_field(iter.current.get(field), out_data, out_len),
+        None => LIBPARSE_ERR_NULL,
+    }
+}
+
+/// Returns the byte offset of the error that stopped an iterator
+///
+/// # Safety
+///
+/// `iter` must be a live iterator returned by `libparse_csv_iter_new`
+#[no_mangle]
+pub unsafe extern "C" fn libparse_csv_iter_error_offset(iter: *const LibparseIter) -> usize {
+    iter.as_ref().map_or(0, |iter| iter.error_offset)
+}
+
+/// Releases an iterator
+///
+/// # Safety
+///
+/// `iter` must be null or an iterator returned by `libparse_csv_iter_new` that was not freed
+/// yet
+#[no_mangle]
+pub unsafe extern "C" fn libparse_csv_iter_free(iter: *mut LibparseIter) {
+    if !iter.is_null() {
+        drop(Box::from_raw(iter));
+    }
+}
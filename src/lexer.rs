@@ -0,0 +1,123 @@
+//! Tokenization layer for two-phase (lex, then parse) grammars
+
+use crate::base::*;
+
+/// Token produced by a lexer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token<'a, K> {
+    pub kind: K,
+    pub text: &'a str,
+    pub span: Span,
+}
+
+/// Token stream input for parsers running after the lexer
+pub type Tokens<'t, 'a, K> = &'t [Token<'a, K>];
+
+type Rule<'a, K> = Box<dyn FnMut(&'a str) -> PResult<&'a str, K> + 'a>;
+
+/// Lexer splits text into tokens by repeatedly applying a set of token parsers
+///
+/// Rules are tried in the order they were added and the first one that consumes input wins.
+/// Skip rules (whitespace, comments) are tried before token rules and produce no tokens.
+pub struct Lexer<'a, K> {
+    tokens: Vec<Rule<'a, K>>,
+    skips: Vec<Rule<'a, ()>>,
+}
+
+impl<'a, K> Lexer<'a, K> {
+    /// Creates a lexer without any rules
+    pub fn new() -> Self {
+        Lexer {
+            tokens: Vec::new(),
+            skips: Vec::new(),
+        }
+    }
+
+    /// Adds a rule producing tokens of the kind output by the given parser
+    pub fn token<P>(mut self, mut parser: P) -> Self
+    where
+        P: Parser<&'a str, K> + 'a,
+    {
+        self.tokens.push(Box::new(move |input| parser.parse(input)));
+        self
+    }
+
+    /// Adds a rule for input that is consumed without producing tokens
+    pub fn skip<P, O>(mut self, mut parser: P) -> Self
+    where
+        P: Parser<&'a str, O> + 'a,
+    {
+        self.skips.push(Box::new(move |input| {
+            parser.parse(input).map(|(next_input, _)| (next_input, ()))
+        }));
+        self
+    }
+
+    /// Splits the entire input into tokens
+    pub fn tokenize(&mut self, mut input: &'a str) -> PResult<&'a str, Vec<Token<'a, K>>> {
+        let mut tokens = Vec::new();
+        while !input.is_empty() {
+            if let Some((next_input, _)) = apply(&mut self.skips, input)? {
+                input = next_input;
+            } else if let Some((next_input, kind)) = apply(&mut self.tokens, input)? {
+                let len = input.len() - next_input.len();
+                tokens.push(Token {
+                    kind,
+                    text: &input[..len],
+                    span: Span::new(input.len(), next_input.len()),
+                });
+                input = next_input;
+            } else {
+                return Err(Error::new(input, ErrorCode::Token));
+            }
+        }
+        Ok((input, tokens))
+    }
+}
+
+impl<K> Default for Lexer<'_, K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Applies the first rule that consumes input, propagating failures and incomplete input
+fn apply<'a, O>(
+    rules: &mut [Rule<'a, O>],
+    input: &'a str,
+) -> Result<Option<(&'a str, O)>, Error<&'a str>> {
+    for rule in rules.iter_mut() {
+        match rule(input) {
+            Ok((next_input, output)) if next_input.len() < input.len() => {
+                return Ok(Some((next_input, output)))
+            }
+            Err(err) if err.is_failure() || err.is_incomplete() => return Err(err),
+            _ => continue,
+        }
+    }
+    Ok(None)
+}
+
+/// Parser that matches any token in a token stream
+pub fn any_token<'t, 'a, K>(
+    input: &'t [Token<'a, K>],
+) -> PResult<&'t [Token<'a, K>], &'t Token<'a, K>> {
+    match input.split_first() {
+        Some((token, rest)) => Ok((rest, token)),
+        None => Err(Error::end_of_input(input, ErrorCode::NoInput)),
+    }
+}
+
+/// Parser generator for parsers that match a single token of the given kind
+pub fn token<'t, 'a, K>(
+    kind: K,
+) -> impl Fn(Tokens<'t, 'a, K>) -> PResult<Tokens<'t, 'a, K>, &'t Token<'a, K>>
+where
+    K: PartialEq + 't,
+    'a: 't,
+{
+    move |input| match any_token(input)? {
+        (rest, token) if token.kind == kind => Ok((rest, token)),
+        _ => Err(Error::new(input, ErrorCode::Token)),
+    }
+}
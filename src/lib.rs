@@ -1,10 +1,24 @@
 //! CSV parser
 
 pub mod base;
+pub mod bytes;
 pub mod chars;
 pub mod combinators;
 pub mod csv;
+pub mod expr;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod lexer;
+pub mod log;
+pub mod memo;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod registry;
 mod tests;
+pub mod trace;
+pub mod urlencoded;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 pub use self::base::*;
 pub use self::combinators::*;
@@ -0,0 +1,322 @@
+//! Parsers for semi-structured log lines: logfmt (`key=value` pairs) and the common log
+//! format of web servers
+
+use crate::base::*;
+use crate::chars::*;
+use crate::combinators::*;
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+use std::ops::RangeInclusive;
+
+/// Fields of a single logfmt line, by key
+pub type LogRecord = BTreeMap<String, String>;
+
+/// Parser that matches a logfmt key: any run of characters other than whitespace, `=` and `"`
+pub fn key(input: &str) -> PResult<&str, &str> {
+    take_while1(|ch| !ch.is_whitespace() && ch != '=' && ch != '"')(input)
+}
+
+/// Parser that matches a logfmt value, either bare or double-quoted with `\"` and `\\` escapes
+pub fn value(input: &str) -> PResult<&str, String> {
+    quoted_value
+        .fallback_on(take_while(|ch| !ch.is_whitespace() && ch != '"').map(String::from))
+        .parse(input)
+}
+
+fn quoted_value(input: &str) -> PResult<&str, String> {
+    right_from_pair(
+        char('"'),
+        left_from_pair(
+            fold_many(
+                take_while1(|ch| ch != '"' && ch != '\\')
+                    .fallback_on(right_from_pair(char('\\'), recognize(any_char))),
+                String::new,
+                |mut text, chunk| {
+                    text.push_str(chunk);
+                    text
+                },
+            ),
+            char('"'),
+        ),
+    )
+    .parse(input)
+}
+
+/// Parser that matches a `key=value` pair; a key without `=` has an empty value
+pub fn key_value(input: &str) -> PResult<&str, (&str, String)> {
+    pair(
+        key,
+        right_from_pair(char('='), value).with_default(String::new()),
+    )
+    .parse(input)
+}
+
+/// Parser that matches a logfmt line of space-separated pairs and consumes its line break
+///
+/// When a key is repeated, the last value wins.
+pub fn logfmt_line(input: &str) -> PResult<&str, LogRecord> {
+    let spaces = |input| take_while(|ch| ch == ' ' || ch == '\t')(input);
+    let (next_input, (_, pairs)) =
+        pair(spaces, zero_or_more(left_from_pair(key_value, spaces))).parse(input)?;
+    let record = pairs
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .collect();
+    if next_input.is_empty() {
+        return Ok((next_input, record));
+    }
+    let (next_input, _) = line_break(next_input).map_err(|_| {
+        Error::failure(
+            next_input,
+            Reason::InvalidInput {
+                expected: "space or a line break",
+            },
+        )
+    })?;
+    Ok((next_input, record))
+}
+
+/// Iterator over the records of a logfmt document, skipping blank lines
+///
+/// Iteration stops after the first error.
+pub struct LogRecords<'a> {
+    input: &'a str,
+    done: bool,
+}
+
+/// Iterates over the records of a logfmt document
+pub fn logfmt_records(input: &str) -> LogRecords<'_> {
+    LogRecords { input, done: false }
+}
+
+impl<'a> Iterator for LogRecords<'a> {
+    type Item = Result<LogRecord, Error<&'a str>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            self.input = self.input.trim_start_matches(['\r', '\n']);
+            if self.input.is_empty() {
+                self.done = true;
+                break;
+            }
+            match logfmt_line(self.input) {
+                Ok((next_input, record)) => {
+                    self.input = next_input;
+                    if !record.is_empty() {
+                        return Some(Ok(record));
+                    }
+                }
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Point in time with the UTC offset it was written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timestamp {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    pub nanosecond: u32,
+    /// Offset from UTC in minutes
+    pub offset_minutes: i16,
+}
+
+/// Parser generator for parsers that match exactly `n` ASCII digits within the given range
+fn digits(n: usize, range: RangeInclusive<u32>) -> impl Fn(&str) -> PResult<&str, u32> {
+    move |input: &str| match input.get(..n) {
+        Some(text) if text.bytes().all(|byte| byte.is_ascii_digit()) => match text.parse() {
+            Ok(number) if range.contains(&number) => Ok((&input[n..], number)),
+            _ => Err(Error::new(input, ErrorCode::Predicate)),
+        },
+        _ => Err(Error::new(input, ErrorCode::Predicate)),
+    }
+}
+
+/// Parser that matches a UTC offset: `Z`, or a sign followed by hours and minutes, with an
+/// optional colon between them
+fn utc_offset(input: &str) -> PResult<&str, i16> {
+    if let Some(rest) = input.strip_prefix('Z') {
+        return Ok((rest, 0));
+    }
+    let (next_input, sign) = char('+').fallback_on(char('-')).parse(input)?;
+    let (next_input, (hours, minutes)) = pair(
+        digits(2, 0..=23),
+        right_from_pair(char(':').with_default(':'), digits(2, 0..=59)),
+    )
+    .parse(next_input)?;
+    let offset = (hours * 60 + minutes) as i16;
+    Ok((next_input, if sign == '-' { -offset } else { offset }))
+}
+
+/// Parser that matches an RFC 3339 timestamp, like `2024-03-01T12:30:00.25+01:00`
+pub fn rfc3339(input: &str) -> PResult<&str, Timestamp> {
+    let date = pair(
+        left_from_pair(digits(4, 0..=9999), char('-')),
+        separated_pair(digits(2, 1..=12), char('-'), digits(2, 1..=31)),
+    );
+    let time = pair(
+        left_from_pair(digits(2, 0..=23), char(':')),
+        separated_pair(digits(2, 0..=59), char(':'), digits(2, 0..=60)),
+    );
+    let fraction = right_from_pair(char('.'), digit1)
+        .map(|digits: &str| {
+            let digits = &digits[..digits.len().min(9)];
+            digits.parse::<u32>().unwrap_or(0) * 10u32.pow(9 - digits.len() as u32)
+        })
+        .with_default(0);
+    let (next_input, (((year, (month, day)), (hour, (minute, second))), (nanosecond, offset))) =
+        pair(
+            separated_pair(
+                date,
+                satisfy(|ch| ch == 'T' || ch == 't' || ch == ' '),
+                time,
+            ),
+            pair(fraction, utc_offset),
+        )
+        .parse(input)?;
+    Ok((
+        next_input,
+        Timestamp {
+            year: year as u16,
+            month: month as u8,
+            day: day as u8,
+            hour: hour as u8,
+            minute: minute as u8,
+            second: second as u8,
+            nanosecond,
+            offset_minutes: offset,
+        },
+    ))
+}
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+fn month_name(input: &str) -> PResult<&str, u32> {
+    match MONTHS.iter().position(|month| input.starts_with(month)) {
+        Some(index) => Ok((&input[3..], index as u32 + 1)),
+        None => Err(Error::new(input, ErrorCode::Predicate)),
+    }
+}
+
+/// Parser that matches a timestamp of the common log format, like `10/Oct/2000:13:55:36 -0700`
+pub fn clf_timestamp(input: &str) -> PResult<&str, Timestamp> {
+    let date = pair(
+        left_from_pair(digits(2, 1..=31), char('/')),
+        separated_pair(month_name, char('/'), digits(4, 0..=9999)),
+    );
+    let time = pair(
+        left_from_pair(digits(2, 0..=23), char(':')),
+        separated_pair(digits(2, 0..=59), char(':'), digits(2, 0..=60)),
+    );
+    let (next_input, (((day, (month, year)), (hour, (minute, second))), offset)) = pair(
+        separated_pair(date, char(':'), time),
+        right_from_pair(char(' '), utc_offset),
+    )
+    .parse(input)?;
+    Ok((
+        next_input,
+        Timestamp {
+            year: year as u16,
+            month: month as u8,
+            day: day as u8,
+            hour: hour as u8,
+            minute: minute as u8,
+            second: second as u8,
+            nanosecond: 0,
+            offset_minutes: offset,
+        },
+    ))
+}
+
+/// Parser that matches an IPv4 or IPv6 address
+pub fn ip_addr(input: &str) -> PResult<&str, IpAddr> {
+    ipv4.map(IpAddr::V4)
+        .fallback_on(ipv6.map(IpAddr::V6))
+        .parse(input)
+}
+
+/// Single entry of the common log format of web servers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommonLogEntry<'a> {
+    pub host: &'a str,
+    pub ident: Option<&'a str>,
+    pub user: Option<&'a str>,
+    pub timestamp: Timestamp,
+    pub request: &'a str,
+    pub status: u16,
+    pub bytes: Option<u64>,
+}
+
+/// Parser that matches a field of the common log format, where `-` means that the value is
+/// missing
+fn clf_field(input: &str) -> PResult<&str, Option<&str>> {
+    take_while1(|ch| !ch.is_whitespace())
+        .map(|field| Some(field).filter(|field| *field != "-"))
+        .parse(input)
+}
+
+/// Parser that matches an entry of the common log format and consumes its line break, like
+/// `127.0.0.1 - frank [10/Oct/2000:13:55:36 -0700] "GET / HTTP/1.0" 200 2326`
+pub fn common_log_line(input: &str) -> PResult<&str, CommonLogEntry<'_>> {
+    let space = || char(' ');
+    let (next_input, (host, (ident, user))) = pair(
+        left_from_pair(take_while1(|ch| !ch.is_whitespace()), space()),
+        pair(
+            left_from_pair(clf_field, space()),
+            left_from_pair(clf_field, space()),
+        ),
+    )
+    .parse(input)?;
+    let (next_input, (timestamp, request)) = pair(
+        left_from_pair(right_from_pair(char('['), clf_timestamp), char(']')),
+        right_from_pair(
+            space(),
+            left_from_pair(
+                right_from_pair(char('"'), take_while(|ch| ch != '"')),
+                char('"'),
+            ),
+        ),
+    )
+    .parse(next_input)?;
+    let (next_input, (status, bytes)) = pair(
+        right_from_pair(space(), digits(3, 0..=599)),
+        right_from_pair(space(), clf_field),
+    )
+    .parse(next_input)?;
+    let bytes = match bytes.map(str::parse) {
+        Some(Ok(bytes)) => Some(bytes),
+        Some(Err(_)) => {
+            return Err(Error::failure(
+                next_input,
+                Reason::InvalidInput {
+                    expected: "number of bytes",
+                },
+            ))
+        }
+        None => None,
+    };
+    let next_input = line_break(next_input).map_or(next_input, |(rest, _)| rest);
+    Ok((
+        next_input,
+        CommonLogEntry {
+            host,
+            ident,
+            user,
+            timestamp,
+            request,
+            status: status as u16,
+            bytes,
+        },
+    ))
+}
@@ -0,0 +1,106 @@
+//! Packrat memoization of parser results
+
+use crate::base::*;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::rc::Rc;
+
+type Outcome<I, O> = Result<(I, O), (I, ErrorCode)>;
+
+/// Results of a memoized parser by the length of the remaining input
+struct Cache<I, O> {
+    capacity: usize,
+    entries: HashMap<usize, (I, Outcome<I, O>)>,
+    /// Positions in order of insertion, for evicting the oldest entry
+    order: VecDeque<usize>,
+}
+
+/// Memoized is a parser that caches the results of another parser by input position
+///
+/// Clones share the cache, so one memoized parser can be used in several alternatives of a
+/// grammar. Results are only reused for the same position of the same input. Exceeding the
+/// nesting depth and left recursion are never remembered, since they depend on where the
+/// parser is invoked from rather than on the input.
+#[must_use = "parsers do nothing unless used"]
+pub struct Memoized<P, I, O> {
+    parser: P,
+    cache: Rc<RefCell<Cache<I, O>>>,
+}
+
+impl<P: Clone, I, O> Clone for Memoized<P, I, O> {
+    fn clone(&self) -> Self {
+        Memoized {
+            parser: self.parser.clone(),
+            cache: Rc::clone(&self.cache),
+        }
+    }
+}
+
+/// Parser generator for parsers that remember the results of the given parser for at most
+/// `capacity` input positions, evicting the oldest ones first
+///
+/// Memoization pays off in grammars that backtrack over the same input many times; the
+/// grammar must be built once and reused for the cache to be effective.
+pub fn memoize<P, I, O>(capacity: usize, parser: P) -> Memoized<P, I, O>
+where
+    P: Parser<I, O>,
+    I: Input + Clone,
+    O: Clone,
+{
+    Memoized {
+        parser,
+        cache: Rc::new(RefCell::new(Cache {
+            capacity,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+        })),
+    }
+}
+
+impl<P, I, O> Memoized<P, I, O> {
+    /// Forgets all remembered results
+    pub fn clear(&self) {
+        let mut cache = self.cache.borrow_mut();
+        cache.entries.clear();
+        cache.order.clear();
+    }
+}
+
+impl<P, I, O> Parser<I, O> for Memoized<P, I, O>
+where
+    P: Parser<I, O>,
+    I: Input + Clone,
+    O: Clone,
+{
+    fn parse(&mut self, input: I) -> PResult<I, O> {
+        let position = input.input_len();
+        if let Some((cached_input, outcome)) = self.cache.borrow().entries.get(&position) {
+            if cached_input.same_position(&input) {
+                return outcome
+                    .clone()
+                    .map_err(|(input, code)| Error::new(input, code));
+            }
+        }
+
+        let result = self.parser.parse(input.clone());
+        if is_streaming() || result.as_ref().is_err_and(|err| is_runaway(&err.code)) {
+            return result;
+        }
+        let outcome = match &result {
+            Ok((next_input, output)) => Ok((next_input.clone(), output.clone())),
+            Err(err) => Err((err.input.clone(), err.code.clone())),
+        };
+        let mut cache = self.cache.borrow_mut();
+        if cache.capacity == 0 {
+            return result;
+        }
+        if cache.entries.insert(position, (input, outcome)).is_none() {
+            cache.order.push_back(position);
+            if cache.order.len() > cache.capacity {
+                let oldest = cache.order.pop_front().expect("cache is not empty");
+                cache.entries.remove(&oldest);
+            }
+        }
+        result
+    }
+}
@@ -0,0 +1,119 @@
+//! Python bindings for CSV parsing
+//!
+//! The module is named `libparse` and is meant to be built as an extension module, e.g. with
+//! maturin. Parse errors are raised as `libparse.ParseError`, a subclass of `ValueError`.
+
+use crate::csv::{self, CsvRecord, OwnedRecords, StrayQuotes};
+use crate::OwnedError;
+use pyo3::create_exception;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+create_exception!(libparse, ParseError, PyValueError);
+
+fn parse_error(err: OwnedError) -> PyErr {
+    ParseError::new_err(err.to_string())
+}
+
+/// CSV dialect, see `csv::Dialect`
+#[pyclass(name = "Dialect", module = "libparse", from_py_object)]
+#[derive(Clone, Default)]
+pub struct PyDialect {
+    inner: csv::Dialect,
+}
+
+#[pymethods]
+impl PyDialect {
+    #[new]
+    #[pyo3(signature = (delimiter = ',', stray_quotes = "terminate", allow_empty = false))]
+    fn new(delimiter: char, stray_quotes: &str, allow_empty: bool) -> PyResult<Self> {
+        if matches!(delimiter, '"' | '\r' | '\n') {
+            return Err(PyValueError::new_err("invalid delimiter"));
+        }
+        let stray_quotes = match stray_quotes {
+            "terminate" => StrayQuotes::Terminate,
+            "literal" => StrayQuotes::Literal,
+            "error" => StrayQuotes::Error,
+            _ => {
+                return Err(PyValueError::new_err(
+                    "stray_quotes must be one of 'terminate', 'literal' or 'error'",
+                ))
+            }
+        };
+        Ok(PyDialect {
+            inner: csv::Dialect {
+                delimiter,
+                stray_quotes,
+                allow_empty,
+                ..Default::default()
+            },
+        })
+    }
+
+    #[getter]
+    fn delimiter(&self) -> char {
+        self.inner.delimiter
+    }
+
+    #[getter]
+    fn stray_quotes(&self) -> &'static str {
+        match self.inner.stray_quotes {
+            StrayQuotes::Terminate => "terminate",
+            StrayQuotes::Literal => "literal",
+            StrayQuotes::Error => "error",
+        }
+    }
+
+    #[getter]
+    fn allow_empty(&self) -> bool {
+        self.inner.allow_empty
+    }
+}
+
+/// Iterator over the records of a CSV document
+#[pyclass(name = "Records", module = "libparse")]
+pub struct PyRecords {
+    records: OwnedRecords,
+}
+
+#[pymethods]
+impl PyRecords {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self) -> PyResult<Option<CsvRecord>> {
+        self.records.next().transpose().map_err(parse_error)
+    }
+}
+
+/// Parses a whole CSV document into a list of records
+#[pyfunction]
+#[pyo3(signature = (input, dialect = None))]
+fn parse_string(input: &str, dialect: Option<PyDialect>) -> PyResult<Vec<CsvRecord>> {
+    let dialect = dialect.unwrap_or_default().inner;
+    csv::parse_with(input, &dialect)
+        .map(|(_, records)| records)
+        .map_err(|err| parse_error(err.to_owned_error(input.len())))
+}
+
+/// Iterates over the records of a CSV document, one at a time
+#[pyfunction]
+#[pyo3(signature = (input, dialect = None))]
+fn records(input: String, dialect: Option<PyDialect>) -> PyRecords {
+    let dialect = dialect.unwrap_or_default().inner;
+    PyRecords {
+        records: csv::records_owned(input, &dialect),
+    }
+}
+
+/// Python module definition
+#[pymodule]
+pub fn libparse(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("ParseError", m.py().get_type::<ParseError>())?;
+    m.add_class::<PyDialect>()?;
+    m.add_class::<PyRecords>()?;
+    m.add_function(wrap_pyfunction!(parse_string, m)?)?;
+    m.add_function(wrap_pyfunction!(records, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,66 @@
+//! Runtime selection of grammars by name
+
+use crate::base::*;
+use crate::csv::{self, CsvRecord, Dialect};
+use std::collections::HashMap;
+
+/// Boxed parser over string input that can be stored in a registry
+pub type BoxedParser<O> = Box<dyn for<'a> FnMut(&'a str) -> PResult<&'a str, O> + Send>;
+
+/// Registry of named grammars that can be selected at runtime, e.g. from a configuration file
+pub struct GrammarRegistry<O> {
+    grammars: HashMap<String, BoxedParser<O>>,
+}
+
+impl<O> GrammarRegistry<O> {
+    /// Creates an empty registry
+    pub fn new() -> Self {
+        GrammarRegistry {
+            grammars: HashMap::new(),
+        }
+    }
+
+    /// Registers a grammar under the given name, returning the grammar it replaced
+    ///
+    /// Composed parsers can be registered by building them inside a closure
+    pub fn register<F>(&mut self, name: &str, parser: F) -> Option<BoxedParser<O>>
+    where
+        F: for<'a> FnMut(&'a str) -> PResult<&'a str, O> + Send + 'static,
+    {
+        self.grammars.insert(name.to_string(), Box::new(parser))
+    }
+
+    /// Looks up a grammar by name
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut BoxedParser<O>> {
+        self.grammars.get_mut(name)
+    }
+
+    /// Parses the input with the grammar of the given name, if there is one
+    pub fn parse<'a>(&mut self, name: &str, input: &'a str) -> Option<PResult<&'a str, O>> {
+        self.get_mut(name).map(|grammar| grammar(input))
+    }
+
+    /// Returns the names of all registered grammars
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.grammars.keys().map(String::as_str)
+    }
+}
+
+impl<O> Default for GrammarRegistry<O> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GrammarRegistry<Vec<CsvRecord>> {
+    /// Creates a registry with the built-in delimiter-separated formats:
+    /// "csv" (comma), "tsv" (tab) and "psv" (pipe)
+    pub fn with_csv_formats() -> Self {
+        let mut registry = Self::new();
+        for (name, delimiter) in [("csv", ','), ("tsv", '\t'), ("psv", '|')] {
+            let dialect = Dialect::with_delimiter(delimiter);
+            registry.register(name, move |input| csv::parse_with(input, &dialect));
+        }
+        registry
+    }
+}
@@ -1,6 +1,7 @@
 //! Parser functional tests
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests {
     use crate::chars::*;
     use crate::csv;
@@ -91,7 +92,7 @@ fn pari_combinator_negative() {
         let mut combi = pair(char('a'), char('b'));
         assert_eq!(
             combi.parse("acb"),
-            Err(Error::new("acb", ErrorCode::Char('b'))),
+            Err(Error::new("cb", ErrorCode::Char('b'))),
         )
     }
 
@@ -109,6 +110,20 @@ fn right_combinator() {
         assert_eq!(right, 'b');
     }
 
+    #[test]
+    fn separated_pair_combinator() {
+        let number = || take_while1(|ch| ch.is_ascii_digit());
+        let mut range = separated_pair(number(), tag(".."), number());
+        assert_eq!(range.parse("1..10;"), Ok((";", ("1", "10"))));
+        assert_eq!(
+            range.parse("1.10"),
+            Err(Error::new(".10", ErrorCode::Tag("..")))
+        );
+
+        let mut key_value = separated_pair(take_while1(|ch| ch != '='), char('='), rest);
+        assert_eq!(key_value.parse("key=a=b"), Ok(("", ("key", "a=b"))));
+    }
+
     #[test]
     fn zero_or_more_combinator_consume_part() {
         let input = "aaabc";
@@ -288,7 +303,7 @@ fn csv_document_parser() {
         assert_eq!(
             csv::parse_string(input),
             Err(Error::failure(
-                "\n\"\"\"The Fall of Hyperion\"\"\"",
+                "\"\"\"The Fall of Hyperion\"\"\"",
                 Reason::InvalidInput {
                     expected: "more fields in this record"
                 },
@@ -306,4 +321,3382 @@ fn csv_document_parser_from_file() {
             assert_eq!(rec.len(), 11);
         }
     }
+
+    #[test]
+    fn spanned_parser() {
+        let input = "ab,cde";
+        let mut combi = zero_or_more(any_char.iff(|ch| *ch != ',').spanned());
+        let (next_input, outputs) = combi.parse(input).unwrap();
+        assert_eq!(next_input, ",cde");
+        assert_eq!(outputs.len(), 2);
+        assert_eq!(outputs[1].1.range(input.len()), 1..2);
+
+        let mut combi = right_from_pair(char(','), csv::field.spanned());
+        let (_, (field, span)) = combi.parse(next_input).unwrap();
+        assert_eq!(field, "cde");
+        assert_eq!(span.len(), 3);
+        assert_eq!(&input[span.range(input.len())], "cde");
+
+        let (_, (_, span)) = csv::field.spanned().parse(",").unwrap();
+        assert!(span.is_empty());
+    }
+
+    #[test]
+    fn take_while_parser() {
+        let mut digits = take_while(|ch| ch.is_ascii_digit());
+        assert_eq!(digits.parse("123abc"), Ok(("abc", "123")));
+        assert_eq!(digits.parse("abc"), Ok(("abc", "")));
+        assert_eq!(digits.parse(""), Ok(("", "")));
+
+        let mut digits = take_while1(|ch| ch.is_ascii_digit());
+        assert_eq!(digits.parse("42"), Ok(("", "42")));
+        assert_eq!(
+            digits.parse("abc"),
+            Err(Error::new("abc", ErrorCode::Predicate))
+        );
+    }
+
+    #[test]
+    fn fold_many_combinator() {
+        let mut combi = fold_many(
+            any_char.iff(|ch| ch.is_ascii_digit()),
+            || 0,
+            |sum, ch| sum + ch.to_digit(10).unwrap(),
+        );
+        assert_eq!(combi.parse("1234x"), Ok(("x", 10)));
+        assert_eq!(combi.parse("x"), Ok(("x", 0)));
+    }
+
+    #[test]
+    fn recognize_combinator() {
+        let mut combi = recognize(pair(char('a'), zero_or_more(char('b'))));
+        assert_eq!(combi.parse("abbbc"), Ok(("c", "abbb")));
+        assert_eq!(
+            combi.parse("bc"),
+            Err(Error::new("bc", ErrorCode::Char('a')))
+        );
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn csv_parse_mapped_file() {
+        let file = csv::MappedFile::open("src/test_data/books.csv").unwrap();
+        let (next_input, records) = file.parse().unwrap().unwrap();
+        assert_eq!(next_input, "");
+        assert_eq!(records.len(), 30);
+        let text = file.as_str().unwrap();
+        let (_, borrowed) = file
+            .records_as_written(&csv::Dialect::default())
+            .unwrap()
+            .unwrap();
+        assert_eq!(borrowed.len(), 30);
+        for (borrowed, owned) in borrowed.iter().zip(&records) {
+            for (field, text_field) in borrowed.iter().zip(owned) {
+                assert_eq!(field.as_str(), text_field);
+            }
+        }
+        match &borrowed[0][0] {
+            csv::Field::Raw(field) => {
+                assert!(text.as_bytes().as_ptr_range().contains(&field.as_ptr()))
+            }
+            other => panic!("unexpected field: {:?}", other),
+        }
+
+        let records = csv::parse_file("src/test_data/books.csv").unwrap();
+        assert_eq!(records.len(), 30);
+        assert_eq!(records[0][1], "Naslov");
+
+        assert!(matches!(
+            csv::parse_file("src/test_data/missing.csv"),
+            Err(csv::FileError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn csv_reader() {
+        let input = std::fs::read_to_string("src/test_data/books.csv").unwrap();
+        let (_, expected) = csv::parse_string(input.as_str()).unwrap();
+        let reader = csv::Reader::new(input.as_bytes());
+        let records = reader.collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(records, expected);
+
+        let mut reader = csv::Reader::new("a,\"multi\r\nline\"\r\nb,c".as_bytes());
+        assert_eq!(
+            reader.read_record().unwrap(),
+            Some(vec!["a".to_string(), "multi\r\nline".to_string()])
+        );
+        assert_eq!(
+            reader.read_record().unwrap(),
+            Some(vec!["b".to_string(), "c".to_string()])
+        );
+        assert_eq!(reader.bytes_consumed(), 20);
+        assert!(reader.read_record().unwrap().is_none());
+
+        let mut reader = csv::Reader::new("a,b\nc\n".as_bytes());
+        assert!(reader.next().unwrap().is_ok());
+        assert!(matches!(
+            reader.next(),
+            Some(Err(csv::FileError::Parse {
+                offset: 4,
+                code: ErrorCode::Failure(Reason::InvalidInput {
+                    expected: "more fields in this record"
+                }),
+            }))
+        ));
+
+        let mut reader = csv::Reader::new("a,b\"c\n".as_bytes());
+        assert!(matches!(
+            reader.next(),
+            Some(Err(csv::FileError::Parse { offset: 3, .. }))
+        ));
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn csv_reader_gzip() {
+        use std::io::Write;
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), Default::default());
+        encoder.write_all(b"a,b\r\n1,2\r\n").unwrap();
+        let compressed = encoder.finish().unwrap();
+        let records = csv::Reader::decompress(std::io::Cursor::new(compressed))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(records, vec![vec!["a", "b"], vec!["1", "2"]]);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn csv_reader_zstd() {
+        let compressed = zstd::encode_all(&b"a,b\r\n1,2\r\n"[..], 0).unwrap();
+        let records = csv::Reader::decompress(std::io::Cursor::new(compressed))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(records, vec![vec!["a", "b"], vec!["1", "2"]]);
+    }
+
+    #[test]
+    fn csv_to_json() {
+        let input =
+            "id,name,score,active\r\n00042,\"Doe, \"\"J\"\"\",-1.5e3,true\r\n7,,0.25,no\r\n";
+        assert_eq!(
+            csv::to_json(input, &Default::default()).unwrap(),
+            concat!(
+                r#"[{"id":"00042","name":"Doe, \"J\"","score":"-1.5e3","active":"true"},"#,
+                r#"{"id":"7","name":"","score":"0.25","active":"no"}]"#
+            )
+        );
+
+        let options = csv::JsonOptions {
+            coerce_types: true,
+            empty_as_null: true,
+        };
+        assert_eq!(
+            csv::to_json(input, &options).unwrap(),
+            concat!(
+                r#"[{"id":"00042","name":"Doe, \"J\"","score":-1.5e3,"active":true},"#,
+                r#"{"id":7,"name":null,"score":0.25,"active":"no"}]"#
+            )
+        );
+
+        assert_eq!(csv::to_json("a,b\n", &options).unwrap(), "[]");
+        assert!(csv::to_json("a,b\nc\n", &options).is_err());
+    }
+
+    #[test]
+    fn csv_writer() {
+        let mut writer = csv::Writer::new(Vec::new());
+        writer
+            .write_record(["plain", "with,comma", "\"quoted\""])
+            .unwrap();
+        writer.write_record(["", "", "multi\nline"]).unwrap();
+        let output = String::from_utf8(writer.into_inner()).unwrap();
+        assert_eq!(
+            output,
+            "plain,\"with,comma\",\"\"\"quoted\"\"\"\r\n,,\"multi\nline\"\r\n"
+        );
+        let (_, records) = csv::parse_string(&output).unwrap();
+        assert_eq!(records[0], vec!["plain", "with,comma", "\"quoted\""]);
+        assert_eq!(records[1], vec!["", "", "multi\nline"]);
+    }
+
+    #[test]
+    fn csv_concat() {
+        let first = "id,name\r\n1,one\r\n2,two\r\n";
+        let second = "name,id,extra\n\"three, 3\",3,x\n";
+        let third = "id\n4";
+        assert_eq!(
+            csv::concat(&[first, second, third]).unwrap(),
+            "id,name,extra\r\n1,one,\r\n2,two,\r\n3,\"three, 3\",x\r\n4,,\r\n"
+        );
+        assert_eq!(csv::concat(&[]).unwrap(), "");
+        assert_eq!(csv::concat(&["", third]).unwrap(), "id\r\n4\r\n");
+        assert!(matches!(
+            csv::concat(&[first, "a,b\nc\n"]),
+            Err(csv::FileError::Parse { offset: 4, .. })
+        ));
+        assert_eq!(
+            csv::concat(&["id,tag,tag\n1,a,b\n", "tag,id\nc,2\n"]).unwrap(),
+            "id,tag,tag\r\n1,a,b\r\n2,c,\r\n"
+        );
+    }
+
+    #[test]
+    fn csv_raw_record_parser() {
+        assert_eq!(
+            csv::raw_record("a,\"b,\"\"c\"\"\",d\r\nnext"),
+            Ok(("\r\nnext", "a,\"b,\"\"c\"\"\",d"))
+        );
+        assert_eq!(csv::raw_record("\n"), Ok(("\n", "")));
+        assert_eq!(csv::raw_record(""), Err(Error::new("", ErrorCode::NoInput)));
+    }
+
+    #[test]
+    fn csv_parse_where() {
+        let input = std::fs::read_to_string("src/test_data/books.csv").unwrap();
+        let (next_input, records) =
+            csv::parse_where(input.as_str(), |raw| raw.contains("Kastaneda")).unwrap();
+        assert_eq!(next_input, "");
+        assert!(!records.is_empty());
+        let (_, all) = csv::parse_string(input.as_str()).unwrap();
+        let expected = all
+            .into_iter()
+            .filter(|rec| rec[3] == "Karlos Kastaneda")
+            .collect::<Vec<_>>();
+        assert_eq!(records, expected);
+
+        assert_eq!(
+            csv::parse_where("a,b\n1,2\n3,4", |raw| raw.starts_with('3')),
+            Ok(("", vec![vec!["3".to_string(), "4".to_string()]]))
+        );
+        assert_eq!(
+            csv::parse_where("a,b\n1\n", |_| false),
+            Err(Error::failure(
+                "1\n",
+                Reason::InvalidInput {
+                    expected: "more fields in this record"
+                }
+            ))
+        );
+        assert_eq!(
+            csv::parse_where("a,b\"c\n", |_| true),
+            Err(Error::failure(
+                "\"c\n",
+                Reason::InvalidInput {
+                    expected: "comma or a line break"
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn with_default_combinator() {
+        let mut combi = pair(char('a'), char('-').with_default('+'));
+        assert_eq!(combi.parse("a-b"), Ok(("b", ('a', '-'))));
+        assert_eq!(combi.parse("ab"), Ok(("b", ('a', '+'))));
+        assert_eq!(combi.parse("a"), Ok(("", ('a', '+'))));
+
+        let mut combi = any_char.iff_or_invalid(|ch| *ch == 'a').with_default('z');
+        assert!(combi.parse("b").unwrap_err().is_failure());
+    }
+
+    #[test]
+    fn success_and_fail_parsers() {
+        assert_eq!(success(42).parse("abc"), Ok(("abc", 42)));
+        assert_eq!(
+            fail::<_, ()>(ErrorCode::Predicate).parse("abc"),
+            Err(Error::new("abc", ErrorCode::Predicate))
+        );
+
+        let mut combi = any_char.and_then_map(|sign| {
+            move |input| match sign {
+                '+' => any_char.parse(input),
+                '-' => success('0').parse(input),
+                _ => fail(ErrorCode::Failure(Reason::InvalidInput {
+                    expected: "sign",
+                }))
+                .parse(input),
+            }
+        });
+        assert_eq!(combi.parse("+5"), Ok(("", '5')));
+        assert_eq!(combi.parse("-5"), Ok(("5", '0')));
+        assert!(combi.parse("x5").unwrap_err().is_failure());
+    }
+
+    #[test]
+    fn cond_combinator() {
+        let mut header = cond(true, left_from_pair(csv::record, line_break));
+        let (next_input, header) = header.parse("a,b\n1,2").unwrap();
+        assert_eq!(next_input, "1,2");
+        assert_eq!(header, Some(vec!["a".to_string(), "b".to_string()]));
+
+        let mut header = cond(false, left_from_pair(csv::record, line_break));
+        assert_eq!(header.parse("1,2"), Ok(("1,2", None)));
+
+        let mut combi = cond(true, char('a'));
+        assert_eq!(combi.parse("b"), Err(Error::new("b", ErrorCode::Char('a'))));
+    }
+
+    #[test]
+    fn lexer_and_token_parsers() {
+        use crate::lexer::{self, Lexer};
+
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        enum Kind {
+            Let,
+            Ident,
+            Number,
+            Assign,
+            Semicolon,
+        }
+
+        let mut lexer = Lexer::new()
+            .skip(take_while1(char::is_whitespace))
+            .token(take_while1(char::is_alphabetic).map(|word| match word {
+                "let" => Kind::Let,
+                _ => Kind::Ident,
+            }))
+            .token(take_while1(|ch| ch.is_ascii_digit()).map(|_| Kind::Number))
+            .token(char('=').map(|_| Kind::Assign))
+            .token(char(';').map(|_| Kind::Semicolon));
+
+        let source = "let x = 42;\nlet y = x;";
+        let (_, tokens) = lexer.tokenize(source).unwrap();
+        assert_eq!(tokens.len(), 10);
+        assert_eq!(tokens[3].kind, Kind::Number);
+        assert_eq!(tokens[3].text, "42");
+        assert_eq!(tokens[3].span.range(source.len()), 8..10);
+
+        let mut statement = right_from_pair(
+            lexer::token(Kind::Let),
+            pair(
+                lexer::token(Kind::Ident),
+                right_from_pair(
+                    lexer::token(Kind::Assign),
+                    left_from_pair(
+                        lexer::any_token.iff(|t| matches!(t.kind, Kind::Ident | Kind::Number)),
+                        lexer::token(Kind::Semicolon),
+                    ),
+                ),
+            ),
+        )
+        .map(|(name, value)| (name.text, value.text));
+        let (rest, statements) = zero_or_more(|input| statement.parse(input))
+            .parse(&tokens)
+            .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(statements, vec![("x", "42"), ("y", "x")]);
+
+        assert_eq!(
+            lexer.tokenize("let x = ?"),
+            Err(Error::new("?", ErrorCode::Token))
+        );
+
+        // Streamed text and token streams that end within a token or a statement
+        use crate::Completeness::{Complete, Partial};
+        let mut tokenize = (|input| lexer.tokenize(input)).complete_or_partial();
+        assert_eq!(tokenize.parse("let x = 4"), Ok(("let x = 4", Partial)));
+        assert!(matches!(
+            tokenize.parse("let x = 4;"),
+            Ok(("", Complete(_)))
+        ));
+        let mut statement = statement.complete_or_partial();
+        assert_eq!(statement.parse(&tokens[..4]), Ok((&tokens[..4], Partial)));
+        assert_eq!(
+            statement.parse(&tokens[..5]),
+            Ok((&tokens[5..5], Complete(("x", "42"))))
+        );
+    }
+
+    #[test]
+    fn by_ref_parser() {
+        let mut digit = any_char
+            .iff(|ch| ch.is_ascii_digit())
+            .map(|ch| ch.to_digit(10).unwrap());
+        let (input, first) = pair(digit.by_ref(), char('-')).parse("1-23").unwrap();
+        let (input, rest) = zero_or_more(digit.by_ref()).parse(input).unwrap();
+        assert_eq!(input, "");
+        assert_eq!(first, (1, '-'));
+        assert_eq!(rest, vec![2, 3]);
+        assert_eq!(digit.parse("4"), Ok(("", 4)));
+
+        let mut comma = |input| char(',')(input);
+        let (input, _) = zero_or_more(&mut comma).parse(",,x").unwrap();
+        assert_eq!(
+            comma.parse(input),
+            Err(Error::new("x", ErrorCode::Char(',')))
+        );
+    }
+
+    #[test]
+    fn tag_parser() {
+        assert_eq!(tag("..").parse("..10"), Ok(("10", "..")));
+        assert_eq!(
+            tag("..").parse(".1"),
+            Err(Error::new(".1", ErrorCode::Tag("..")))
+        );
+    }
+
+    #[test]
+    fn lexeme_and_symbol() {
+        fn space(input: &str) -> PResult<&str, ()> {
+            fold_many(
+                take_while1(char::is_whitespace)
+                    .fallback_on(recognize(pair(char('#'), take_while(|ch| ch != '\n')))),
+                || (),
+                |_, _| (),
+            )
+            .parse(input)
+        }
+
+        let ident = take_while1(char::is_alphanumeric);
+        let mut assignment = pair(
+            lexeme(space, &ident),
+            right_from_pair(symbol(space, "="), lexeme(space, &ident)),
+        );
+        let (input, pairs) = right_from_pair(space, zero_or_more(assignment.by_ref()))
+            .parse("  # settings\nkey = value # comment\nother=1\n")
+            .unwrap();
+        assert_eq!(input, "");
+        assert_eq!(pairs, vec![("key", "value"), ("other", "1")]);
+
+        let (input, word) = lexeme(whitespace, &ident).parse("word \t\nnext").unwrap();
+        assert_eq!(word, "word");
+        assert_eq!(input, "next");
+    }
+
+    #[test]
+    fn dbg_parser() {
+        let mut combi = pair(char('a').dbg("a"), char('b').dbg("b")).dbg("pair");
+        assert_eq!(combi.parse("abc"), Ok(("c", ('a', 'b'))));
+        assert_eq!(
+            combi.parse("ac"),
+            Err(Error::new("c", ErrorCode::Char('b')))
+        );
+    }
+
+    #[test]
+    fn trace_capture() {
+        let mut combi = pair(
+            char('a').named("a"),
+            char('b').fallback_on(char('c').named("c")).named("b|c"),
+        )
+        .named("pair");
+        assert_eq!(combi.parse("ac"), Ok(("", ('a', 'c'))));
+
+        let input = "ac,ax";
+        let (result, trace) =
+            trace::capture(|| zero_or_more(left_from_pair(combi.by_ref(), char(','))).parse(input));
+        assert_eq!(result, Ok(("ax", vec![('a', 'c')])));
+        assert_eq!(trace.roots.len(), 2);
+        assert!(trace.roots[0].ok);
+        assert_eq!(trace.roots[0].span.range(input.len()), 0..2);
+        let names = |node: &trace::TraceNode| {
+            node.children
+                .iter()
+                .map(|child| child.name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&trace.roots[0]), vec!["a", "b|c"]);
+        assert_eq!(names(&trace.roots[0].children[1]), vec!["c"]);
+        assert!(!trace.roots[1].ok);
+        assert_eq!(trace.roots[1].span.range(input.len()), 3..4);
+        assert!(!trace.roots[1].children[1].ok);
+        assert_eq!(trace.roots[1].children[1].span.range(input.len()), 4..4);
+
+        let json = trace.to_json(input.len());
+        assert!(json.starts_with("[{\"name\":\"pair\",\"start\":0,\"end\":2,\"ok\":true,"));
+        assert!(json.contains("{\"name\":\"c\",\"start\":1,\"end\":2,\"ok\":true,"));
+        let folded = trace.to_folded();
+        assert!(folded.lines().any(|line| line.starts_with("pair;b|c;c ")));
+    }
+
+    #[test]
+    fn max_depth_combinator() {
+        // list = '(' list* ')'
+        fn list(input: &str) -> PResult<&str, usize> {
+            max_depth(
+                32,
+                right_from_pair(char('('), left_from_pair(zero_or_more(list), char(')')))
+                    .map(|items| 1 + items.into_iter().max().unwrap_or(0)),
+            )
+            .parse(input)
+        }
+
+        assert_eq!(list("(()(()))"), Ok(("", 3)));
+        // The innermost list still probes for nested items one level deeper
+        let nested = format!("{}{}", "(".repeat(31), ")".repeat(31));
+        assert_eq!(list(&nested), Ok(("", 31)));
+
+        let deep = format!("{}{}", "(".repeat(100_000), ")".repeat(100_000));
+        let err = list(&deep).unwrap_err();
+        assert_eq!(err.code, ErrorCode::DepthExceeded);
+        assert!(err.is_failure());
+        assert_eq!(list("()"), Ok(("", 1)));
+    }
+
+    #[test]
+    fn csv_parse_with_dialect() {
+        let dialect = csv::Dialect::with_delimiter('\t');
+        let (next_input, records) =
+            csv::parse_with("a\tb,c\t\"d\te\"\r\n1\t2\t3\r\n", &dialect).unwrap();
+        assert_eq!(next_input, "");
+        assert_eq!(records[0], vec!["a", "b,c", "d\te"]);
+        assert_eq!(records[1], vec!["1", "2", "3"]);
+
+        let (_, fields) = csv::record_with(&dialect).parse("x,y\tz").unwrap();
+        assert_eq!(fields, vec!["x,y", "z"]);
+    }
+
+    #[test]
+    fn grammar_registry() {
+        let mut registry = registry::GrammarRegistry::with_csv_formats();
+        let mut names = registry.names().collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, vec!["csv", "psv", "tsv"]);
+
+        let (_, records) = registry.parse("psv", "a|b\n1|2").unwrap().unwrap();
+        assert_eq!(records, vec![vec!["a", "b"], vec!["1", "2"]]);
+        let (_, records) = registry.parse("csv", "a|b\n1|2").unwrap().unwrap();
+        assert_eq!(records, vec![vec!["a|b"], vec!["1|2"]]);
+        assert!(registry.parse("xml", "<a/>").is_none());
+
+        registry.register("lines", |input| {
+            zero_or_more(left_from_pair(csv::record, line_break)).parse(input)
+        });
+        let (next_input, records) = registry.parse("lines", "a,b\nc\nd").unwrap().unwrap();
+        assert_eq!(next_input, "d");
+        assert_eq!(records.len(), 2);
+    }
+
+    #[test]
+    fn eof_and_all_consuming() {
+        assert_eq!(eof(""), Ok(("", ())));
+        assert_eq!(eof("a"), Err(Error::new("a", ErrorCode::Eof)));
+        assert_eq!(
+            left_from_pair(csv::record, eof).parse("a,b"),
+            Ok(("", vec!["a".to_string(), "b".to_string()]))
+        );
+
+        let mut combi = all_consuming(zero_or_more(char('a')));
+        assert_eq!(combi.parse("aa"), Ok(("", vec!['a', 'a'])));
+        assert_eq!(combi.parse("aab"), Err(Error::new("b", ErrorCode::Eof)));
+    }
+
+    #[test]
+    fn rest_parsers() {
+        assert_eq!(rest("anđele"), Ok(("", "anđele")));
+        assert_eq!(rest(""), Ok(("", "")));
+        assert_eq!(rest_len("anđele"), Ok(("anđele", 7)));
+
+        let mut combi = pair(left_from_pair(csv::field, char(',')), rest);
+        assert_eq!(
+            combi.parse("key,the rest, as is"),
+            Ok(("", ("key".to_string(), "the rest, as is")))
+        );
+    }
+
+    #[test]
+    fn line_parsers() {
+        assert_eq!(till_line_break("a\rb\r\nc"), Ok(("\r\nc", "a\rb")));
+        assert_eq!(till_line_break("abc"), Ok(("", "abc")));
+        assert_eq!(till_line_break("\nabc"), Ok(("\nabc", "")));
+
+        let input = "first\r\nsecond\n\nlast";
+        let (next_input, lines) = zero_or_more(line).parse(input).unwrap();
+        assert_eq!(next_input, "");
+        assert_eq!(lines, vec!["first", "second", "", "last"]);
+        assert_eq!(line(""), Err(Error::new("", ErrorCode::NoInput)));
+
+        let (next_input, lines) = zero_or_more(line_with_break).parse(input).unwrap();
+        assert_eq!(next_input, "");
+        assert_eq!(lines, vec!["first\r\n", "second\n", "\n", "last"]);
+    }
+
+    #[test]
+    fn csv_numbered_records() {
+        let input = "id,note\r\n1,\"two\r\nlines\"\r\n2,\"three\nmore\nlines\"\n3,plain\n";
+        let (_, records) = csv::parse_numbered(input).unwrap();
+        let lines = records.iter().map(|rec| rec.line).collect::<Vec<_>>();
+        assert_eq!(lines, vec![1, 2, 4, 7]);
+        assert_eq!(records[3].fields, vec!["3", "plain"]);
+
+        let input = std::fs::read_to_string("src/test_data/books.csv").unwrap();
+        let (_, records) = csv::parse_numbered(input.as_str()).unwrap();
+        assert_eq!(records.last().unwrap().line, 30);
+    }
+
+    #[test]
+    fn csv_numbered_records_with_escapes() {
+        let (_, records) =
+            csv::parse_numbered_with("a\\nb\tc\nd\te\n", &csv::Dialect::mysql()).unwrap();
+        let lines = records.iter().map(|rec| rec.line).collect::<Vec<_>>();
+        assert_eq!(lines, vec![1, 2]);
+        assert_eq!(records[0].fields, vec!["a\nb", "c"]);
+    }
+
+    #[test]
+    fn csv_records_iterator() {
+        let input = std::fs::read_to_string("src/test_data/books.csv").unwrap();
+        let (_, expected) = csv::parse_string(input.as_str()).unwrap();
+        let records = csv::records(input.as_str())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(records, expected);
+
+        let mut records = csv::records("a,b\n1\n");
+        assert!(records.next().unwrap().is_ok());
+        assert_eq!(
+            records.next(),
+            Some(Err(Error::failure(
+                "1\n",
+                Reason::InvalidInput {
+                    expected: "more fields in this record"
+                }
+            )))
+        );
+        assert_eq!(records.next(), None);
+        assert_eq!(records.remaining(), "1\n");
+    }
+
+    #[test]
+    fn csv_projection() {
+        let input = "id,name,country\r\n1,Ana,RS\r\n2,\"Bo, Jr.\",US\r\n";
+        let projection = csv::Projection::new()
+            .rename("country", "Country")
+            .select(0)
+            .select("id");
+        let (_, records) = csv::parse_projected(input, &projection).unwrap();
+        assert_eq!(
+            records,
+            vec![
+                vec!["Country", "id", "id"],
+                vec!["RS", "1", "1"],
+                vec!["US", "2", "2"],
+            ]
+        );
+
+        let projection = csv::Projection::new().select("missing");
+        assert!(csv::parse_projected(input, &projection)
+            .unwrap_err()
+            .is_failure());
+        let projection = csv::Projection::new().select(3);
+        assert!(csv::parse_projected(input, &projection).is_err());
+    }
+
+    #[test]
+    fn csv_dedup() {
+        let input = "id,name\n1,a\n2,b\n1,c\n2,b\n3,b\n";
+        let (_, result) = csv::dedup(input, &[0]).unwrap();
+        assert_eq!(result.duplicates, vec![3, 4]);
+        assert_eq!(
+            result.records,
+            vec![
+                vec!["id", "name"],
+                vec!["1", "a"],
+                vec!["2", "b"],
+                vec!["3", "b"]
+            ]
+        );
+
+        let (_, result) = csv::dedup(input, &[]).unwrap();
+        assert_eq!(result.duplicates, vec![4]);
+
+        let mut deduper = csv::Deduper::new(&[1, 0]);
+        let mut reader = csv::Reader::new("x,y\nab,c\na,bc\nab,c\n".as_bytes());
+        let flags = reader
+            .by_ref()
+            .map(|record| deduper.is_duplicate(&record.unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(flags, vec![false, false, false, true]);
+
+        assert!(csv::dedup("a,b\n1\n", &[0]).is_err());
+    }
+
+    #[test]
+    fn csv_sort() {
+        let (_, mut records) = csv::parse_string("b,10\na,9\n,1\nc,\nb,2.5\nx,abc\n").unwrap();
+        csv::sort(
+            &mut records,
+            &[csv::SortKey::new(0), csv::SortKey::new(1).numeric()],
+        );
+        let keys = records
+            .iter()
+            .map(|rec| format!("{}:{}", rec[0], rec[1]))
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec!["a:9", "b:2.5", "b:10", "c:", "x:abc", ":1"]);
+
+        let key = csv::SortKey::new(1).numeric().descending().nulls_first();
+        csv::sort(&mut records, &[key]);
+        let values = records
+            .iter()
+            .map(|rec| rec[1].as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(values, vec!["", "abc", "10", "9", "2.5", "1"]);
+
+        let mut writer = csv::Writer::new(Vec::new());
+        writer.write_records(&records[..2]).unwrap();
+        assert_eq!(writer.into_inner(), b"c,\r\nx,abc\r\n");
+    }
+
+    #[test]
+    fn csv_sort_external() {
+        let input = std::fs::read_to_string("src/test_data/books.csv").unwrap();
+        let (_, mut expected) = csv::parse_string(input.as_str()).unwrap();
+        let key = [
+            csv::SortKey::new(3),
+            csv::SortKey::new(0).numeric().descending(),
+        ];
+        csv::sort(&mut expected[1..], &key);
+
+        let mut writer = csv::Writer::new(Vec::new());
+        let reader = csv::Reader::new(input.as_bytes());
+        csv::sort_external(reader, &mut writer, &key, 7, true).unwrap();
+        let output = String::from_utf8(writer.into_inner()).unwrap();
+        let (_, records) = csv::parse_string(&output).unwrap();
+        assert_eq!(records, expected);
+    }
+
+    #[test]
+    fn csv_fingerprint() {
+        let (_, canonical) = csv::fingerprint("a,b\r\n1,\"x,y\"\r\n").unwrap();
+        let (_, same) = csv::fingerprint("\"a\",b\n\"1\",\"x,y\"").unwrap();
+        assert_eq!(canonical, same);
+        assert_eq!(canonical, 0xcdc8_f864_8331_75aa);
+
+        let (_, shifted) = csv::fingerprint("a,b\r\n1x,\",y\"\r\n").unwrap();
+        assert_ne!(canonical, shifted);
+        let (_, reordered) = csv::fingerprint("1,\"x,y\"\r\na,b\r\n").unwrap();
+        assert_ne!(canonical, reordered);
+        assert!(csv::fingerprint("a,b\n1\n").is_err());
+    }
+
+    #[test]
+    fn csv_render_table() {
+        let (_, records) =
+            csv::parse_string("id,name,note\n1,Anđela,\"two\nlines\"\n22,Bo,a|b\n").unwrap();
+        let options = csv::TableOptions {
+            header: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            csv::render_table(&records, &options),
+            concat!(
+                "id | name   | note\n",
+                "---+--------+-----------\n",
+                "1  | Anđela | two\\nlines\n",
+                "22 | Bo     | a|b\n",
+            )
+        );
+
+        let options = csv::TableOptions {
+            style: csv::TableStyle::Markdown,
+            max_width: Some(5),
+            ..Default::default()
+        };
+        assert_eq!(
+            csv::render_table(&records, &options),
+            concat!(
+                "| id  | name  | note  |\n",
+                "| --- | ----- | ----- |\n",
+                "| 1   | Anđe… | two\\… |\n",
+                "| 22  | Bo    | a\\|b  |\n",
+            )
+        );
+        assert_eq!(csv::render_table(&[], &options), "");
+
+        let options = csv::TableOptions {
+            max_width: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(csv::render_table(&records[1..2], &options), " |  |\n");
+    }
+
+    #[cfg(feature = "unicode-width")]
+    #[test]
+    fn csv_render_table_display_width() {
+        let records = vec![
+            vec!["東京".to_string(), "x".to_string()],
+            vec!["e\u{301}te\u{301}".to_string(), "y".to_string()],
+            vec!["abcd".to_string(), "z".to_string()],
+        ];
+        assert_eq!(
+            csv::render_table(&records, &csv::TableOptions::default()),
+            "東京 | x\ne\u{301}te\u{301}  | y\nabcd | z\n"
+        );
+        let options = csv::TableOptions {
+            max_width: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(
+            csv::render_table(&records, &options),
+            "東… | x\ne\u{301}te\u{301} | y\nab… | z\n"
+        );
+    }
+
+    #[test]
+    fn csv_diff() {
+        let old = "id,name,qty\n1,apple,3\n2,pear,5\n3,plum,1\n";
+        let new = "id,name,qty\n3,plum,1\n1,apple,4\n4,fig,2\n";
+        let (_, diff) = csv::diff(old, new, &[0]).unwrap();
+        assert_eq!(diff.removed, vec![vec!["2", "pear", "5"]]);
+        assert_eq!(diff.added, vec![vec!["4", "fig", "2"]]);
+        assert_eq!(
+            diff.changed,
+            vec![csv::ChangedRecord {
+                key: vec!["1".to_string()],
+                cells: vec![csv::CellChange {
+                    column: 2,
+                    old: "3".to_string(),
+                    new: "4".to_string(),
+                }],
+            }]
+        );
+
+        let (_, diff) = csv::diff(
+            old,
+            "\"id\",name,qty\r\n1,apple,3\r\n2,pear,5\r\n3,plum,1",
+            &[0],
+        )
+        .unwrap();
+        assert!(diff.is_empty());
+        assert!(csv::diff(old, "a,b\n1\n", &[0]).is_err());
+    }
+
+    #[test]
+    fn csv_aggregate() {
+        let input = "item,qty,color\na,3,red\nb,,blue\nc,4.5,red\nd,n/a,green\ne,-1.5,red\n";
+        let sum = csv::aggregate(input, "qty", csv::Agg::Sum).unwrap().1;
+        assert_eq!(sum.value, Some(6.0));
+        assert_eq!((sum.count, sum.skipped), (3, 2));
+        let min = csv::aggregate(input, 1, csv::Agg::Min).unwrap().1;
+        assert_eq!(min.value, Some(-1.5));
+        let max = csv::aggregate(input, 1, csv::Agg::Max).unwrap().1;
+        assert_eq!(max.value, Some(4.5));
+        let mean = csv::aggregate(input, "qty", csv::Agg::Mean).unwrap().1;
+        assert_eq!(mean.value, Some(2.0));
+        let distinct = csv::aggregate(input, "color", csv::Agg::CountDistinct)
+            .unwrap()
+            .1;
+        assert_eq!(distinct.value, Some(3.0));
+        assert_eq!(distinct.count, 5);
+
+        let empty = csv::aggregate("qty\n", "qty", csv::Agg::Mean).unwrap().1;
+        assert_eq!(empty.value, None);
+        let empty = csv::aggregate("qty\n", "qty", csv::Agg::Sum).unwrap().1;
+        assert_eq!(empty.value, None);
+        let empty = csv::aggregate("qty\n\n", "qty", csv::Agg::CountDistinct)
+            .unwrap()
+            .1;
+        assert_eq!((empty.value, empty.skipped), (None, 1));
+        assert!(csv::aggregate(input, "price", csv::Agg::Sum).is_err());
+    }
+
+    #[test]
+    fn csv_head_tail_sample() {
+        let input = std::fs::read_to_string("src/test_data/books.csv").unwrap();
+        let (_, all) = csv::parse_string(input.as_str()).unwrap();
+
+        let (rest, head) = csv::head(input.as_str(), 3).unwrap();
+        assert_eq!(head, all[..3]);
+        assert!(rest.starts_with("3,"));
+        let (rest, head) = csv::head(input.as_str(), 100).unwrap();
+        assert_eq!((rest, head.len()), ("", 30));
+
+        let (_, tail) = csv::tail(input.as_str(), 4).unwrap();
+        assert_eq!(tail, all[26..]);
+        assert!(csv::tail(input.as_str(), 0).unwrap().1.is_empty());
+
+        let (_, sample) = csv::sample(input.as_str(), 5, 42).unwrap();
+        assert_eq!(sample.len(), 5);
+        assert_eq!(csv::sample(input.as_str(), 5, 42).unwrap().1, sample);
+        let ids = sample
+            .iter()
+            .map(|rec| all.iter().position(|other| other == rec).unwrap())
+            .collect::<Vec<_>>();
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_ne!(csv::sample(input.as_str(), 5, 7).unwrap().1, sample);
+        assert_eq!(csv::sample(input.as_str(), 50, 1).unwrap().1, all);
+    }
+
+    #[test]
+    fn csv_stray_quotes() {
+        let input = "id,title\n1,test\"quote\n";
+        assert!(csv::parse_string(input).unwrap_err().is_failure());
+
+        let lenient = csv::Dialect {
+            stray_quotes: csv::StrayQuotes::Literal,
+            ..Default::default()
+        };
+        let (_, records) = csv::parse_with(input, &lenient).unwrap();
+        assert_eq!(records[1], vec!["1", "test\"quote"]);
+        let (_, field) = csv::field_with(&lenient).parse("a\"b\"c,d").unwrap();
+        assert_eq!(field, "a\"b\"c");
+        let (_, field) = csv::field_with(&lenient).parse("\"quoted\",d").unwrap();
+        assert_eq!(field, "quoted");
+
+        let strict = csv::Dialect {
+            stray_quotes: csv::StrayQuotes::Error,
+            ..Default::default()
+        };
+        assert_eq!(
+            csv::parse_with(input, &strict).unwrap_err().code,
+            ErrorCode::Failure(Reason::InvalidInput {
+                expected: "no quotes inside an unquoted field"
+            })
+        );
+        let (_, records) = csv::parse_with("a,\"b\"\"c\"\n", &strict).unwrap();
+        assert_eq!(records[0], vec!["a", "b\"c"]);
+    }
+
+    #[test]
+    fn csv_allow_empty() {
+        assert_eq!(
+            csv::parse_string(""),
+            Err(Error::new("", ErrorCode::NoInput))
+        );
+
+        let dialect = csv::Dialect {
+            allow_empty: true,
+            ..Default::default()
+        };
+        assert_eq!(csv::parse_with("", &dialect), Ok(("", vec![])));
+        assert_eq!(
+            csv::record_with(&dialect)(""),
+            Ok(("", vec![String::new()]))
+        );
+        assert_eq!(csv::records_with("", &dialect).count(), 0);
+        assert_eq!(
+            csv::parse_with("a\n\n", &dialect),
+            Ok(("", vec![vec!["a".to_string()], vec![String::new()]]))
+        );
+        assert_eq!(
+            csv::parse_with("a,b\r\n", &dialect),
+            Ok(("", vec![vec!["a".to_string(), "b".to_string()]]))
+        );
+    }
+
+    #[test]
+    fn csv_parse_owned() {
+        fn load(body: String) -> Result<Vec<csv::CsvRecord>, csv::FileError> {
+            csv::parse_string_owned(body)
+        }
+
+        let records = load(String::from("id,name\r\n1,\"Ana\"\r\n")).unwrap();
+        assert_eq!(records, vec![vec!["id", "name"], vec!["1", "Ana"]]);
+
+        match load(String::from("id,name\n1\n")) {
+            Err(csv::FileError::Parse { offset, .. }) => assert_eq!(offset, 8),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(matches!(
+            csv::parse_bytes_owned(vec![b'a', 0xff]),
+            Err(csv::FileError::Utf8(_))
+        ));
+        assert_eq!(
+            csv::parse_owned_with(String::from("a;b\n"), &csv::Dialect::with_delimiter(';'))
+                .unwrap(),
+            vec![vec!["a", "b"]]
+        );
+    }
+
+    #[test]
+    fn error_to_owned() {
+        let input = "id,name\n1\n";
+        let owned = csv::parse_string(input)
+            .unwrap_err()
+            .to_owned_error(input.len());
+        assert_eq!(owned.offset, 8);
+        assert_eq!(owned.excerpt, "1\n");
+
+        let long = "ü".repeat(40);
+        let owned = Error::new(long.as_str(), ErrorCode::Eof).to_owned_error(long.len() + 3);
+        assert_eq!(owned.offset, 3);
+        assert_eq!(owned.excerpt.chars().count(), 32);
+        assert_eq!(
+            owned.to_string(),
+            format!(
+                "parse error at byte 3 near {:?}: expected end of input",
+                owned.excerpt
+            )
+        );
+
+        let handle = std::thread::spawn(move || owned.code);
+        assert_eq!(handle.join().unwrap(), ErrorCode::Eof);
+    }
+
+    #[test]
+    fn csv_shared_parser() {
+        fn assert_shareable<T: Clone + Send + Sync>() {}
+        assert_shareable::<csv::Dialect>();
+        assert_shareable::<csv::CsvParser>();
+
+        let parser = csv::CsvParser::new(csv::Dialect::with_delimiter(';'));
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                let mut parser = parser.clone();
+                std::thread::spawn(move || {
+                    let input = format!("id;n\n{};{}\n", i, i * 2);
+                    parser
+                        .parse(input.as_str())
+                        .map(|(_, records)| records)
+                        .unwrap()
+                })
+            })
+            .collect();
+        for (i, handle) in handles.into_iter().enumerate() {
+            assert_eq!(
+                handle.join().unwrap()[1],
+                vec![i.to_string(), (i * 2).to_string()]
+            );
+        }
+        assert_eq!(parser.records("a;b\nc;d").count(), 2);
+    }
+
+    #[cfg(feature = "ffi")]
+    #[test]
+    fn ffi_csv() {
+        use crate::ffi::*;
+        use std::ptr;
+
+        unsafe fn field(get: impl Fn(*mut *const u8, *mut usize) -> i32) -> String {
+            let (mut data, mut len) = (ptr::null(), 0);
+            assert_eq!(get(&mut data, &mut len), LIBPARSE_OK);
+            String::from_utf8(std::slice::from_raw_parts(data, len).to_vec()).unwrap()
+        }
+
+        let input = "id,name\r\n1,\"Ana, B\"\r\n";
+        unsafe {
+            let mut doc = ptr::null_mut();
+            let status = libparse_csv_parse(input.as_ptr(), input.len(), &mut doc, ptr::null_mut());
+            assert_eq!(status, LIBPARSE_OK);
+            assert_eq!(libparse_document_len(doc), 2);
+            assert_eq!(libparse_document_record_len(doc, 1), 2);
+            assert_eq!(
+                field(|d, l| libparse_document_field(doc, 1, 1, d, l)),
+                "Ana, B"
+            );
+            let (mut data, mut len) = (ptr::null(), 0);
+            assert_eq!(
+                libparse_document_field(doc, 2, 0, &mut data, &mut len),
+                LIBPARSE_ERR_INDEX
+            );
+            libparse_document_free(doc);
+
+            let bad = "a,b\nc\n";
+            let mut offset = 0;
+            let status = libparse_csv_parse(bad.as_ptr(), bad.len(), &mut doc, &mut offset);
+            assert_eq!((status, offset), (LIBPARSE_ERR_PARSE, 4));
+            let invalid = [b'a', 0xff];
+            let status = libparse_csv_parse(invalid.as_ptr(), 2, &mut doc, ptr::null_mut());
+            assert_eq!(status, LIBPARSE_ERR_UTF8);
+
+            let mut iter = ptr::null_mut();
+            assert_eq!(
+                libparse_csv_iter_new(input.as_ptr(), input.len(), &mut iter),
+                LIBPARSE_OK
+            );
+            assert_eq!(libparse_csv_iter_next(iter), LIBPARSE_OK);
+            assert_eq!(field(|d, l| libparse_csv_iter_field(iter, 0, d, l)), "id");
+            assert_eq!(libparse_csv_iter_next(iter), LIBPARSE_OK);
+            assert_eq!(libparse_csv_iter_record_len(iter), 2);
+            assert_eq!(
+                field(|d, l| libparse_csv_iter_field(iter, 1, d, l)),
+                "Ana, B"
+            );
+            assert_eq!(libparse_csv_iter_next(iter), LIBPARSE_DONE);
+            libparse_csv_iter_free(iter);
+
+            assert_eq!(
+                libparse_csv_iter_new(bad.as_ptr(), bad.len(), &mut iter),
+                LIBPARSE_OK
+            );
+            assert_eq!(libparse_csv_iter_next(iter), LIBPARSE_OK);
+            assert_eq!(libparse_csv_iter_next(iter), LIBPARSE_ERR_PARSE);
+            assert_eq!(libparse_csv_iter_error_offset(iter), 4);
+            assert_eq!(libparse_csv_iter_next(iter), LIBPARSE_DONE);
+            libparse_csv_iter_free(iter);
+        }
+    }
+
+    #[cfg(feature = "wasm")]
+    #[test]
+    fn wasm_parse_csv() {
+        assert_eq!(
+            crate::wasm::parse_csv("id,name\n1,\"A \"\"B\"\"\"\n"),
+            r#"{"records":[["id","name"],["1","A \"B\""]]}"#
+        );
+        assert_eq!(crate::wasm::validate_csv("a\nb\n"), r#"{"records":2}"#);
+        assert!(crate::wasm::parse_csv("a,b\nc\n")
+            .starts_with(r#"{"error":{"offset":4,"excerpt":"c\n","message":"#));
+    }
+
+    #[cfg(feature = "python")]
+    #[test]
+    fn python_module() {
+        use pyo3::prelude::*;
+        use pyo3::types::PyDict;
+
+        Python::initialize();
+        Python::attach(|py| {
+            let module = PyModule::new(py, "libparse").unwrap();
+            crate::python::libparse(&module).unwrap();
+            let locals = PyDict::new(py);
+            locals.set_item("libparse", module).unwrap();
+            py.run(
+                cr#"
+assert libparse.parse_string("a,b\n1,2\n") == [["a", "b"], ["1", "2"]]
+dialect = libparse.Dialect(delimiter=";", stray_quotes="literal")
+assert dialect.delimiter == ";" and dialect.stray_quotes == "literal"
+assert libparse.parse_string('x;y"z\n', dialect) == [["x", 'y"z']]
+assert list(libparse.records("a\nb\n")) == [["a"], ["b"]]
+try:
+    libparse.parse_string("a,b\nc\n")
+    raise AssertionError("expected a parse error")
+except libparse.ParseError as err:
+    assert "byte 4" in str(err) and isinstance(err, ValueError)
+it = libparse.records("a,b\nc\n")
+assert next(it) == ["a", "b"]
+try:
+    next(it)
+    raise AssertionError("expected a parse error")
+except libparse.ParseError:
+    pass
+"#,
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+        });
+    }
+
+    #[test]
+    fn csv_records_owned() {
+        let dialect = csv::Dialect::default();
+        let mut records = csv::records_owned(String::from("a,b\n1,2\n3\n"), &dialect);
+        assert_eq!(
+            records.next(),
+            Some(Ok(vec!["a".to_string(), "b".to_string()]))
+        );
+        assert_eq!(
+            records.next(),
+            Some(Ok(vec!["1".to_string(), "2".to_string()]))
+        );
+        assert_eq!(records.next().unwrap().unwrap_err().offset, 8);
+        assert_eq!(records.next(), None);
+    }
+
+    #[test]
+    fn csv_parse_report() {
+        let input = "\u{feff}id,name\r\n1,\"Ana\"\n2,Bo \r\n";
+        let (_, (records, report)) = csv::parse_reported(input, &csv::Dialect::default()).unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(
+            (report.records, report.fields, report.quoted_fields),
+            (3, 6, 1)
+        );
+        assert_eq!(
+            report.warnings,
+            vec![
+                csv::ParseWarning::ByteOrderMark,
+                csv::ParseWarning::MixedLineEndings,
+                csv::ParseWarning::TrailingWhitespace { record: 2 },
+            ]
+        );
+
+        let (_, (_, report)) =
+            csv::parse_reported("a,\"b\"\n\"c\",d", &csv::Dialect::default()).unwrap();
+        assert_eq!(report.quoted_fields, 2);
+        assert!(report.warnings.is_empty());
+        assert!(csv::parse_reported("a,b\nc\n", &csv::Dialect::default()).is_err());
+    }
+
+    #[test]
+    fn csv_parse_observer() {
+        #[derive(Default)]
+        struct Metrics {
+            records: usize,
+            progress: Vec<(usize, usize)>,
+            warnings: Vec<csv::ParseWarning>,
+            errors: usize,
+        }
+
+        impl csv::ParseObserver for Metrics {
+            fn on_record(&mut self, _record: &csv::CsvRecord) {
+                self.records += 1;
+            }
+
+            fn on_error(&mut self, _error: &Error<&str>) {
+                self.errors += 1;
+            }
+
+            fn on_warning(&mut self, warning: &csv::ParseWarning) {
+                self.warnings.push(*warning);
+            }
+
+            fn bytes_consumed(&mut self, consumed: usize, total: usize) {
+                self.progress.push((consumed, total));
+            }
+        }
+
+        let mut metrics = Metrics::default();
+        let (_, records) =
+            csv::parse_observed("a,b\r\n1,2\n", &csv::Dialect::default(), &mut metrics).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(metrics.records, 2);
+        assert_eq!(metrics.progress, vec![(5, 9), (9, 9)]);
+        assert_eq!(metrics.warnings, vec![csv::ParseWarning::MixedLineEndings]);
+
+        let mut metrics = Metrics::default();
+        assert!(csv::parse_observed("a,b\nc\n", &csv::Dialect::default(), &mut metrics).is_err());
+        assert_eq!((metrics.records, metrics.errors), (1, 1));
+    }
+
+    #[test]
+    fn csv_reader_progress() {
+        let mut reader = csv::Reader::new("a,b\n1,2\n".as_bytes());
+        assert_eq!(reader.progress(), None);
+        reader = reader.with_total_len(8);
+        assert_eq!(reader.progress(), Some(0.0));
+        reader.read_record().unwrap();
+        assert_eq!(reader.progress(), Some(0.5));
+        reader.read_record().unwrap();
+        assert_eq!(reader.progress(), Some(1.0));
+
+        let mut reader = csv::Reader::open("src/test_data/books.csv").unwrap();
+        assert!(reader.progress().unwrap() <= 1.0);
+        while reader.read_record().unwrap().is_some() {}
+        assert_eq!(reader.progress(), Some(1.0));
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn csv_parse_file_progress() {
+        struct Progress(Vec<f64>);
+
+        impl csv::ParseObserver for Progress {
+            fn bytes_consumed(&mut self, consumed: usize, total: usize) {
+                self.0.push(consumed as f64 / total as f64);
+            }
+        }
+
+        let mut progress = Progress(Vec::new());
+        let records = csv::parse_file_observed("src/test_data/books.csv", &mut progress).unwrap();
+        assert_eq!(progress.0.len(), records.len());
+        assert!(progress.0.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(progress.0.last(), Some(&1.0));
+    }
+
+    #[test]
+    fn csv_parse_budgeted() {
+        use std::time::{Duration, Instant};
+
+        let input = "a,b\n1,2\n3,4\n";
+        let dialect = csv::Dialect::default();
+        let (rest, parsed) = csv::parse_budgeted(input, &dialect, &mut csv::Budget::new()).unwrap();
+        assert_eq!((rest, parsed.records.len(), parsed.exceeded), ("", 3, None));
+
+        let mut budget = csv::Budget::new().max_records(2);
+        let (rest, parsed) = csv::parse_budgeted(input, &dialect, &mut budget).unwrap();
+        assert_eq!((rest, parsed.records.len()), ("3,4\n", 2));
+        assert_eq!(parsed.exceeded, Some(csv::BudgetExceeded::Records));
+        let mut budget = csv::Budget::new().max_records(3);
+        let (_, parsed) = csv::parse_budgeted(input, &dialect, &mut budget).unwrap();
+        assert_eq!(parsed.exceeded, None);
+
+        let mut budget = csv::Budget::new().max_bytes(7);
+        let (rest, parsed) = csv::parse_budgeted(input, &dialect, &mut budget).unwrap();
+        assert_eq!((rest, parsed.records.len()), ("1,2\n3,4\n", 1));
+        assert_eq!(parsed.exceeded, Some(csv::BudgetExceeded::Bytes));
+        let unterminated = "\"a,b\n1,2\n3,";
+        let mut budget = csv::Budget::new().max_bytes(4);
+        let (rest, parsed) = csv::parse_budgeted(unterminated, &dialect, &mut budget).unwrap();
+        assert_eq!((rest, parsed.records.len()), (unterminated, 0));
+        assert_eq!(parsed.exceeded, Some(csv::BudgetExceeded::Bytes));
+        let mut budget = csv::Budget::new().max_bytes(input.len());
+        let (_, parsed) = csv::parse_budgeted(input, &dialect, &mut budget).unwrap();
+        assert_eq!((parsed.records.len(), parsed.exceeded), (3, None));
+        // Limits that split a CRLF
+        let crlf = "a,b\r\nc,d\r\n";
+        for (max, records) in [(4, 0), (9, 1)] {
+            let mut budget = csv::Budget::new().max_bytes(max);
+            let (rest, parsed) = csv::parse_budgeted(crlf, &dialect, &mut budget).unwrap();
+            assert_eq!(parsed.records.len(), records);
+            assert_eq!(rest.len(), crlf.len() - 5 * records);
+            assert_eq!(parsed.exceeded, Some(csv::BudgetExceeded::Bytes));
+        }
+
+        let start = Instant::now();
+        let mut ticks = 0;
+        let clock = || {
+            ticks += 1;
+            start + Duration::from_secs(ticks)
+        };
+        let mut budget =
+            csv::Budget::new().deadline_with_clock(start + Duration::from_secs(2), clock);
+        let (rest, parsed) = csv::parse_budgeted(input, &dialect, &mut budget).unwrap();
+        assert_eq!((rest, parsed.records.len()), ("1,2\n3,4\n", 1));
+        assert_eq!(parsed.exceeded, Some(csv::BudgetExceeded::Deadline));
+
+        let mut budget = csv::Budget::new().max_records(1);
+        let (_, parsed) = csv::parse_budgeted("a,b\n1\n", &dialect, &mut budget).unwrap();
+        assert_eq!(parsed.exceeded, Some(csv::BudgetExceeded::Records));
+        assert!(csv::parse_budgeted("a,b\n1\n", &dialect, &mut csv::Budget::new()).is_err());
+    }
+
+    #[test]
+    fn unicode_char_parsers() {
+        use crate::chars::unicode::*;
+
+        assert_eq!(satisfy(|ch| ch == 'x')("xy"), Ok(("y", 'x')));
+        assert_eq!(letter("ћирилица"), Ok(("ирилица", 'ћ')));
+        assert_eq!(letter("1a"), Err(Error::new("1a", ErrorCode::Predicate)));
+        assert_eq!(numeric("٣x"), Ok(("x", '٣')));
+        assert_eq!(alphanumeric("Ω1"), Ok(("1", 'Ω')));
+        assert_eq!(lowercase("ßA"), Ok(("A", 'ß')));
+        assert_eq!(uppercase("Ж"), Ok(("", 'Ж')));
+        assert_eq!(white_space("\u{3000}a"), Ok(("a", '\u{3000}')));
+        assert_eq!(white_space(""), Err(Error::new("", END_OF_STRING)));
+    }
+
+    #[cfg(feature = "unicode-xid")]
+    #[test]
+    fn unicode_identifiers() {
+        use crate::chars::unicode::*;
+
+        assert_eq!(identifier("име_1 = 2"), Ok((" = 2", "име_1")));
+        assert_eq!(identifier("変数x"), Ok(("", "変数x")));
+        assert_eq!(
+            identifier("1abc"),
+            Err(Error::new("1abc", ErrorCode::Predicate))
+        );
+        assert_eq!(xid_continue("_"), Ok(("", '_')));
+        assert!(xid_start("_").is_err());
+    }
+
+    #[cfg(feature = "unicode-segmentation")]
+    #[test]
+    fn grapheme_parsers() {
+        use crate::chars::grapheme::*;
+
+        let family = "👨‍👩‍👧";
+        let input = format!("{}e\u{301}x", family);
+        assert_eq!(any_grapheme(&input), Ok((&input[family.len()..], family)));
+        assert_eq!(
+            take_graphemes(2)(&input),
+            Ok(("x", &input[..input.len() - 1]))
+        );
+        assert_eq!(take_graphemes(3)(&input), Ok(("", input.as_str())));
+        assert_eq!(
+            take_graphemes(4)(&input),
+            Err(Error::new(input.as_str(), END_OF_STRING))
+        );
+        assert_eq!(
+            take_graphemes_while(|g| g != "x")(&input),
+            Ok(("x", &input[..input.len() - 1]))
+        );
+        assert_eq!(count_graphemes(&input), 3);
+        assert_eq!(input.chars().count(), 8);
+
+        let mut short = max_graphemes(2, take_graphemes_while(|g| g != ","));
+        assert_eq!(short("e\u{301}e\u{301},"), Ok((",", "e\u{301}e\u{301}")));
+        assert_eq!(short("abc,"), Err(Error::new("abc,", ErrorCode::Predicate)));
+        assert_eq!(any_grapheme(""), Err(Error::new("", END_OF_STRING)));
+
+        use crate::Completeness::Partial;
+        assert_eq!(
+            any_grapheme.complete_or_partial().parse(""),
+            Ok(("", Partial))
+        );
+        assert_eq!(
+            take_graphemes(4).complete_or_partial().parse(&input),
+            Ok((input.as_str(), Partial))
+        );
+    }
+
+    #[test]
+    fn byte_parsers() {
+        use crate::bytes::*;
+
+        let input: &[u8] = &[0xca, 0xfe, 0x01, 0x00, 0x00, 0x01, 0xff];
+        assert_eq!(byte(0xca)(input), Ok((&input[1..], 0xca)));
+        assert_eq!(
+            byte(0x00)(input),
+            Err(Error::new(input, ErrorCode::Byte(0x00)))
+        );
+        assert_eq!(any_byte(&input[6..]), Ok((&[][..], 0xff)));
+        assert_eq!(any_byte(&[]), Err(Error::new(&[][..], ErrorCode::NoInput)));
+        assert_eq!(
+            tag_bytes(b"\xca\xfe")(input),
+            Ok((&input[2..], &input[..2]))
+        );
+        assert_eq!(
+            tag_bytes(b"PK")(input),
+            Err(Error::new(input, ErrorCode::ByteTag(b"PK")))
+        );
+        assert_eq!(take_bytes(3)(input), Ok((&input[3..], &input[..3])));
+        assert_eq!(
+            take_bytes(8)(input),
+            Err(Error::new(input, ErrorCode::NoInput))
+        );
+        assert_eq!(
+            take_bytes_while(|b| b >= 0xc0)(input),
+            Ok((&input[2..], &input[..2]))
+        );
+
+        assert_eq!(be_u16(input), Ok((&input[2..], 0xcafe)));
+        assert_eq!(le_u16(input), Ok((&input[2..], 0xfeca)));
+        assert_eq!(le_u32(&input[2..]), Ok((&input[6..], 0x0100_0001)));
+        assert_eq!(be_i16(&input[5..]), Ok((&[][..], 0x01ff)));
+        assert_eq!(le_i16(&input[5..]), Ok((&[][..], -255)));
+        assert_eq!(be_u64(input), Err(Error::new(input, ErrorCode::NoInput)));
+    }
+
+    #[test]
+    fn csv_parse_bytes() {
+        let input = b"name,city\r\nJos\xe9,\"S\xe3o \"\"Paulo\"\"\"\r\nAna,\"a,b\nc\"\r\n";
+        let (rest, records) = csv::parse_bytes(input).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(records.len(), 3);
+        assert_eq!(
+            records[1],
+            vec![b"Jos\xe9".to_vec(), b"S\xe3o \"Paulo\"".to_vec()]
+        );
+        assert_eq!(records[2][1], b"a,b\nc");
+
+        let (_, lossy) = csv::parse_bytes_lossy(input).unwrap();
+        assert_eq!(lossy[1], vec!["Jos\u{fffd}", "S\u{fffd}o \"Paulo\""]);
+
+        let utf8 = std::fs::read("src/test_data/books.csv").unwrap();
+        let (_, expected) = csv::parse_string(std::str::from_utf8(&utf8).unwrap()).unwrap();
+        assert_eq!(csv::parse_bytes_lossy(&utf8).unwrap().1, expected);
+
+        let dialect = csv::Dialect::with_delimiter('¦');
+        let (_, records) = csv::parse_bytes_with("a¦b\n\u{e9}¦d".as_bytes(), &dialect).unwrap();
+        assert_eq!(records[1], vec!["é".as_bytes().to_vec(), b"d".to_vec()]);
+
+        let bad: &[u8] = b"a,b\nc\n";
+        assert_eq!(
+            csv::parse_bytes(bad),
+            Err(Error::failure(
+                &bad[3..],
+                Reason::InvalidInput {
+                    expected: "more fields in this record"
+                }
+            ))
+        );
+        assert!(csv::parse_bytes(b"a,b\"c\n").unwrap_err().is_failure());
+        let unterminated = "a,\"b\nc,d\n";
+        assert_eq!(
+            csv::parse_bytes(unterminated.as_bytes()).unwrap_err().code,
+            csv::parse_string(unterminated).unwrap_err().code
+        );
+        let limited = csv::Dialect {
+            max_quoted_len: Some(4),
+            ..Default::default()
+        };
+        assert_eq!(
+            csv::parse_bytes_with(b"\"a\"\"b\",\"abcd\"", &limited)
+                .unwrap()
+                .1,
+            vec![vec![b"a\"b".to_vec(), b"abcd".to_vec()]]
+        );
+        assert_eq!(
+            csv::parse_bytes_with(b"\"abcde\"", &limited)
+                .unwrap_err()
+                .code,
+            ErrorCode::Failure(Reason::InvalidInput {
+                expected: "quoted field within the size limit"
+            })
+        );
+        let literal = csv::Dialect {
+            stray_quotes: csv::StrayQuotes::Literal,
+            ..Default::default()
+        };
+        assert_eq!(
+            csv::parse_bytes_with(b"\"a,b", &literal).unwrap().1,
+            vec![vec![b"\"a".to_vec(), b"b".to_vec()]]
+        );
+        assert_eq!(
+            csv::parse_bytes(b""),
+            Err(Error::new(&b""[..], ErrorCode::NoInput))
+        );
+    }
+
+    #[test]
+    fn complete_or_partial() {
+        use crate::Completeness::{Complete, Partial};
+
+        assert_eq!(char('a').complete_or_partial().parse(""), Ok(("", Partial)));
+        assert_eq!(
+            char('a').complete_or_partial().parse("ab"),
+            Ok(("b", Complete('a')))
+        );
+        assert_eq!(
+            char('a').complete_or_partial().parse("b"),
+            Err(Error::new("b", ErrorCode::Char('a')))
+        );
+        assert_eq!(
+            tag("hello").complete_or_partial().parse("hel"),
+            Ok(("hel", Partial))
+        );
+        assert_eq!(
+            tag("hello").complete_or_partial().parse("help"),
+            Err(Error::new("help", ErrorCode::Tag("hello")))
+        );
+
+        let mut digits = take_while1(|ch| ch.is_ascii_digit()).complete_or_partial();
+        assert_eq!(digits.parse("123"), Ok(("123", Partial)));
+        assert_eq!(digits.parse("123,"), Ok((",", Complete("123"))));
+        assert_eq!(
+            line.complete_or_partial().parse("abc"),
+            Ok(("abc", Partial))
+        );
+        assert_eq!(
+            line.complete_or_partial().parse("abc\r"),
+            Ok(("abc\r", Partial))
+        );
+        assert_eq!(
+            line.complete_or_partial().parse("abc\r\nd"),
+            Ok(("d", Complete("abc")))
+        );
+
+        let mut pairs = zero_or_more(tag("ab").fallback_on(tag("x"))).complete_or_partial();
+        assert_eq!(pairs.parse("abxa"), Ok(("abxa", Partial)));
+        assert_eq!(pairs.parse("abxc"), Ok(("c", Complete(vec!["ab", "x"]))));
+
+        assert_eq!(
+            csv::record.complete_or_partial().parse("a,\"b"),
+            Ok(("a,\"b", Partial))
+        );
+        assert_eq!(
+            csv::record.complete_or_partial().parse("a,b\n"),
+            Ok(("\n", Complete(vec!["a".to_string(), "b".to_string()])))
+        );
+        assert_eq!(
+            crate::bytes::le_u32
+                .complete_or_partial()
+                .parse(&[1, 0][..]),
+            Ok((&[1, 0][..], Partial))
+        );
+
+        // Outside of streaming, the end of input is final
+        assert_eq!(digits_run("123"), Ok(("", "123")));
+        assert_eq!(char('a').parse(""), Err(Error::new("", END_OF_STRING)));
+        assert!(!is_streaming());
+
+        fn digits_run(input: &str) -> PResult<&str, &str> {
+            take_while1(|ch| ch.is_ascii_digit())(input)
+        }
+    }
+
+    #[test]
+    fn number_parsers() {
+        assert_eq!(digit1("00042,"), Ok((",", "00042")));
+        assert_eq!(digit1("x"), Err(Error::new("x", ErrorCode::Predicate)));
+        assert_eq!(integer_text("-0042 "), Ok((" ", "-0042")));
+        assert_eq!(integer_text("+"), Err(Error::new("", ErrorCode::Predicate)));
+        assert_eq!(float_text("-12.5e3x"), Ok(("x", "-12.5e3")));
+        assert_eq!(float_text("7.e"), Ok(("e", "7.")));
+        assert_eq!(float_text("1E+2"), Ok(("", "1E+2")));
+
+        assert_eq!(number_i64("00042"), Ok(("", 42)));
+        assert_eq!(number_i64("-9223372036854775808"), Ok(("", i64::MIN)));
+        assert!(number_i64("9223372036854775808").unwrap_err().is_failure());
+        assert_eq!(number_f64("-12.5e3x"), Ok(("x", -12500.0)));
+        assert_eq!(number_f64("3,"), Ok((",", 3.0)));
+        assert_eq!(recognize(number_f64).parse("0.50;"), Ok((";", "0.50")));
+    }
+
+    #[test]
+    fn csv_number_format() {
+        let european = csv::NumberFormat::european();
+        assert_eq!(european.parse_f64("1.234,56"), Some(1234.56));
+        assert_eq!(european.parse_f64("-1.234.567"), Some(-1234567.0));
+        assert_eq!(european.parse_f64("1234,5"), Some(1234.5));
+        assert_eq!(european.parse_f64("1.23,4"), None);
+        assert_eq!(european.parse_f64("1,2,3"), None);
+        assert_eq!(european.parse_i64("12.000"), Some(12000));
+        assert_eq!(european.parse_i64("12,5"), None);
+
+        let default = csv::NumberFormat::default();
+        assert_eq!(default.parse_f64("1234.56"), Some(1234.56));
+        assert_eq!(default.parse_f64("1,234.56"), None);
+        let english = csv::NumberFormat {
+            thousands_separator: Some(','),
+            ..Default::default()
+        };
+        assert_eq!(english.parse_f64("1,234.56"), Some(1234.56));
+    }
+
+    #[test]
+    fn csv_parse_typed() {
+        let schema = csv::Schema::new()
+            .column("count", csv::ColumnType::Integer)
+            .column(2, csv::ColumnType::Float)
+            .number_format(csv::NumberFormat::european());
+        assert_eq!(
+            csv::parse_typed("item,count,price\nčaj,two,1\n", &schema),
+            Err(csv::TypedError::Coercion {
+                record: 1,
+                column: 1,
+                value: "two".to_string(),
+                expected: csv::ColumnType::Integer
+            })
+        );
+
+        let input = "item,count,price\nčaj,2,\"1.234,50\"\nkafa,,3\n";
+        let typed = csv::parse_typed(input, &schema).unwrap();
+        assert_eq!(typed.header, vec!["item", "count", "price"]);
+        assert_eq!(
+            typed.rows,
+            vec![
+                vec![
+                    csv::Value::Text("čaj".to_string()),
+                    csv::Value::Integer(2),
+                    csv::Value::Float(1234.5)
+                ],
+                vec![
+                    csv::Value::Text("kafa".to_string()),
+                    csv::Value::Null,
+                    csv::Value::Float(3.0)
+                ],
+            ]
+        );
+
+        let missing = csv::Schema::new().column("total", csv::ColumnType::Float);
+        assert_eq!(
+            csv::parse_typed(input, &missing),
+            Err(csv::TypedError::MissingColumn(csv::Column::from("total")))
+        );
+    }
+
+    #[test]
+    fn csv_parse_typed_booleans_and_enums() {
+        let input = std::fs::read_to_string("src/test_data/books.csv").unwrap();
+        let schema = csv::Schema::new()
+            .column("Pročitano", csv::ColumnType::Boolean)
+            .column(
+                "Jezik",
+                csv::ColumnType::one_of(&["en", "sr", "el", "it", "sv", "ja", "ru"]),
+            )
+            .bool_tokens(&["Da", "yes", "1"], &["Ne", "no", "0"]);
+        let typed = csv::parse_typed(&input, &schema).unwrap();
+        assert_eq!(typed.rows[0][8], csv::Value::Boolean(true));
+        assert_eq!(typed.rows[1][8], csv::Value::Boolean(false));
+        assert_eq!(typed.rows[0][6], csv::Value::Text("en".to_string()));
+
+        let schema = csv::Schema::new().column("Jezik", csv::ColumnType::one_of(&["en", "sr"]));
+        assert!(matches!(
+            csv::parse_typed(&input, &schema),
+            Err(csv::TypedError::Coercion { record: 9, column: 6, ref value, .. }) if value == "el"
+        ));
+
+        let schema = csv::Schema::new().column(0, csv::ColumnType::Boolean);
+        let typed = csv::parse_typed("ok\nTRUE\nfalse\n\n", &schema).unwrap();
+        assert_eq!(
+            typed.rows,
+            vec![
+                vec![csv::Value::Boolean(true)],
+                vec![csv::Value::Boolean(false)],
+                vec![csv::Value::Null]
+            ]
+        );
+        assert!(csv::parse_typed("ok\nDa\n", &schema).is_err());
+    }
+
+    #[test]
+    fn csv_skip_preamble() {
+        let input = "Instrument: X-200\r\nDate: 2024-01-01\r\n\r\ntime,value\r\n0,1.5\r\n";
+        assert_eq!(
+            csv::skip_lines(3)(input),
+            Ok((
+                "time,value\r\n0,1.5\r\n",
+                "Instrument: X-200\r\nDate: 2024-01-01\r\n\r\n"
+            ))
+        );
+        assert_eq!(
+            csv::skip_until(|line| line.starts_with("time,"))(input),
+            Ok((
+                "time,value\r\n0,1.5\r\n",
+                "Instrument: X-200\r\nDate: 2024-01-01\r\n\r\n"
+            ))
+        );
+        assert_eq!(
+            csv::skip_until(|line| line == "Instrument: X-200")(input),
+            Ok((input, ""))
+        );
+        assert!(csv::skip_lines(6)(input).is_err());
+        assert!(csv::skip_until(|line| line.starts_with("id,"))(input).is_err());
+
+        let (_, (preamble, records)) =
+            csv::parse_after(input, csv::skip_lines(3), &csv::Dialect::default()).unwrap();
+        assert_eq!(preamble.lines().count(), 3);
+        assert_eq!(records, vec![vec!["time", "value"], vec!["0", "1.5"]]);
+    }
+
+    #[test]
+    fn csv_parse_with_trailer() {
+        let dialect = csv::Dialect::default();
+        let is_trailer = |record: &csv::CsvRecord| record[0] == "TOTAL" || record[0] == "COUNT";
+        let input = "item,qty,price\na,1,2\nb,3,4\nTOTAL,4\nCOUNT,2\n";
+        let (rest, parsed) = csv::parse_with_trailer(input, &dialect, is_trailer).unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(parsed.records.len(), 3);
+        assert_eq!(parsed.trailer, vec![vec!["TOTAL", "4"], vec!["COUNT", "2"]]);
+
+        let input = "item,qty\na,1\nTOTAL,1\nb,2\n";
+        assert_eq!(
+            csv::parse_with_trailer(input, &dialect, is_trailer),
+            Err(Error::failure(
+                "\nb,2\n",
+                Reason::InvalidInput {
+                    expected: "trailer records at the end of the document"
+                }
+            ))
+        );
+        assert!(csv::parse_with_trailer("a,b\nc\n", &dialect, is_trailer).is_err());
+        let (_, parsed) = csv::parse_with_trailer("a,b\r\nc,d", &dialect, is_trailer).unwrap();
+        assert!(parsed.trailer.is_empty());
+    }
+
+    #[test]
+    fn csv_parse_transformed() {
+        let dialect = csv::Dialect::default();
+        let transforms = csv::Transforms::new()
+            .trim(0)
+            .uppercase(0)
+            .column(1, |value| format!("<{}>", value));
+        assert_eq!(
+            csv::parse_transformed(" ab ,c\nd,e", &dialect, &transforms)
+                .unwrap()
+                .1,
+            vec![vec!["AB", "<c>"], vec!["D", "<e>"]]
+        );
+
+        let transforms = csv::Transforms::new()
+            .with_header()
+            .replace("price", ",", ".")
+            .lowercase("name");
+        assert_eq!(
+            csv::parse_transformed("name,price\nTea,\"1,5\"\n", &dialect, &transforms)
+                .unwrap()
+                .1,
+            vec![vec!["name", "price"], vec!["tea", "1.5"]]
+        );
+
+        let transforms = csv::Transforms::new().trim("missing");
+        assert!(csv::parse_transformed("a,b\n", &dialect, &transforms).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn csv_parse_transformed_regex() {
+        let transforms = csv::Transforms::new()
+            .with_header()
+            .replace_regex("date", r"(\d{2})/(\d{2})/(\d{4})", "$3-$2-$1")
+            .replace_regex(0, r"\s+", " ");
+        assert_eq!(
+            csv::parse_transformed(
+                "name,date\nAna  Lee,01/02/2024\nBo,n/a\n",
+                &csv::Dialect::default(),
+                &transforms
+            )
+            .unwrap()
+            .1,
+            vec![
+                vec!["name", "date"],
+                vec!["Ana Lee", "2024-02-01"],
+                vec!["Bo", "n/a"]
+            ]
+        );
+    }
+
+    #[test]
+    fn csv_writer_append() {
+        let path = std::env::temp_dir().join(format!("libparse-append-{}.csv", std::process::id()));
+        std::fs::write(&path, "a,b\r\nc,d").unwrap();
+
+        let mut writer = csv::Writer::append(&path).unwrap();
+        assert!(!writer.ends_with_line_break());
+        writer.write_record(["e", "f,g"]).unwrap();
+        assert!(writer.ends_with_line_break());
+        writer.flush().unwrap();
+        drop(writer);
+
+        let mut writer = csv::Writer::append(&path).unwrap();
+        assert!(writer.ends_with_line_break());
+        writer.write_record(["h", "i"]).unwrap();
+        drop(writer);
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(written, "a,b\r\nc,d\r\ne,\"f,g\"\r\nh,i\r\n");
+    }
+
+    #[test]
+    fn csv_writer_options() {
+        let options = csv::WriterOptions {
+            delimiter: ';',
+            line_ending: csv::LineEnding::Lf,
+            final_line_break: false,
+            ..Default::default()
+        };
+        let mut writer = csv::Writer::with_options(Vec::new(), options);
+        writer
+            .write_records(&[vec!["a", "b;c"], vec!["d,e", "f"]])
+            .unwrap();
+        assert!(!writer.ends_with_line_break());
+        assert_eq!(writer.into_inner(), b"a;\"b;c\"\nd,e;f");
+
+        let options = csv::WriterOptions {
+            delimiter: '→',
+            ..Default::default()
+        };
+        let mut writer = csv::Writer::with_options(Vec::new(), options);
+        writer.write_record(["a", "b"]).unwrap();
+        assert_eq!(String::from_utf8(writer.into_inner()).unwrap(), "a→b\r\n");
+    }
+
+    #[test]
+    fn csv_record_builder() {
+        let mut builder = csv::RecordBuilder::new().reject_nul().expected_fields(3);
+        builder.push("a").unwrap().push("b,\"c\"").unwrap();
+        assert_eq!(builder.push("x\0"), Err(csv::RecordError::Nul { field: 2 }));
+        assert_eq!(
+            builder.to_line(&csv::WriterOptions::default()),
+            Err(csv::RecordError::FieldCount {
+                expected: 3,
+                found: 2
+            })
+        );
+        builder.push(String::from("d\ne")).unwrap();
+        assert_eq!(
+            builder.push("f").map(|_| ()),
+            Err(csv::RecordError::FieldCount {
+                expected: 3,
+                found: 4
+            })
+        );
+        let line = builder.to_line(&csv::WriterOptions::default()).unwrap();
+        assert_eq!(line, "a,\"b,\"\"c\"\"\",\"d\ne\"\r\n");
+        assert_eq!(
+            csv::record(&line).unwrap().1,
+            builder.clone().build().unwrap()
+        );
+        assert_eq!(builder.build().unwrap(), vec!["a", "b,\"c\"", "d\ne"]);
+    }
+
+    #[test]
+    fn csv_record_builder_rejects_formulas() {
+        let mut builder = csv::RecordBuilder::new();
+        builder.push("a").unwrap().push("=1+1").unwrap();
+        let options = csv::WriterOptions {
+            formulas: csv::FormulaPolicy::Reject,
+            ..Default::default()
+        };
+        assert_eq!(
+            builder.to_line(&options),
+            Err(csv::RecordError::Formula { field: 1 })
+        );
+        assert_eq!(
+            builder.to_line(&csv::WriterOptions::default()).unwrap(),
+            "a,=1+1\r\n"
+        );
+    }
+
+    #[test]
+    fn csv_formula_sanitization() {
+        assert!(csv::is_formula("=SUM(A1:A2)"));
+        assert!(csv::is_formula("@cmd"));
+        assert!(!csv::is_formula("a=b"));
+        assert_eq!(csv::neutralize_formula("+1"), "'+1");
+        assert_eq!(csv::neutralize_formula("text"), "text");
+
+        let (_, records) =
+            csv::parse_string("name,note\n\"=HYPERLINK(\"\"x\"\")\",ok\nb,-2\n").unwrap();
+        assert_eq!(csv::find_formulas(&records), vec![(1, 0), (2, 1)]);
+
+        let options = csv::WriterOptions {
+            formulas: csv::FormulaPolicy::Neutralize,
+            ..Default::default()
+        };
+        let mut writer = csv::Writer::with_options(Vec::new(), options);
+        writer.write_records(&records).unwrap();
+        assert_eq!(
+            writer.into_inner(),
+            b"name,note\r\n\"'=HYPERLINK(\"\"x\"\")\",ok\r\nb,'-2\r\n"
+        );
+
+        let options = csv::WriterOptions {
+            formulas: csv::FormulaPolicy::Reject,
+            ..Default::default()
+        };
+        let mut writer = csv::Writer::with_options(Vec::new(), options);
+        assert!(writer.write_records(&records).is_err());
+        assert_eq!(writer.into_inner(), b"name,note\r\n");
+    }
+
+    #[test]
+    fn parse_all_matches() {
+        let text = "order #12 shipped, #7 pending; total 3 ☃ #";
+        let mut ticket = right_from_pair(char('#'), digit1);
+        assert_eq!(
+            ticket.parse_all_matches(text),
+            vec![("12", 6..9), ("7", 19..21)]
+        );
+        assert_eq!(
+            chars::number_i64.parse_all_matches(text),
+            vec![(12, 7..9), (7, 20..21), (3, 37..38)]
+        );
+        assert!(take_while(|ch| ch == 'x')
+            .parse_all_matches(text)
+            .is_empty());
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn chars_regex() {
+        let date = chars::regex(r"\d{4}-\d{2}-\d{2}");
+        assert_eq!(date("2024-01-31,x"), Ok((",x", "2024-01-31")));
+        assert_eq!(
+            date("on 2024-01-31"),
+            Err(Error::new("on 2024-01-31", ErrorCode::Predicate))
+        );
+
+        let version = chars::regex_captures(r"v(\d+)\.(\d+)(-\w+)?");
+        assert_eq!(
+            version("v1.2 rest"),
+            Ok((" rest", vec![Some("v1.2"), Some("1"), Some("2"), None]))
+        );
+        let mut key_value =
+            separated_pair(chars::regex("[a-z]+"), char('='), chars::regex("[0-9]+"));
+        assert_eq!(key_value.parse("ab=12;"), Ok((";", ("ab", "12"))));
+    }
+
+    #[test]
+    fn custom_parsers_compose() {
+        #[derive(Clone)]
+        struct Digits;
+
+        impl<'a> Parser<&'a str, u32> for Digits {
+            fn parse(&mut self, input: &'a str) -> PResult<&'a str, u32> {
+                chars::digit1
+                    .map(|digits: &str| digits.parse().unwrap_or(u32::MAX))
+                    .parse(input)
+            }
+        }
+
+        fn twice<I: Copy, O, P: Parser<I, O> + Clone>(parser: P) -> impl Parser<I, (O, O)> {
+            pair(parser.clone(), parser)
+        }
+
+        fn assert_send<T: Send>(_: &T) {}
+
+        let mut sum = twice(
+            Digits
+                .map(|n| n * 10)
+                .fallback_on(|input| Ok((input, 0)))
+                .spanned(),
+        )
+        .map(|((a, _), (b, _))| a + b);
+        assert_send(&sum);
+        assert_eq!(sum.parse("12x"), Ok(("x", 120)));
+
+        let mut grammars: Vec<DynParser<&str, u32>> =
+            vec![Digits.boxed(), char('x').map(|_| 0).boxed()];
+        assert_eq!(grammars[1].parse("x1"), Ok(("1", 0)));
+        let grammar: &mut dyn Parser<&str, u32> = &mut Digits;
+        assert_eq!(ByRef::new(grammar).parse("7"), Ok(("", 7)));
+    }
+
+    #[test]
+    fn rewind_non_copy_input() {
+        #[derive(Debug, PartialEq, Eq)]
+        struct Tokens {
+            tokens: Vec<String>,
+            pos: usize,
+        }
+
+        impl Rewind for Tokens {
+            type Checkpoint = usize;
+
+            fn checkpoint(&self) -> usize {
+                self.pos
+            }
+
+            fn rewind(mut self, checkpoint: usize) -> Self {
+                self.pos = checkpoint;
+                self
+            }
+        }
+
+        fn word(mut input: Tokens) -> PResult<Tokens, String> {
+            match input.tokens.get(input.pos).cloned() {
+                Some(token) => {
+                    input.pos += 1;
+                    Ok((input, token))
+                }
+                None => Err(Error::new(input, ErrorCode::Token)),
+            }
+        }
+
+        let tokens = |pos| Tokens {
+            tokens: vec!["let".to_string(), "x".to_string()],
+            pos,
+        };
+        let mut keyword_pair = pair(word.iff(|word| word == "let"), word);
+        assert_eq!(
+            keyword_pair.parse(tokens(0)),
+            Ok((tokens(2), ("let".to_string(), "x".to_string())))
+        );
+        assert_eq!(
+            keyword_pair.parse(tokens(1)),
+            Err(Error::new(tokens(1), ErrorCode::Predicate))
+        );
+        let mut three = pair(pair(word, word), word);
+        assert_eq!(
+            three.parse(tokens(0)),
+            Err(Error::new(tokens(2), ErrorCode::Token))
+        );
+        assert_eq!(
+            word.iff(|word| word == "x")
+                .with_default(String::new())
+                .parse(tokens(0)),
+            Ok((tokens(0), String::new()))
+        );
+    }
+
+    #[test]
+    fn flat_map() {
+        let mut even = chars::number_i64.flat_map(|input, number| {
+            if number % 2 == 0 {
+                Ok((input, number / 2))
+            } else {
+                Err(Error::failure(
+                    input,
+                    Reason::InvalidInput {
+                        expected: "even number",
+                    },
+                ))
+            }
+        });
+        assert_eq!(even.parse("42,"), Ok((",", 21)));
+        assert!(even.parse("7").unwrap_err().is_failure());
+        assert!(!even.parse("x").unwrap_err().is_failure());
+    }
+
+    #[test]
+    fn csv_dialect_presets() {
+        let (_, records) = csv::parse_with("a,b\"c\r\n", &csv::Dialect::excel()).unwrap();
+        assert_eq!(records, vec![vec!["a", "b\"c"]]);
+
+        let mysql = csv::Dialect::mysql();
+        let (_, records) = csv::parse_with("1\t\\N\tx,y\n", &mysql).unwrap();
+        assert_eq!(records, vec![vec!["1", "\\N", "x,y"]]);
+        assert!(mysql.is_null(&records[0][1]));
+        assert!(!csv::Dialect::default().is_null(""));
+
+        let mut writer = csv::Writer::with_options(Vec::new(), (&csv::Dialect::unix()).into());
+        writer.write_record(["a", "b"]).unwrap();
+        assert_eq!(writer.into_inner(), b"a,b\n");
+
+        let schema = csv::Schema::new()
+            .column("n", csv::ColumnType::Integer)
+            .dialect(csv::Dialect::postgres());
+        let typed = csv::parse_typed("n,name\n1,\n,x\n", &schema).unwrap();
+        assert_eq!(typed.rows[0][1], csv::Value::Null);
+        assert_eq!(typed.rows[1][0], csv::Value::Null);
+    }
+
+    #[test]
+    fn log_logfmt() {
+        let input = "level=info msg=\"user \\\"bob\\\" logged in\" took=12ms cached\n\n  at=end\n";
+        let records = log::logfmt_records(input)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["msg"], "user \"bob\" logged in");
+        assert_eq!(records[0]["took"], "12ms");
+        assert_eq!(records[0]["cached"], "");
+        assert_eq!(records[1]["at"], "end");
+
+        let mut records = log::logfmt_records("a=1\nb=\"open\nc=3\n");
+        assert!(records.next().unwrap().is_ok());
+        assert!(records.next().unwrap().unwrap_err().is_failure());
+        assert!(records.next().is_none());
+    }
+
+    #[test]
+    fn log_common_log_format() {
+        let input =
+            "127.0.0.1 - frank [10/Oct/2000:13:55:36 -0700] \"GET /a.gif HTTP/1.0\" 200 2326\n\
+                     ::1 - - [01/Jan/2024:00:00:00 +0000] \"HEAD / HTTP/1.1\" 304 -";
+        let (rest, entry) = log::common_log_line(input).unwrap();
+        assert_eq!(entry.host, "127.0.0.1");
+        assert_eq!(entry.ident, None);
+        assert_eq!(entry.user, Some("frank"));
+        assert_eq!(entry.timestamp.month, 10);
+        assert_eq!(entry.timestamp.offset_minutes, -420);
+        assert_eq!(entry.request, "GET /a.gif HTTP/1.0");
+        assert_eq!((entry.status, entry.bytes), (200, Some(2326)));
+        let (rest, entry) = log::common_log_line(rest).unwrap();
+        assert_eq!(rest, "");
+        assert_eq!((entry.status, entry.bytes), (304, None));
+        assert_eq!(
+            log::ip_addr(entry.host).unwrap().1,
+            "::1".parse::<std::net::IpAddr>().unwrap()
+        );
+
+        let (_, timestamp) = log::rfc3339("2024-03-01T12:30:05.25+01:30 rest").unwrap();
+        assert_eq!(
+            timestamp,
+            log::Timestamp {
+                year: 2024,
+                month: 3,
+                day: 1,
+                hour: 12,
+                minute: 30,
+                second: 5,
+                nanosecond: 250_000_000,
+                offset_minutes: 90,
+            }
+        );
+        assert!(log::rfc3339("2024-13-01T00:00:00Z").is_err());
+        assert!(log::rfc3339("2024-00-01T00:00:00Z").is_err());
+        assert!(log::rfc3339("2024-01-00T00:00:00Z").is_err());
+        assert_eq!(
+            log::clf_timestamp("01/Jan/2000:00:00:00 +0000")
+                .unwrap()
+                .1
+                .day,
+            1
+        );
+        assert!(log::clf_timestamp("00/Jan/2000:00:00:00 +0000").is_err());
+        assert!(log::ip_addr("300.1.1.1").is_err());
+    }
+
+    #[test]
+    fn urlencoded_parse() {
+        assert_eq!(
+            urlencoded::parse("a=1&b=hello%20world&&c=x+y%2By&flag&=z"),
+            Ok((
+                "",
+                vec![
+                    ("a".to_string(), "1".to_string()),
+                    ("b".to_string(), "hello world".to_string()),
+                    ("c".to_string(), "x y+y".to_string()),
+                    ("flag".to_string(), String::new()),
+                    (String::new(), "z".to_string()),
+                ]
+            ))
+        );
+        assert_eq!(urlencoded::parse(""), Ok(("", vec![])));
+        assert_eq!(
+            urlencoded::parse("a=%E2%98%83&b=%zz"),
+            Err(Error::failure(
+                "%zz",
+                Reason::InvalidInput {
+                    expected: "percent-encoded UTF-8 text"
+                }
+            ))
+        );
+        assert_eq!(chars::percent_byte("%2Fx"), Ok(("x", b'/')));
+        assert_eq!(
+            chars::percent_decode("a+b%21", false),
+            Some("a+b!".to_string())
+        );
+        assert_eq!(chars::percent_decode("%FF", false), None);
+    }
+
+    #[test]
+    fn token_parsers() {
+        assert_eq!(chars::identifier("_foo1 bar"), Ok((" bar", "_foo1")));
+        assert!(chars::identifier("1foo").is_err());
+
+        let (rest, version) = chars::semver("1.20.3-rc.1+build.05 x").unwrap();
+        assert_eq!(rest, " x");
+        assert_eq!(
+            version,
+            chars::Version {
+                major: 1,
+                minor: 20,
+                patch: 3,
+                pre_release: Some("rc.1"),
+                build: Some("build.05"),
+            }
+        );
+        assert_eq!(chars::semver("0.1.0").unwrap().1.pre_release, None);
+        assert!(chars::semver("01.1.0").is_err());
+        assert_eq!(chars::semver("1.0.0-01").unwrap().0, "-01");
+
+        let (_, uuid) = chars::uuid("123e4567-e89b-12d3-a456-426614174000").unwrap();
+        assert_eq!(uuid[..4], [0x12, 0x3e, 0x45, 0x67]);
+        assert_eq!(uuid[15], 0x00);
+        assert!(chars::uuid("123e4567-e89b-12d3-a456-42661417400").is_err());
+
+        assert_eq!(
+            chars::ipv4("192.168.0.1:80"),
+            Ok((":80", std::net::Ipv4Addr::new(192, 168, 0, 1)))
+        );
+        assert!(chars::ipv4("256.1.1.1").is_err());
+        assert!(chars::ipv4("1.2.3.4567").is_err());
+        assert_eq!(
+            chars::ipv6("::ffff:192.0.2.1 x").unwrap().1,
+            "::ffff:192.0.2.1".parse::<std::net::Ipv6Addr>().unwrap()
+        );
+        assert!(chars::ipv6("1.2.3.4").is_err());
+    }
+
+    #[test]
+    fn expr_precedence() {
+        let eval = expr::arithmetic;
+        assert_eq!(eval("1 + 2 * 3"), Ok(("", 7.0)));
+        assert_eq!(eval(" (1 + 2) * 3 ;"), Ok((";", 9.0)));
+        assert_eq!(eval("8 - 3 - 2"), Ok(("", 3.0)));
+        assert_eq!(eval("2 ^ 3 ^ 2"), Ok(("", 512.0)));
+        assert_eq!(eval("-2 ^ 2"), Ok(("", -4.0)));
+        assert_eq!(eval("--1 * 4 / 2"), Ok(("", 2.0)));
+        assert_eq!(eval("1 + 2 )"), Ok((")", 3.0)));
+        assert!(eval("1 +").is_err());
+        assert!(eval("(1 + 2").is_err());
+        let nested = |depth| format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+        assert_eq!(eval(&nested(128)), Ok(("", 1.0)));
+        assert_eq!(
+            eval(&nested(200_000)).unwrap_err().code,
+            ErrorCode::DepthExceeded
+        );
+
+        #[derive(Debug, PartialEq)]
+        enum Ast {
+            Var(char),
+            Not(Box<Ast>),
+            And(Box<Ast>, Box<Ast>),
+            Or(Box<Ast>, Box<Ast>),
+        }
+        let var = satisfy(|ch| ch.is_ascii_lowercase()).map(Ast::Var);
+        let not = char('!').map(|_| expr::Prefix {
+            precedence: 3,
+            apply: |ast| Ast::Not(Box::new(ast)),
+        });
+        let infix = |input| {
+            let and = char('&').map(|_| expr::Infix {
+                precedence: 2,
+                assoc: expr::Assoc::Left,
+                apply: |a, b| Ast::And(Box::new(a), Box::new(b)),
+            });
+            let or = char('|').map(|_| expr::Infix {
+                precedence: 1,
+                assoc: expr::Assoc::Left,
+                apply: |a, b| Ast::Or(Box::new(a), Box::new(b)),
+            });
+            and.fallback_on(or).parse(input)
+        };
+        let (_, ast) = expr::precedence(var, not, infix).parse("a|!b&c").unwrap();
+        assert_eq!(
+            ast,
+            Ast::Or(
+                Box::new(Ast::Var('a')),
+                Box::new(Ast::And(
+                    Box::new(Ast::Not(Box::new(Ast::Var('b')))),
+                    Box::new(Ast::Var('c'))
+                ))
+            )
+        );
+    }
+
+    #[test]
+    fn left_recursion_detection() {
+        // sum = sum '+' digit | digit
+        fn sum(input: &str) -> PResult<&str, &str> {
+            left_recursion_guard(
+                "sum",
+                recognize(separated_pair(sum, char('+'), chars::digit1)).fallback_on(chars::digit1),
+            )
+            .parse(input)
+        }
+        assert_eq!(
+            sum("1+2"),
+            Err(Error::new("1+2", ErrorCode::LeftRecursion("sum")))
+        );
+
+        // sum = digit '+' sum | digit
+        fn right_sum(input: &str) -> PResult<&str, &str> {
+            left_recursion_guard(
+                "sum",
+                recognize(separated_pair(chars::digit1, char('+'), right_sum))
+                    .fallback_on(chars::digit1),
+            )
+            .parse(input)
+        }
+        assert_eq!(right_sum("1+2+3;"), Ok((";", "1+2+3")));
+        assert_eq!(right_sum("1+"), Ok(("+", "1")));
+    }
+
+    #[test]
+    fn fallback_after_failure() {
+        let failure = || {
+            fail(ErrorCode::Failure(Reason::InvalidInput {
+                expected: "digit",
+            }))
+        };
+        let mut recovered = failure().fallback_on(char('x'));
+        assert_eq!(recovered.parse("xy"), Ok(("y", 'x')));
+        let err = recovered.parse("y").unwrap_err();
+        assert!(err.is_failure());
+
+        let mut guarded = max_depth(0, char('x')).fallback_on(char('x'));
+        assert_eq!(
+            guarded.parse("x"),
+            Err(Error::new("x", ErrorCode::DepthExceeded))
+        );
+    }
+
+    #[test]
+    fn memoize_combinator() {
+        use std::cell::Cell;
+
+        let other = String::from("hello!");
+        let calls = Cell::new(0);
+        let word = memo::memoize(8, |input| {
+            calls.set(calls.get() + 1);
+            take_while1(|ch: char| ch.is_ascii_alphabetic())(input)
+        });
+        let mut statement = pair(word.clone(), char(';'))
+            .fallback_on(pair(word.clone(), char('.')))
+            .fallback_on(pair(word.clone(), char('!')));
+        assert_eq!(statement.parse("hello!"), Ok(("", ("hello", '!'))));
+        assert_eq!(calls.get(), 1);
+
+        assert_eq!(statement.parse(&other), Ok(("", ("hello", '!'))));
+        assert_eq!(calls.get(), 2);
+
+        word.clear();
+        assert_eq!(
+            statement.parse("1"),
+            Err(Error::new("1", ErrorCode::Predicate))
+        );
+        assert_eq!(calls.get(), 3);
+
+        // Errors of the depth guards depend on the caller, not on the input
+        let mut guarded = memo::memoize(16, max_depth(1, char('a')));
+        assert_eq!(
+            max_depth(1, |input| guarded.parse(input)).parse("a"),
+            Err(Error::new("a", ErrorCode::DepthExceeded))
+        );
+        assert_eq!(guarded.parse("a"), Ok(("", 'a')));
+    }
+
+    #[test]
+    fn fallback_reports_furthest_error() {
+        let keyword = |word| {
+            tag(word)
+                .flat_map(|next_input, word| char(' ')(next_input).map(|(rest, _)| (rest, word)))
+        };
+        let mut declaration = keyword("let").fallback_on(keyword("var"));
+        assert_eq!(declaration.parse("var x"), Ok(("x", "var")));
+        assert_eq!(
+            declaration.parse("let!"),
+            Err(Error::new("!", ErrorCode::Char(' ')))
+        );
+        assert_eq!(
+            declaration.parse("var!"),
+            Err(Error::new("!", ErrorCode::Char(' ')))
+        );
+        assert_eq!(
+            declaration.parse("const"),
+            Err(Error::new(
+                "const",
+                ErrorCode::Expected(vec![ErrorCode::Tag("let"), ErrorCode::Tag("var")])
+            ))
+        );
+    }
+
+    #[test]
+    fn fallback_keeps_positions_and_failures() {
+        let mut sequence = pair(char('a'), char('b')).fallback_on(pair(char('x'), char('y')));
+        assert_eq!(
+            sequence.parse("ac"),
+            Err(Error::new("c", ErrorCode::Char('b')))
+        );
+        assert_eq!(sequence.parse("xy"), Ok(("", ('x', 'y'))));
+
+        let mut committed = pair(char('1'), char('2')).fallback_on(fail(ErrorCode::Failure(
+            Reason::InvalidInput { expected: "digits" },
+        )));
+        let err = committed.parse("1x").unwrap_err();
+        assert!(err.is_failure());
+        assert_eq!(err.input, "1x");
+    }
+
+    #[test]
+    fn fallback_merges_expected_errors() {
+        let mut separator = char(',')
+            .map(|_| "")
+            .fallback_on(char('"').map(|_| ""))
+            .fallback_on(line_break);
+        let err = separator.parse("x").unwrap_err();
+        assert_eq!(
+            err.code,
+            ErrorCode::Expected(vec![
+                ErrorCode::Char(','),
+                ErrorCode::Char('"'),
+                ErrorCode::LineBreak
+            ])
+        );
+        assert_eq!(
+            err.to_owned_error(1).to_string(),
+            "parse error at byte 0 near \"x\": expected ',' or '\"' or line break"
+        );
+        assert_eq!(
+            ErrorCode::Char('a').merge(ErrorCode::Char('a')),
+            ErrorCode::Char('a')
+        );
+    }
+
+    #[test]
+    fn zero_or_more_stops_without_progress() {
+        let mut combi = zero_or_more(take_while(|ch| ch == 'a'));
+        assert_eq!(combi.parse("aab"), Ok(("b", vec!["aa"])));
+        assert_eq!(combi.parse("b"), Ok(("b", Vec::new())));
+        let mut combi = fold_many(char('x').with_default('-'), || 0, |count, _| count + 1);
+        assert_eq!(combi.parse("xxy"), Ok(("y", 2)));
+    }
+
+    #[test]
+    fn pure_parser_shared_between_threads() {
+        let short = |len: &usize| *len < 4;
+        let parser = take_while1(|ch| ch.is_ascii_digit())
+            .map(str::len)
+            .fallback_on(char('-').map(char::len_utf8))
+            .iff(short);
+        let inputs = ["12a", "-", "1234", "x"];
+        let results: Vec<_> = std::thread::scope(|scope| {
+            let parser = &parser;
+            let handles: Vec<_> = inputs
+                .iter()
+                .map(|input| scope.spawn(move || parser.parse_ref(*input)))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect()
+        });
+        assert_eq!(results[0], Ok(("a", 2)));
+        assert_eq!(results[1], Ok(("", 1)));
+        assert_eq!(results[2], Err(Error::new("1234", ErrorCode::Predicate)));
+        assert!(results[3].is_err());
+    }
+
+    #[test]
+    fn and_then_map_once_moves_captured_values() {
+        let mut combi = take_while1(|ch| ch.is_ascii_alphabetic())
+            .map(|name: &str| vec![name.to_string()])
+            .and_then_map_once(|mut names: Vec<String>| {
+                move |input| {
+                    let (next_input, name) =
+                        right_from_pair(char(','), take_while1(|ch| ch != ',')).parse(input)?;
+                    names.push(name.to_string());
+                    Ok((next_input, names))
+                }
+            });
+        assert_eq!(
+            combi.parse("a,b,c"),
+            Ok((",c", vec!["a".to_string(), "b".to_string()]))
+        );
+        assert!(combi.parse("a").is_err());
+
+        let once = char('x').map(|_| String::from("owned"));
+        assert_eq!(once.parse_once("xy"), Ok(("y", "owned".to_string())));
+    }
+
+    #[test]
+    fn named_parser() {
+        let field = || take_while(|ch| ch != ',').named("field");
+        assert_eq!(field().name(), "field");
+        assert_eq!(format!("{:?}", field()), "Named(\"field\")");
+
+        let (result, trace) =
+            trace::capture(|| separated_pair(field(), char(','), field()).parse("a,b"));
+        assert_eq!(result, Ok(("", ("a", "b"))));
+        let names: Vec<_> = trace.roots.iter().map(|node| node.name).collect();
+        assert_eq!(names, vec!["field", "field"]);
+    }
+
+    #[test]
+    fn csv_unterminated_quote() {
+        let input = format!("id,name\n1,\"{}", "x,".repeat(100_000));
+        let err = csv::parse_string(&input).unwrap_err();
+        assert_eq!(
+            err.code,
+            ErrorCode::Failure(Reason::InvalidInput {
+                expected: "closing quote"
+            })
+        );
+        assert_eq!(csv::field(&input[10..]).unwrap_err().input, &input[10..]);
+        assert!(csv::raw_record(&input[10..]).unwrap_err().is_failure());
+
+        let excel = csv::Dialect::excel();
+        assert_eq!(
+            csv::field_with(&excel).parse("\"ab,c"),
+            Ok((",c", "\"ab".to_string()))
+        );
+
+        let limited = csv::Dialect {
+            max_quoted_len: Some(4),
+            ..Default::default()
+        };
+        let field = csv::field_with(&limited);
+        assert_eq!(field("\"abcd\",x"), Ok((",x", "abcd".to_string())));
+        assert_eq!(field("\"a\"\"b\""), Ok(("", "a\"b".to_string())));
+        assert!(field("\"abcde\"").unwrap_err().is_failure());
+        assert!(field("\"abc\"\"\"").unwrap_err().is_failure());
+        let err = field(&input[10..]).unwrap_err();
+        assert_eq!(
+            err.code,
+            ErrorCode::Failure(Reason::InvalidInput {
+                expected: "quoted field within the size limit"
+            })
+        );
+    }
+
+    #[test]
+    fn csv_field_quoted_and_unquoted() {
+        let dialect = csv::Dialect::default();
+        let quoted = csv::field_quoted(&dialect);
+        assert_eq!(
+            quoted("\"a,\"\"b\"\"\",c"),
+            Ok((",c", "a,\"b\"".to_string()))
+        );
+        assert_eq!(quoted("a,b"), Err(Error::new("a,b", ErrorCode::Char('"'))));
+        let excel = csv::Dialect::excel();
+        assert!(csv::field_quoted(&excel)("\"ab").unwrap_err().is_failure());
+
+        let unquoted = csv::field_unquoted(&dialect);
+        assert_eq!(unquoted("a b,c"), Ok((",c", "a b".to_string())));
+        assert_eq!(unquoted("\"a\""), Ok(("\"a\"", String::new())));
+    }
+
+    #[test]
+    fn csv_field_as_written() {
+        let dialect = csv::Dialect::default();
+        let (rest, fields) =
+            csv::record_as_written(&dialect)("1,\"2\",\"a \"\"b\"\"\"\nx").unwrap();
+        assert_eq!(rest, "\nx");
+        assert_eq!(
+            fields,
+            vec![
+                csv::Field::Raw("1"),
+                csv::Field::Quoted("2".to_string()),
+                csv::Field::Quoted("a \"b\"".to_string())
+            ]
+        );
+        assert!(!fields[0].is_quoted() && fields[1].is_quoted());
+        assert_eq!(fields[2].as_str(), "a \"b\"");
+        assert_eq!(fields[0].clone().into_string(), "1");
+        assert_eq!(
+            csv::record_as_written(&dialect)(""),
+            Err(Error::new("", ErrorCode::NoInput))
+        );
+    }
+
+    #[test]
+    fn csv_parse_multi() {
+        let input = "id,name\n1,a\n2,\"b\n\nc\"\n\n\r\nkey,value,unit\nx,1,m\n\nlonely\n";
+        let (rest, documents) = csv::parse_multi(input).unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(documents.len(), 3);
+        assert_eq!(documents[0].header, vec!["id", "name"]);
+        assert_eq!(documents[0].records[1], vec!["2", "b\n\nc"]);
+        assert_eq!(documents[1].header.len(), 3);
+        assert_eq!(documents[1].records, vec![vec!["x", "1", "m"]]);
+        assert!(documents[2].records.is_empty());
+
+        assert!(csv::parse_multi("a,b\n1\n\nc\n").unwrap_err().is_failure());
+        assert_eq!(
+            csv::parse_multi("\n\n"),
+            Err(Error::new("\n\n", ErrorCode::NoInput))
+        );
+    }
+
+    #[test]
+    fn csv_transpose() {
+        let input = "time,0,1,2\nvoltage,1.5,1.7,1.6\n";
+        let (_, records) = csv::parse_transposed(input, &csv::Dialect::default()).unwrap();
+        assert_eq!(
+            records,
+            vec![
+                vec!["time", "voltage"],
+                vec!["0", "1.5"],
+                vec!["1", "1.7"],
+                vec!["2", "1.6"]
+            ]
+        );
+        assert_eq!(csv::transpose(csv::transpose(records.clone())), records);
+
+        let ragged = vec![
+            vec!["a".to_string()],
+            vec!["b".to_string(), "c".to_string()],
+        ];
+        assert_eq!(csv::transpose(ragged), vec![vec!["a", "b"], vec!["", "c"]]);
+        assert!(csv::transpose(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn csv_group_by() {
+        let input = "city,sales\nParis,3\nRome,5\nParis,4\n";
+        let (_, groups) = csv::group_by(input, "city").unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(
+            groups["Paris"],
+            vec![vec!["Paris", "3"], vec!["Paris", "4"]]
+        );
+        assert_eq!(groups["Rome"], vec![vec!["Rome", "5"]]);
+
+        let (_, totals) = csv::group_fold(
+            input,
+            0,
+            || 0,
+            |total, record| total + record[1].parse::<u32>().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(totals["Paris"], 7);
+        assert_eq!(totals["Rome"], 5);
+
+        assert!(csv::group_by(input, "country").unwrap_err().is_failure());
+        assert_eq!(
+            csv::group_by("", 0),
+            Err(Error::new("", ErrorCode::NoInput))
+        );
+    }
+
+    #[test]
+    fn csv_join() {
+        let people = "id,name,city\n1,Ann,Oslo\n2,Bob,Rome\n3,Cid,Nice\n";
+        let orders = "person,item,city\n1,pen,Bergen\n1,ink,Oslo\n4,cup,Lyon\n";
+        let on = [csv::Column::Name("id".to_string())];
+        let orders_by_id = orders.replacen("person", "id", 1);
+        let (_, joined) =
+            csv::join(people, &orders_by_id, on.clone(), csv::JoinKind::Inner).unwrap();
+        assert_eq!(
+            joined,
+            vec![
+                vec!["id", "name", "city_left", "item", "city_right"],
+                vec!["1", "Ann", "Oslo", "pen", "Bergen"],
+                vec!["1", "Ann", "Oslo", "ink", "Oslo"],
+            ]
+        );
+
+        let (_, joined) =
+            csv::join(people, &orders_by_id, on.clone(), csv::JoinKind::Left).unwrap();
+        assert_eq!(joined.len(), 5);
+        assert_eq!(joined[3], vec!["2", "Bob", "Rome", "", ""]);
+
+        let (_, joined) = csv::join_with(
+            people,
+            &orders_by_id,
+            on,
+            csv::JoinKind::Outer,
+            &csv::Collision::KeepRight,
+        )
+        .unwrap();
+        assert_eq!(joined[0], vec!["id", "name", "item", "city"]);
+        assert_eq!(joined.last().unwrap(), &vec!["4", "", "cup", "Lyon"]);
+        assert_eq!(joined.len(), 6);
+
+        assert!(csv::join(people, orders, ["id"], csv::JoinKind::Inner)
+            .unwrap_err()
+            .is_failure());
+    }
+
+    #[test]
+    fn csv_reader_resume() {
+        let input = "a,b\n1,\"x\ny\"\n2,z\n";
+        let mut reader = csv::Reader::new(std::io::Cursor::new(input));
+        assert_eq!(reader.state(), csv::ParserState::default());
+        reader.read_record().unwrap();
+        reader.read_record().unwrap();
+        let state = reader.state();
+        assert_eq!((state.offset, state.fields), (12, Some(2)));
+
+        let mut resumed = csv::Reader::resume(std::io::Cursor::new(input), &state).unwrap();
+        assert_eq!(
+            resumed.read_record().unwrap(),
+            Some(vec!["2".to_string(), "z".to_string()])
+        );
+        assert_eq!(resumed.bytes_consumed(), input.len());
+        assert_eq!(resumed.read_record().unwrap(), None);
+
+        let mut resumed =
+            csv::Reader::resume(std::io::Cursor::new("a,b\nc,d\ne,f\n3\n"), &state).unwrap();
+        assert!(resumed.read_record().is_err());
+
+        // Lines read before an I/O error are kept as the partially read record
+        struct Failing<'a>(&'a [u8], bool);
+        impl std::io::Read for Failing<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                if self.0.is_empty() && !std::mem::replace(&mut self.1, true) {
+                    return Err(std::io::Error::other("connection lost"));
+                }
+                let len = self.0.len().min(buf.len());
+                buf[..len].copy_from_slice(&self.0[..len]);
+                self.0 = &self.0[len..];
+                Ok(len)
+            }
+        }
+        let input = "a\tb\n1\\\n2\t3\n";
+        let source = Failing(&input.as_bytes()[..7], false);
+        let mut reader = csv::Reader::new(std::io::BufReader::with_capacity(4, source))
+            .with_dialect(csv::Dialect::mysql());
+        reader.read_record().unwrap();
+        assert!(reader.read_record().is_err());
+        let state = reader.state();
+        assert_eq!((state.offset, state.partial.as_str()), (4, "1\\\n"));
+        assert_eq!(state.dialect, csv::Dialect::mysql());
+        let mut resumed = csv::Reader::resume(std::io::Cursor::new(input), &state).unwrap();
+        assert_eq!(
+            resumed.read_record().unwrap(),
+            Some(vec!["1\n2".to_string(), "3".to_string()])
+        );
+        assert_eq!(resumed.read_record().unwrap(), None);
+    }
+
+    #[test]
+    fn csv_reader_stray_quotes() {
+        let read_all = |input: &str, dialect: csv::Dialect| {
+            csv::Reader::new(input.as_bytes())
+                .with_dialect(dialect)
+                .map(Result::unwrap)
+                .collect::<Vec<_>>()
+        };
+        for input in [
+            "a,b\"c\nd,e\n",
+            "a,\"b\nc,d\n",
+            "a,\"b\"\"c\"\n\"d\ne\",\"f\"\n",
+        ] {
+            let expected = csv::parse_with(input, &csv::Dialect::excel()).unwrap().1;
+            assert_eq!(read_all(input, csv::Dialect::excel()), expected);
+        }
+
+        // Lines read before an I/O error, including an opening quote
+        struct Failing<'a>(&'a [u8], usize);
+        impl std::io::Read for Failing<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                if self.1 == 0 {
+                    self.1 = usize::MAX;
+                    return Err(std::io::Error::other("connection lost"));
+                }
+                let len = self.0.len().min(buf.len()).min(self.1);
+                buf[..len].copy_from_slice(&self.0[..len]);
+                self.0 = &self.0[len..];
+                self.1 -= len;
+                Ok(len)
+            }
+        }
+        let source = Failing(b"a,b\n\"1\n2\",3\n", 6);
+        let mut reader = csv::Reader::new(std::io::BufReader::with_capacity(4, source));
+        assert_eq!(
+            reader.read_record().unwrap(),
+            Some(vec!["a".into(), "b".into()])
+        );
+        assert!(reader.read_record().is_err());
+        assert_eq!(
+            reader.read_record().unwrap(),
+            Some(vec!["1\n2".to_string(), "3".to_string()])
+        );
+        assert_eq!(reader.read_record().unwrap(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "gzip")]
+    fn csv_reader_resume_compressed() {
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(b"a,b\n1,2\n3,4\n").unwrap();
+        let compressed = encoder.finish().unwrap();
+        let mut reader = csv::Reader::decompress(std::io::Cursor::new(compressed.clone())).unwrap();
+        reader.read_record().unwrap();
+        let state = reader.state();
+
+        assert_eq!(
+            csv::Reader::resume(std::io::Cursor::new(compressed.clone()), &state)
+                .err()
+                .map(|err| err.kind()),
+            Some(std::io::ErrorKind::InvalidInput)
+        );
+        let resumed =
+            csv::Reader::resume_decompressed(std::io::Cursor::new(compressed), &state).unwrap();
+        let rest = resumed.collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(rest, vec![vec!["1", "2"], vec!["3", "4"]]);
+    }
+
+    #[test]
+    fn csv_channel_pipeline() {
+        use std::sync::mpsc::sync_channel;
+
+        let (text_sender, text_receiver) = sync_channel::<String>(1);
+        let (record_sender, record_receiver) = sync_channel(1);
+        let producer = std::thread::spawn(move || {
+            for chunk in ["id,no", "te\n1,\"a\n", "b\"\n2,", "c"] {
+                text_sender.send(chunk.to_string()).unwrap();
+            }
+        });
+        let parser = std::thread::spawn(move || {
+            let mut writer = csv::Writer::new(csv::channel_writer(record_sender));
+            for record in csv::channel_reader(text_receiver) {
+                writer.write_record(record.unwrap()).unwrap();
+            }
+            writer.into_inner().finish()
+        });
+        let records: Vec<_> = record_receiver.iter().collect();
+        producer.join().unwrap();
+        parser.join().unwrap().unwrap();
+        assert_eq!(
+            records,
+            vec![vec!["id", "note"], vec!["1", "a\nb"], vec!["2", "c"]]
+        );
+    }
+
+    #[test]
+    fn csv_channel_writer_batches() {
+        use std::io::Write;
+        use std::sync::mpsc::sync_channel;
+
+        let (sender, receiver) = sync_channel(8);
+        let mut writer = csv::channel_writer(sender);
+        writer.write_all(b"a,b\n1,\"x\n").unwrap();
+        writer.write_all(b"y\"\n2,z\n3,").unwrap();
+        writer.write_all(b"w").unwrap();
+        writer.finish().unwrap();
+        let records: Vec<_> = receiver.iter().collect();
+        assert_eq!(
+            records,
+            vec![
+                vec!["a", "b"],
+                vec!["1", "x\ny"],
+                vec!["2", "z"],
+                vec!["3", "w"]
+            ]
+        );
+
+        let (sender, _) = sync_channel(8);
+        let mut writer = csv::channel_writer(sender);
+        let err = writer.write(b"a,b\n1\n").unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::BrokenPipe);
+    }
+
+    #[test]
+    fn csv_channel_writer_errors() {
+        use std::io::{ErrorKind, Write};
+        use std::sync::mpsc::sync_channel;
+
+        let (sender, receiver) = sync_channel(8);
+        let mut writer = csv::channel_writer_with(sender, &csv::Dialect::with_delimiter(';'));
+        assert_eq!(writer.write(b"a;b\n1\n2;3\n").unwrap(), 6);
+        assert_eq!(
+            writer.write(b"2;3\n").unwrap_err().kind(),
+            ErrorKind::InvalidData
+        );
+        assert_eq!(writer.write(b"2;3\n4;").unwrap(), 6);
+        assert!(writer.write_all(b"x\"y\n5").is_err());
+        writer.write_all(b"5;6").unwrap();
+        writer.flush().unwrap();
+        writer.finish().unwrap();
+        let records: Vec<_> = receiver.iter().collect();
+        assert_eq!(
+            records,
+            vec![vec!["a", "b"], vec!["2", "3"], vec!["5", "6"]]
+        );
+
+        // Bytes after the last record sent are not accepted
+        let (sender, receiver) = sync_channel(1);
+        let mut writer = csv::channel_writer(sender);
+        assert_eq!(writer.write(b"a,b\n").unwrap(), 4);
+        drop(receiver);
+        assert_eq!(writer.write(b"c,").unwrap(), 2);
+        assert_eq!(
+            writer.write(b"d\n").unwrap_err().kind(),
+            ErrorKind::BrokenPipe
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_round_trip() {
+        let dialect: csv::Dialect =
+            serde_json::from_str(r#"{"delimiter": ";", "null_token": "NA"}"#).unwrap();
+        assert_eq!(dialect.delimiter, ';');
+        assert_eq!(dialect.stray_quotes, csv::StrayQuotes::Terminate);
+        assert!(dialect.is_null("NA"));
+        let json = serde_json::to_string(&csv::Dialect::mysql()).unwrap();
+        assert_eq!(
+            serde_json::from_str::<csv::Dialect>(&json).unwrap(),
+            csv::Dialect::mysql()
+        );
+        let state = csv::ParserState {
+            offset: 12,
+            fields: Some(2),
+            dialect: csv::Dialect::mysql(),
+            partial: "1\\\n".to_string(),
+        };
+        let json = serde_json::to_string(&state).unwrap();
+        assert_eq!(
+            serde_json::from_str::<csv::ParserState>(&json).unwrap(),
+            state
+        );
+
+        let schema = csv::Schema::new()
+            .column("age", csv::ColumnType::Integer)
+            .dialect(dialect);
+        let json = serde_json::to_string(&schema).unwrap();
+        let schema: csv::Schema = serde_json::from_str(&json).unwrap();
+        let typed = csv::parse_typed("name;age\nann;NA\n", &schema).unwrap();
+        assert_eq!(typed.rows[0][1], csv::Value::Null);
+
+        let err = csv::parse_string("a,b\n1\n")
+            .unwrap_err()
+            .to_owned_error("a,b\n1\n".len());
+        let json = serde_json::to_value(&err).unwrap();
+        assert_eq!(json["offset"], 4);
+        assert_eq!(
+            json["code"]["Failure"]["InvalidInput"]["expected"],
+            "more fields in this record"
+        );
+    }
+
+    #[test]
+    fn error_to_json() {
+        let input = "id,name\n1,\"Ann\"x\n";
+        let err = char('"')(&input[15..]).unwrap_err();
+        assert_eq!(err.line_column(input), (2, 8));
+        assert_eq!(
+            err.to_json(input),
+            "{\"code\":\"char\",\"message\":\"expected '\\\"'\",\"offset\":15,\"line\":2,\
+             \"column\":8,\"excerpt\":\"x\\n\"}"
+        );
+        let json: serde_json::Value = serde_json::from_str(&err.to_json(input)).unwrap();
+        assert_eq!(json["line"], 2);
+        assert_eq!(Error::new("", ErrorCode::NoInput).line_column(""), (1, 1));
+    }
+
+    #[test]
+    fn csv_parse_report_stray_quotes() {
+        let input = "a,b\nc,x\"y\n\"d,e\n";
+        let (_, (records, report)) = csv::parse_reported(input, &csv::Dialect::excel()).unwrap();
+        assert_eq!(records[1], vec!["c", "x\"y"]);
+        assert_eq!(records[2], vec!["\"d", "e"]);
+        assert_eq!(report.quoted_fields, 0);
+        assert_eq!(
+            report.warnings,
+            vec![csv::ParseWarning::StrayQuote { record: 1 }]
+        );
+        assert!(csv::parse_reported(input, &csv::Dialect::default()).is_err());
+    }
+
+    #[test]
+    fn csv_parse_report_trimmed_whitespace() {
+        let dialect = csv::Dialect {
+            whitespace_delimited: true,
+            ..Default::default()
+        };
+        let (_, (records, report)) = csv::parse_reported("a  b\nc d \n", &dialect).unwrap();
+        assert_eq!(records[1], vec!["c", "d"]);
+        assert_eq!(
+            report.warnings,
+            vec![csv::ParseWarning::TrimmedWhitespace { record: 1 }]
+        );
+        let (_, (_, report)) = csv::parse_reported("a b\n\tc d", &dialect).unwrap();
+        assert_eq!(
+            report.warnings,
+            vec![csv::ParseWarning::TrimmedWhitespace { record: 1 }]
+        );
+        let (_, (_, report)) = csv::parse_reported("a  b\nc\td", &dialect).unwrap();
+        assert!(report.warnings.is_empty());
+    }
+
+    #[test]
+    fn debug_composed_parsers() {
+        let number = digit1.named("number");
+        let parser = number
+            .clone()
+            .map(str::len)
+            .iff(|len| *len < 4)
+            .fallback_on(char('x').named("x").map(|_| 0));
+        assert_eq!(
+            format!("{:?}", parser),
+            "Fallback { primary: Predicate { parser: Map { parser: Named(\"number\"), .. }, \
+             assume_invalid: false, .. }, fallback: Map { parser: Named(\"x\"), .. } }"
+        );
+        let parser = number.and_then_map(|digits: &str| tag(digits).named("repeat"));
+        assert_eq!(
+            format!("{:?}", parser),
+            "AndThenMap { first: Named(\"number\"), .. }"
+        );
+        let parser = pair(digit1, char(','))
+            .named("field")
+            .map(|(digits, _)| digits);
+        assert_eq!(
+            format!("{:?}", parser),
+            "Map { parser: Named(\"field\"), .. }"
+        );
+    }
+
+    #[test]
+    fn error_kind_codes() {
+        let err = csv::field_quoted(&csv::Dialect::default())("\"abc").unwrap_err();
+        let copy = err.clone();
+        assert_eq!(copy, err);
+        assert_eq!(err.code.kind(), ErrorKind::Failure);
+        assert_eq!(ErrorCode::LineBreak.kind().code(), 7);
+        assert_eq!(ErrorCode::Expected(vec![]).kind().code(), 14);
+        assert_eq!(ErrorKind::from_code(3), Some(ErrorKind::Char));
+        assert_eq!(ErrorKind::from_code(0), None);
+        assert_eq!(ErrorCode::Char('a').name(), "char");
+    }
+
+    #[test]
+    fn csv_whitespace_delimited() {
+        let dialect = csv::Dialect::whitespace();
+        let input = "  PID\tUSER   COMMAND  \n    1  root \"/sbin/init splash\"\n  412 ana\tbash\n";
+        let (rem_input, records) = csv::parse_with(input, &dialect).unwrap();
+        assert_eq!(rem_input, "");
+        assert_eq!(
+            records,
+            vec![
+                vec!["PID", "USER", "COMMAND"],
+                vec!["1", "root", "/sbin/init splash"],
+                vec!["412", "ana", "bash"],
+            ]
+        );
+        let (_, (_, report)) = csv::parse_reported(input, &dialect).unwrap();
+        assert_eq!(report.quoted_fields, 1);
+        assert_eq!(
+            report.warnings,
+            vec![csv::ParseWarning::TrimmedWhitespace { record: 0 }]
+        );
+        assert!(csv::parse_with("a b\nc\n", &dialect).is_err());
+        assert_eq!(
+            csv::parse_bytes_with(b" a\t b \nc  d\n", &dialect)
+                .unwrap()
+                .1,
+            vec![
+                vec![b"a".to_vec(), b"b".to_vec()],
+                vec![b"c".to_vec(), b"d".to_vec()]
+            ]
+        );
+        assert_eq!(
+            csv::parse_with("a  b\n", &csv::Dialect::with_delimiter(' '))
+                .unwrap()
+                .1,
+            vec![vec!["a", "", "b"]]
+        );
+    }
+
+    #[test]
+    fn csv_escape_dialect() {
+        let mysql = csv::Dialect::mysql();
+        let input = "1\tsay \"hi\"\\ttab\t\\N\n2\tline\\nbreak \\\\ and \\\ttab\tx\n";
+        let (rem_input, records) = csv::parse_with(input, &mysql).unwrap();
+        assert_eq!(rem_input, "");
+        assert_eq!(
+            records,
+            vec![
+                vec!["1", "say \"hi\"\ttab", "\\N"],
+                vec!["2", "line\nbreak \\ and \ttab", "x"],
+            ]
+        );
+        assert!(mysql.is_null(&records[0][2]));
+        let (_, (_, report)) = csv::parse_reported(input, &mysql).unwrap();
+        assert!(report.warnings.is_empty());
+        assert_eq!(
+            csv::record_as_written(&mysql)("a\tb\\,c").unwrap().1,
+            vec![csv::Field::Raw("a"), csv::Field::Escaped("b,c".to_string())]
+        );
+        let bytes = csv::parse_bytes_with(input.as_bytes(), &mysql).unwrap().1;
+        assert_eq!(bytes[1][1], b"line\nbreak \\ and \ttab".to_vec());
+        assert_eq!(bytes[0][2], b"\\N".to_vec());
+        assert!(csv::parse_with("a\tb\\", &mysql).unwrap_err().is_failure());
+
+        let mut writer = csv::Writer::with_options(Vec::new(), csv::WriterOptions::from(&mysql));
+        writer.write_records(&records).unwrap();
+        let output = String::from_utf8(writer.into_inner()).unwrap();
+        assert_eq!(csv::parse_with(&output, &mysql).unwrap().1, records);
+    }
+
+    #[test]
+    fn csv_record_boundary() {
+        let dialect = csv::Dialect::default();
+        let input = b"id,note\n1,\"two\nlines\"\n2,\"a \"\"b\"\"\nc\"\n3,x\n";
+        assert_eq!(csv::find_record_boundary(input, 0, &dialect), Some(0));
+        assert_eq!(csv::find_record_boundary(input, 1, &dialect), Some(8));
+        assert_eq!(csv::find_record_boundary(input, 9, &dialect), Some(22));
+        assert_eq!(csv::find_record_boundary(input, 23, &dialect), Some(36));
+        assert_eq!(csv::find_record_boundary(input, 37, &dialect), None);
+
+        let mut start = 0;
+        let mut records = Vec::new();
+        while start < input.len() {
+            let end = csv::find_record_boundary(&input[start..], 10, &dialect)
+                .map_or(input.len(), |len| start + len);
+            let chunk = std::str::from_utf8(&input[start..end]).unwrap();
+            records.extend(csv::parse_with(chunk, &dialect).unwrap().1);
+            start = end;
+        }
+        assert_eq!(
+            records,
+            csv::parse_with(std::str::from_utf8(input).unwrap(), &dialect)
+                .unwrap()
+                .1
+        );
+
+        let mysql = csv::Dialect::mysql();
+        assert_eq!(
+            csv::find_record_boundary(b"a\\\nb\tc\nd\n", 1, &mysql),
+            Some(7)
+        );
+        assert_eq!(
+            csv::find_record_boundary(b"a\"b\nc\n", 1, &csv::Dialect::excel()),
+            Some(4)
+        );
+    }
+
+    #[test]
+    fn csv_tail_file() {
+        let path = std::env::temp_dir().join(format!("libparse-tail-{}.csv", std::process::id()));
+        let mut input = String::from("id,note\n");
+        for i in 0..5000 {
+            input.push_str(&format!("{},\"line {}\nwith \"\"quotes\"\"\n\"\n", i, i));
+        }
+        std::fs::write(&path, &input).unwrap();
+        let last = csv::tail_file(&path, 3).unwrap();
+        let all = csv::tail_file(&path, 10_000).unwrap();
+        std::fs::write(&path, "a\tb\\\nc\nd\te\n").unwrap();
+        let escaped = csv::tail_file_with(&path, 1, &csv::Dialect::mysql()).unwrap();
+        // Runs of escapes that start before the last block read from the file
+        let long = "y".repeat(64 * 1024 - 52);
+        let across = |escapes: usize, n: usize| {
+            let input = format!(
+                "head\n{}{}\n{}\n",
+                "x".repeat(10),
+                "\\".repeat(escapes),
+                long
+            );
+            std::fs::write(&path, &input).unwrap();
+            let tail = csv::tail_file_with(&path, n, &csv::Dialect::mysql()).unwrap();
+            assert_eq!(
+                tail,
+                csv::tail_with(&input, n, &csv::Dialect::mysql()).unwrap().1
+            );
+            tail.len()
+        };
+        assert_eq!(across(100, 2), 2);
+        assert_eq!(across(101, 2), 2);
+        std::fs::write(&path, "x,y\na,b\nc\n").unwrap();
+        let err = csv::tail_file(&path, 2).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(input.len() > 64 * 1024);
+        assert_eq!(last, csv::tail(&input, 3).unwrap().1);
+        assert_eq!(last[2], vec!["4999", "line 4999\nwith \"quotes\"\n"]);
+        assert_eq!(all.len(), 5001);
+        assert_eq!(escaped, vec![vec!["d", "e"]]);
+        assert!(matches!(err, csv::FileError::Parse { offset: 8, .. }));
+    }
+
+    #[test]
+    fn csv_typed_constraints() {
+        let schema = csv::Schema::new()
+            .column("id", csv::ColumnType::Integer)
+            .constrain("code", csv::Constraint::MaxLen(3))
+            .constrain("code", csv::Constraint::NonEmpty);
+        let input = "id,code\n1,ABC\n2,ČŽŠ\n3,ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789\n";
+        let err = csv::parse_typed(input, &schema).unwrap_err();
+        assert_eq!(
+            err,
+            csv::TypedError::Cell(csv::CellError {
+                record: 3,
+                column: 1,
+                header: "code".to_string(),
+                excerpt: "ABCDEFGHIJKLMNOPQRSTUVWXYZ012345".to_string(),
+                constraint: csv::Constraint::MaxLen(3),
+            })
+        );
+        assert_eq!(
+            err.to_string(),
+            "record 3, column 1 (\"code\"): \"ABCDEFGHIJKLMNOPQRSTUVWXYZ012345\" is not at most 3 \
+             characters"
+        );
+        assert!(matches!(
+            csv::parse_typed("id,code\n1,\n", &schema),
+            Err(csv::TypedError::Cell(csv::CellError {
+                record: 1,
+                constraint: csv::Constraint::NonEmpty,
+                ..
+            }))
+        ));
+        assert!(matches!(
+            csv::parse_typed("id\n1\n", &schema),
+            Err(csv::TypedError::MissingColumn(_))
+        ));
+    }
+
+    #[test]
+    fn csv_parse_interned() {
+        let input = "country,city\nRS,Beograd\nRS,\"Novi Sad\"\nHR,Zagreb\nRS,Niš\n";
+        let mut interner = csv::Interner::new();
+        let (rem_input, records) =
+            csv::parse_interned(input, &csv::Dialect::default(), &mut interner).unwrap();
+        assert_eq!(rem_input, "");
+        assert_eq!(
+            records
+                .iter()
+                .map(|record| record.iter().map(|field| &**field).collect::<Vec<_>>())
+                .collect::<Vec<_>>(),
+            csv::parse_string(input).unwrap().1
+        );
+        assert!(std::sync::Arc::ptr_eq(&records[1][0], &records[4][0]));
+        assert_eq!(interner.len(), 8);
+
+        let (_, more) =
+            csv::parse_interned("HR\nRS\n", &csv::Dialect::default(), &mut interner).unwrap();
+        assert!(std::sync::Arc::ptr_eq(&more[1][0], &records[1][0]));
+        assert_eq!(interner.len(), 8);
+        assert!(csv::parse_interned("a,b\nc\n", &csv::Dialect::default(), &mut interner).is_err());
+        assert!(csv::parse_interned("", &csv::Dialect::default(), &mut interner).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "bumpalo")]
+    fn csv_parse_in_arena() {
+        let mut bump = bumpalo::Bump::new();
+        let input = "id,name\n1,\"Ana \"\"A\"\"\"\n2,\"Bo, Jr.\"\n";
+        let (rem_input, records) =
+            csv::parse_in_arena(input, &csv::Dialect::default(), &bump).unwrap();
+        assert_eq!(rem_input, "");
+        assert_eq!(
+            records
+                .iter()
+                .map(|record| record.to_vec())
+                .collect::<Vec<_>>(),
+            csv::parse_string(input).unwrap().1
+        );
+        // Fields without escaped quotes are borrowed from the input
+        assert!(input
+            .as_bytes()
+            .as_ptr_range()
+            .contains(&records[2][1].as_ptr()));
+
+        let (_, records) =
+            csv::parse_in_arena("1\ta\\tb\t\\N\n", &csv::Dialect::mysql(), &bump).unwrap();
+        assert_eq!(records, &[&["1", "a\tb", "\\N"][..]]);
+        assert!(csv::parse_in_arena("a,b\nc\n", &csv::Dialect::default(), &bump).is_err());
+        bump.reset();
+    }
+
+    #[test]
+    fn csv_parse_collected() {
+        let input = "a,\"b,c\"\n1,2\n";
+        let (rem_input, records) =
+            csv::parse_collected::<Box<[String]>>(input, &csv::Dialect::default()).unwrap();
+        assert_eq!(rem_input, "");
+        assert_eq!(
+            records
+                .iter()
+                .map(|record| record.to_vec())
+                .collect::<Vec<_>>(),
+            csv::parse_string(input).unwrap().1
+        );
+        assert!(csv::parse_collected::<Vec<String>>("a,b\nc\n", &csv::Dialect::default()).is_err());
+        assert!(csv::parse_collected::<Vec<String>>("a,b\"c\n", &csv::Dialect::default()).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "smallvec")]
+    fn csv_parse_small() {
+        let (_, records) = csv::parse_small("a\tb\n1\t2\n", &csv::Dialect::mysql()).unwrap();
+        assert_eq!(records.len(), 2);
+        assert!(!records[1].spilled());
+        assert_eq!(records[1].as_slice(), ["1", "2"]);
+    }
+
+    #[test]
+    fn csv_parse_record_into() {
+        let dialect = csv::Dialect::default();
+        let mut record = vec!["old".to_string(); 4];
+        let (rem_input, ()) =
+            csv::parse_record_into("a,\"b \"\"c\"\"\",d\ne", &dialect, &mut record).unwrap();
+        assert_eq!(rem_input, "\ne");
+        assert_eq!(record, vec!["a", "b \"c\"", "d"]);
+
+        let input = "id,name\n1,Ana\n2,Bo\n";
+        let mut records = csv::records(input);
+        let mut names = Vec::new();
+        while records.read_into(&mut record).unwrap() {
+            names.push(record[1].clone());
+        }
+        assert_eq!(names, vec!["name", "Ana", "Bo"]);
+        let mut records = csv::records("a,b\nc\nd,e\n");
+        assert!(records.read_into(&mut record).unwrap());
+        assert!(records.read_into(&mut record).is_err());
+        assert!(!records.read_into(&mut record).unwrap());
+
+        let mut reader = csv::Reader::new(input.as_bytes());
+        let mut ids = Vec::new();
+        while reader.read_record_into(&mut record).unwrap() {
+            ids.push(record[0].clone());
+        }
+        assert_eq!(ids, vec!["id", "1", "2"]);
+    }
+
+    #[test]
+    fn repeat_into_collection() {
+        let mut numbers = Vec::with_capacity(8);
+        let number = || left_from_pair(number_i64, char(',').with_default(','));
+        for (line, expected) in [("1,2,3;", vec![1, 2, 3]), ("40;", vec![40])] {
+            numbers.clear();
+            let (rem_input, count) = number().repeat_into(&mut numbers).parse(line).unwrap();
+            assert_eq!((rem_input, count), (";", expected.len()));
+            assert_eq!(numbers, expected);
+        }
+
+        let mut distinct = std::collections::BTreeSet::new();
+        let (_, count) = number().repeat_into(&mut distinct).parse("3,1,3").unwrap();
+        assert_eq!(count, 3);
+        assert_eq!(distinct.into_iter().collect::<Vec<_>>(), vec![1, 3]);
+
+        let mut words = String::new();
+        let (rem_input, count) = take_while(|ch| ch != ' ')
+            .repeat_into(&mut words)
+            .parse("ab cd")
+            .unwrap();
+        assert_eq!((rem_input, count, words.as_str()), (" cd", 1, "ab"));
+
+        let mut fields = Vec::new();
+        let dialect = csv::Dialect::default();
+        let quoted = left_from_pair(csv::field_quoted(&dialect), char(',').with_default(','));
+        assert!(quoted.repeat_into(&mut fields).parse("\"a\",\"b").is_err());
+        assert_eq!(fields, vec!["a"]);
+    }
+
+    #[test]
+    fn thread_state_restored_after_panic() {
+        let panicking = |_: &str| -> PResult<&str, char> { panic!("parser panicked") };
+        let streaming = std::panic::catch_unwind(|| panicking.complete_or_partial().parse("x"));
+        assert!(streaming.is_err());
+        assert!(!is_streaming());
+
+        let nested = std::panic::catch_unwind(|| max_depth(1, panicking).parse("x"));
+        assert!(nested.is_err());
+        assert_eq!(max_depth(1, char('x')).parse("x"), Ok(("", 'x')));
+
+        let guarded = std::panic::catch_unwind(|| left_recursion_guard("p", panicking).parse("x"));
+        assert!(guarded.is_err());
+        assert_eq!(
+            left_recursion_guard("p", char('x')).parse("x"),
+            Ok(("", 'x'))
+        );
+    }
 }
@@ -0,0 +1,146 @@
+//! Capture of parser invocation trees for grammar visualization and profiling
+
+use crate::base::*;
+use crate::csv::json::write_string;
+use std::cell::RefCell;
+use std::fmt::{self, Write};
+use std::time::{Duration, Instant};
+
+/// Single recorded parser invocation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceNode {
+    pub name: &'static str,
+    /// Input consumed on success, or input examined up to the error position on failure
+    pub span: Span,
+    pub ok: bool,
+    pub elapsed: Duration,
+    pub children: Vec<TraceNode>,
+}
+
+/// Tree of parser invocations recorded by `capture`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Trace {
+    pub roots: Vec<TraceNode>,
+}
+
+thread_local! {
+    static STACK: RefCell<Option<Vec<Vec<TraceNode>>>> = const { RefCell::new(None) };
+}
+
+/// Runs the given function while recording invocations of named parsers (see `Parser::named`) on this thread
+pub fn capture<F, R>(f: F) -> (R, Trace)
+where
+    F: FnOnce() -> R,
+{
+    let mut outer = STACK.with(|stack| stack.replace(Some(vec![Vec::new()])));
+    let restore = OnDrop(move || {
+        STACK.with(|stack| stack.replace(outer.take()));
+    });
+    let result = f();
+    let mut frames = STACK
+        .with(|stack| stack.take())
+        .expect("trace stack is set during capture");
+    drop(restore);
+    let roots = frames.pop().unwrap_or_default();
+    (result, Trace { roots })
+}
+
+/// Applies the parsing function, recording the invocation under the given name if a trace is
+/// being captured on this thread
+pub(crate) fn record<I, O, F>(name: &'static str, input: I, parse: F) -> PResult<I, O>
+where
+    I: Input,
+    F: FnOnce(I) -> PResult<I, O>,
+{
+    let capturing = STACK.with(|stack| match stack.borrow_mut().as_mut() {
+        Some(frames) => {
+            frames.push(Vec::new());
+            true
+        }
+        None => false,
+    });
+    if !capturing {
+        return parse(input);
+    }
+
+    let len_before = input.input_len();
+    let start = Instant::now();
+    let result = parse(input);
+    let elapsed = start.elapsed();
+    let (len_after, ok) = match &result {
+        Ok((next_input, _)) => (next_input.input_len(), true),
+        Err(err) => (err.input.input_len(), false),
+    };
+
+    STACK.with(|stack| {
+        if let Some(frames) = stack.borrow_mut().as_mut() {
+            let children = frames.pop().unwrap_or_default();
+            let node = TraceNode {
+                name,
+                span: Span::new(len_before, len_after),
+                ok,
+                elapsed,
+                children,
+            };
+            if let Some(parent) = frames.last_mut() {
+                parent.push(node);
+            }
+        }
+    });
+    result
+}
+
+impl Trace {
+    /// Renders the trace as a JSON array of invocation trees, with spans as byte offsets
+    /// into the original input of the given length
+    pub fn to_json(&self, original_len: usize) -> String {
+        let mut json = String::new();
+        write_nodes(&self.roots, original_len, &mut json).expect("writing to a String cannot fail");
+        json
+    }
+
+    /// Renders the trace in the folded stacks format consumed by flamegraph tools, weighting
+    /// each stack by the nanoseconds spent in it (excluding named children)
+    pub fn to_folded(&self) -> String {
+        let mut folded = String::new();
+        for node in &self.roots {
+            fold(node, &mut Vec::new(), &mut folded);
+        }
+        folded
+    }
+}
+
+fn write_nodes<W: Write>(nodes: &[TraceNode], original_len: usize, out: &mut W) -> fmt::Result {
+    out.write_char('[')?;
+    for (i, node) in nodes.iter().enumerate() {
+        if i > 0 {
+            out.write_char(',')?;
+        }
+        let range = node.span.range(original_len);
+        out.write_str("{\"name\":")?;
+        write_string(node.name, out)?;
+        write!(
+            out,
+            ",\"start\":{},\"end\":{},\"ok\":{},\"nanos\":{},\"children\":",
+            range.start,
+            range.end,
+            node.ok,
+            node.elapsed.as_nanos()
+        )?;
+        write_nodes(&node.children, original_len, out)?;
+        out.write_char('}')?;
+    }
+    out.write_char(']')
+}
+
+fn fold<'a>(node: &'a TraceNode, stack: &mut Vec<&'a str>, out: &mut String) {
+    stack.push(node.name);
+    let children: Duration = node.children.iter().map(|child| child.elapsed).sum();
+    let own = node.elapsed.saturating_sub(children);
+    out.push_str(&stack.join(";"));
+    out.push_str(&format!(" {}\n", own.as_nanos()));
+    for child in &node.children {
+        fold(child, stack, out);
+    }
+    stack.pop();
+}
@@ -0,0 +1,55 @@
+//! Parser for URL-encoded data, like query strings and HTML form submissions
+//!
+//! Data is a list of `key=value` pairs separated by `&`, where `+` stands for a space and other
+//! special characters are percent-encoded.
+
+use crate::base::*;
+use crate::chars::*;
+use crate::combinators::*;
+
+/// Decoded key and value of a single pair
+pub type UrlPair = (String, String);
+
+fn decoded<'a>(text: &'a str, input: &'a str) -> PResult<&'a str, String> {
+    match percent_decode(text, true) {
+        Some(decoded) => Ok((input, decoded)),
+        None => Err(Error::failure(
+            text,
+            Reason::InvalidInput {
+                expected: "percent-encoded UTF-8 text",
+            },
+        )),
+    }
+}
+
+/// Parser that matches a single `key=value` pair, decoding both; a key without `=` has an
+/// empty value
+pub fn url_pair(input: &str) -> PResult<&str, UrlPair> {
+    pair(
+        take_while(|ch| ch != '&' && ch != '=')
+            .flat_map(|next_input, key| decoded(key, next_input)),
+        right_from_pair(char('='), take_while(|ch| ch != '&'))
+            .flat_map(|next_input, value| decoded(value, next_input))
+            .with_default(String::new()),
+    )
+    .parse(input)
+}
+
+/// Parses URL-encoded data into decoded pairs, in order of appearance
+///
+/// Empty pairs, like the one in `a=1&&b=2`, are skipped. A leading `?` is not part of the
+/// data and has to be stripped beforehand.
+pub fn parse(input: &str) -> PResult<&str, Vec<UrlPair>> {
+    let mut pairs = Vec::new();
+    let mut rem_input = input;
+    loop {
+        let (next_input, (key, value)) = url_pair(rem_input)?;
+        if !key.is_empty() || !value.is_empty() {
+            pairs.push((key, value));
+        }
+        match char('&')(next_input) {
+            Ok((next_input, _)) => rem_input = next_input,
+            Err(_) => return Ok((next_input, pairs)),
+        }
+    }
+}
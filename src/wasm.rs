@@ -0,0 +1,65 @@
+//! JavaScript bindings for CSV parsing in the browser
+//!
+//! Results are returned as JSON text, so they can be turned into plain objects with
+//! `JSON.parse` on the JavaScript side. A successful parse produces
+//! `{"records":[["id","name"],...]}`, while a failed one produces
+//! `{"error":{"offset":7,"excerpt":"...","message":"..."}}`.
+
+use crate::csv::{self, json::write_string, CsvRecord};
+use crate::Error;
+use std::fmt::Write;
+use wasm_bindgen::prelude::*;
+
+/// Parses a CSV document into a JSON description of its records or of the parse error
+#[wasm_bindgen(js_name = parseCsv)]
+pub fn parse_csv(input: &str) -> String {
+    let mut json = String::new();
+    match csv::parse_string(input) {
+        Ok((_, records)) => write_records(&records, &mut json),
+        Err(err) => write_error(&err, input.len(), &mut json),
+    }
+    .expect("writing to a String cannot fail");
+    json
+}
+
+/// Validates a CSV document, producing `{"records":N}` with the number of records on success
+/// and the same error object as `parseCsv` otherwise
+#[wasm_bindgen(js_name = validateCsv)]
+pub fn validate_csv(input: &str) -> String {
+    let mut json = String::new();
+    match csv::parse_string(input) {
+        Ok((_, records)) => write!(json, "{{\"records\":{}}}", records.len()),
+        Err(err) => write_error(&err, input.len(), &mut json),
+    }
+    .expect("writing to a String cannot fail");
+    json
+}
+
+fn write_records(records: &[CsvRecord], out: &mut String) -> std::fmt::Result {
+    out.push_str("{\"records\":[");
+    for (i, record) in records.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push('[');
+        for (j, field) in record.iter().enumerate() {
+            if j > 0 {
+                out.push(',');
+            }
+            write_string(field, out)?;
+        }
+        out.push(']');
+    }
+    out.push_str("]}");
+    Ok(())
+}
+
+fn write_error(err: &Error<&str>, original_len: usize, out: &mut String) -> std::fmt::Result {
+    let err = err.to_owned_error(original_len);
+    write!(out, "{{\"error\":{{\"offset\":{},\"excerpt\":", err.offset)?;
+    write_string(&err.excerpt, out)?;
+    out.push_str(",\"message\":");
+    write_string(&err.to_string(), out)?;
+    out.push_str("}}");
+    Ok(())
+}